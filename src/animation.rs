@@ -0,0 +1,301 @@
+use std::io;
+
+use crate::color::Color;
+use crate::core::matrix::{Decomposition, Matrix4};
+use crate::core::point::Point;
+use crate::core::quaternion::Quaternion;
+use crate::core::vector::Vector;
+use crate::render::{render, RenderSettings};
+use crate::scene::Scene;
+use crate::sequence::SequenceWriter;
+
+/// Values that can be blended between keyframes.
+pub trait Interpolate {
+    /// The value a fraction `t` of the way from `self` to `other`.
+    fn interpolate(&self, other: &Self, t: f64) -> Self;
+}
+
+impl Interpolate for f64 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self + (other - self) * t
+    }
+}
+
+impl Interpolate for Point {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Interpolate for Vector {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self + (*other - *self) * t
+    }
+}
+
+impl Interpolate for Color {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        *self * (1.0 - t) + *other * t
+    }
+}
+
+/// Turns at a constant speed, the short way round.
+impl Interpolate for Quaternion {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        self.slerp(*other, t)
+    }
+}
+
+/// Blends each part on its own, turning the rotation at a constant speed.
+impl Interpolate for Decomposition {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Decomposition {
+            translation: self.translation.interpolate(&other.translation, t),
+            rotation: self.rotation.interpolate(&other.rotation, t),
+            scale: self.scale.interpolate(&other.scale, t),
+            shear: [0, 1, 2].map(|i| self.shear[i].interpolate(&other.shear[i], t)),
+        }
+    }
+}
+
+/// Blends the decompositions of the matrices, so that rotations keep their size halfway through
+/// instead of shrinking as they would if the matrices were blended element by element.
+impl Interpolate for Matrix4 {
+    fn interpolate(&self, other: &Self, t: f64) -> Self {
+        Matrix4::compose(&self.decompose().interpolate(&other.decompose(), t))
+    }
+}
+
+/// How a value moves from one keyframe to the next.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum Interpolation {
+    /// At a constant speed.
+    #[default]
+    Linear,
+    /// Speeding up out of the first keyframe and slowing down into the next.
+    Ease,
+    /// Along a timing curve from (0, 0) to (1, 1) with the control points (x1, y1) and (x2, y2),
+    /// like `cubic-bezier` in CSS. The x coordinates have to be between 0 and 1.
+    Bezier(f64, f64, f64, f64),
+}
+
+impl Interpolation {
+    /// Maps the fraction of time `t` that has passed between two keyframes to the fraction of
+    /// the change in value.
+    pub fn apply(&self, t: f64) -> f64 {
+        match *self {
+            Interpolation::Linear => t,
+            Interpolation::Ease   => t * t * (3.0 - 2.0 * t),
+            Interpolation::Bezier(x1, y1, x2, y2) => {
+                // x(s) only increases, so the s that gives x(s) = t can be found by bisection.
+                let (mut low, mut high) = (0.0, 1.0);
+                for _ in 0..50 {
+                    let s = (low + high) / 2.0;
+                    match cubic_bezier(x1, x2, s) < t {
+                        true  => low = s,
+                        false => high = s,
+                    }
+                }
+                cubic_bezier(y1, y2, (low + high) / 2.0)
+            }
+        }
+    }
+}
+
+/// One coordinate of a cubic Bézier curve that starts at 0 and ends at 1.
+fn cubic_bezier(p1: f64, p2: f64, s: f64) -> f64 {
+    let r = 1.0 - s;
+    3.0 * r * r * s * p1 + 3.0 * r * s * s * p2 + s * s * s
+}
+
+/// A value at a point in time, and how to get from there to the next keyframe.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Keyframe<T> {
+    pub time: f64,
+    pub value: T,
+    pub interpolation: Interpolation,
+}
+
+impl<T> Keyframe<T> {
+    pub fn new(time: f64, value: T, interpolation: Interpolation) -> Self {
+        Self { time, value, interpolation, }
+    }
+}
+
+/// A value that changes over time, given by keyframes.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Track<T> {
+    keyframes: Vec<Keyframe<T>>,
+}
+
+impl<T: Interpolate + Clone> Track<T> {
+    /// A track through `keyframes`, which do not need to be in order. Panics if there are none.
+    pub fn new(mut keyframes: Vec<Keyframe<T>>) -> Self {
+        assert!(!keyframes.is_empty(), "a track needs at least one keyframe");
+        keyframes.sort_by(|a, b| a.time.total_cmp(&b.time));
+
+        Self { keyframes }
+    }
+
+    pub fn keyframes(&self) -> &[Keyframe<T>] {
+        &self.keyframes
+    }
+
+    /// The value at `time`. Before the first keyframe and after the last one, the value is held.
+    pub fn value_at(&self, time: f64) -> T {
+        let next = self.keyframes.partition_point(|k| k.time <= time);
+        match (self.keyframes.get(next.wrapping_sub(1)), self.keyframes.get(next)) {
+            (Some(from), Some(to)) => {
+                let t = (time - from.time) / (to.time - from.time);
+                from.value.interpolate(&to.value, from.interpolation.apply(t))
+            }
+            (Some(last), None)  => last.value.clone(),
+            (None, Some(first)) => first.value.clone(),
+            (None, None)        => unreachable!("a track has at least one keyframe"),
+        }
+    }
+}
+
+/// The frames of an animation from `start` up to but not including `end`, so that a clip that
+/// ends where it starts loops without repeating a frame.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frames {
+    pub start: f64,
+    pub end: f64,
+    pub fps: f64,
+}
+
+impl Frames {
+    pub fn new(start: f64, end: f64, fps: f64) -> Self {
+        Self { start, end, fps, }
+    }
+
+    pub fn count(&self) -> usize {
+        // Rounding errors should not add a frame right at the end.
+        ((self.end - self.start) * self.fps - 1e-9).ceil().max(0.0) as usize
+    }
+
+    pub fn time(&self, frame: usize) -> f64 {
+        self.start + frame as f64 / self.fps
+    }
+
+    pub fn times(&self) -> impl Iterator<Item = f64> + '_ {
+        (0..self.count()).map(|frame| self.time(frame))
+    }
+}
+
+/// Renders each of `frames` of the scene returned by `scene_at` for its time, and passes them to
+/// `writer`, which is finished after the last frame.
+pub fn render_frames<E>(
+    frames: &Frames,
+    settings: &RenderSettings,
+    writer: &mut dyn SequenceWriter,
+    mut scene_at: impl FnMut(f64) -> Result<Scene, E>,
+) -> Result<(), E>
+where
+    E: From<io::Error>,
+{
+    for time in frames.times() {
+        let scene = scene_at(time)?;
+        writer.write_frame(&render(scene.camera.as_ref(), &scene.world, settings))?;
+    }
+
+    Ok(writer.finish()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::float::ApproxEq;
+    use crate::core::tuple::Tuple;
+
+    use super::*;
+
+    #[test]
+    fn interpolating_between_keyframes() {
+        // Given
+        let track = Track::new(vec![
+            Keyframe::new(2.0, Point::new(4.0, 0.0, 0.0), Interpolation::Linear),
+            Keyframe::new(0.0, Point::new(0.0, 0.0, 0.0), Interpolation::Linear),
+            Keyframe::new(4.0, Point::new(4.0, 2.0, 0.0), Interpolation::Linear),
+        ]);
+
+        // Then
+        assert_eq!(track.value_at(-1.0), Point::new(0.0, 0.0, 0.0));
+        assert_eq!(track.value_at(0.5), Point::new(1.0, 0.0, 0.0));
+        assert_eq!(track.value_at(2.0), Point::new(4.0, 0.0, 0.0));
+        assert_eq!(track.value_at(3.0), Point::new(4.0, 1.0, 0.0));
+        assert_eq!(track.value_at(9.0), Point::new(4.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn easing_between_keyframes() {
+        // Given
+        let ease = Interpolation::Ease;
+        let bezier = Interpolation::Bezier(0.42, 0.0, 0.58, 1.0);
+        let linear_bezier = Interpolation::Bezier(0.25, 0.25, 0.75, 0.75);
+
+        // Then
+        assert_eq!(ease.apply(0.0), 0.0);
+        assert_eq!(ease.apply(0.5), 0.5);
+        assert_eq!(ease.apply(1.0), 1.0);
+        assert!(ease.apply(0.1) < 0.1);
+        assert!(ease.apply(0.9) > 0.9);
+        assert!(bezier.apply(0.5).approx_eq(0.5));
+        assert!(bezier.apply(0.2) < 0.2);
+        assert!(linear_bezier.apply(0.3).approx_eq(0.3));
+    }
+
+    #[test]
+    fn a_track_applies_the_interpolation_of_the_keyframe_it_leaves() {
+        // Given
+        let track = Track::new(vec![
+            Keyframe::new(0.0, 0.0, Interpolation::Ease),
+            Keyframe::new(1.0, 10.0, Interpolation::Linear),
+            Keyframe::new(2.0, 20.0, Interpolation::Linear),
+        ]);
+
+        // Then
+        assert!(track.value_at(0.25) < 2.5);
+        assert!(track.value_at(1.25).approx_eq(12.5));
+    }
+
+    #[test]
+    fn interpolating_colors_and_matrices() {
+        // Given
+        let black = Color::new(0.0, 0.0, 0.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+        let a = Matrix4::translation(0.0, 2.0, 0.0);
+        let b = Matrix4::translation(4.0, 2.0, 0.0) * Matrix4::scaling(3.0, 3.0, 3.0);
+        let c = Matrix4::translation(4.0, 2.0, 0.0) * Matrix4::rotation_z(2.0) * Matrix4::scaling(3.0, 3.0, 3.0);
+
+        // Then
+        assert_eq!(black.interpolate(&white, 0.25), Color::new(0.25, 0.25, 0.25));
+        assert_eq!(a.interpolate(&b, 0.5), Matrix4::translation(2.0, 2.0, 0.0) * Matrix4::scaling(2.0, 2.0, 2.0));
+        assert_eq!(a.interpolate(&c, 0.5), Matrix4::translation(2.0, 2.0, 0.0) * Matrix4::rotation_z(1.0) * Matrix4::scaling(2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn interpolating_rotations() {
+        // Given
+        let track = Track::new(vec![
+            Keyframe::new(0.0, Quaternion::identity(), Interpolation::Linear),
+            Keyframe::new(1.0, Quaternion::from_axis_angle(Vector::new(0.0, 1.0, 0.0), 2.0), Interpolation::Linear),
+        ]);
+
+        // Then
+        assert_eq!(track.value_at(0.25).to_matrix(), Matrix4::rotation_y(0.5));
+    }
+
+    #[test]
+    fn frames_end_before_the_end_time() {
+        // Given
+        let frames = Frames::new(1.0, 3.0, 24.0);
+
+        // Then
+        assert_eq!(frames.count(), 48);
+        assert_eq!(frames.times().next(), Some(1.0));
+        assert!(frames.times().last().unwrap().approx_eq(3.0 - 1.0 / 24.0));
+        assert_eq!(Frames::new(0.0, 0.1, 30.0).count(), 3);
+        assert_eq!(Frames::new(2.0, 1.0, 30.0).count(), 0);
+    }
+}
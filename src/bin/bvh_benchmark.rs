@@ -0,0 +1,89 @@
+use std::env;
+use std::f64::consts::PI;
+use std::time::Instant;
+
+use punytracer::bvh::Bvh;
+use punytracer::core::point::Point;
+use punytracer::core::tuple::Tuple;
+use punytracer::core::vector::Vector;
+use punytracer::intersection::hit;
+use punytracer::obj::ObjFile;
+use punytracer::ray::Ray;
+use punytracer::shape::Shape;
+
+const RAYS_PER_SIDE: usize = 100;
+
+/// A finely tessellated sphere, used when no OBJ file is passed on the command line.
+fn tessellated_sphere(segments: usize) -> String {
+    let mut obj = String::new();
+    let rings = segments / 2;
+
+    for ring in 0..=rings {
+        let theta = PI * ring as f64 / rings as f64;
+        for segment in 0..segments {
+            let phi = 2.0 * PI * segment as f64 / segments as f64;
+            obj += &format!("v {} {} {}\n", theta.sin() * phi.cos(), theta.cos(), theta.sin() * phi.sin());
+        }
+    }
+
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let a = ring * segments + segment + 1;
+            let b = ring * segments + (segment + 1) % segments + 1;
+            obj += &format!("f {} {} {} {}\n", a, b, b + segments, a + segments);
+        }
+    }
+
+    obj
+}
+
+fn main() {
+    let obj = match env::args().nth(1) {
+        Some(path) => ObjFile::load(&path).expect("Could not read OBJ file"),
+        None       => ObjFile::parse(&tessellated_sphere(128)),
+    };
+    let shapes: Vec<Box<dyn Shape>> = obj.into_shapes();
+    println!("Loaded {} triangles", shapes.len());
+
+    let start = Instant::now();
+    let bvh = Bvh::build(&shapes);
+    println!("Built BVH in {:?}", start.elapsed());
+
+    // Shoot a grid of rays from in front of the model straight through its bounding box.
+    let bounds = bvh.bounds();
+    let size = bounds.max - bounds.min;
+    let rays = (0..RAYS_PER_SIDE * RAYS_PER_SIDE)
+        .map(|i| {
+            let (u, v) = ((i % RAYS_PER_SIDE) as f64 + 0.5, (i / RAYS_PER_SIDE) as f64 + 0.5);
+            let origin = Point::new(
+                bounds.min.x() + size.x() * u / RAYS_PER_SIDE as f64,
+                bounds.min.y() + size.y() * v / RAYS_PER_SIDE as f64,
+                bounds.min.z() - size.z() - 1.0,
+            );
+            Ray::new(origin, Vector::new(0.0, 0.0, 1.0))
+        })
+        .collect::<Vec<_>>();
+
+    let start = Instant::now();
+    let brute_force = rays.iter()
+        .map(|r| {
+            let xs = shapes.iter().flat_map(|s| s.intersect(r)).collect::<Vec<_>>();
+            hit(&xs).map(|i| i.t)
+        })
+        .collect::<Vec<_>>();
+    let brute_force_time = start.elapsed();
+
+    let start = Instant::now();
+    let accelerated = rays.iter()
+        .map(|r| hit(&bvh.intersect(&shapes, r)).map(|i| i.t))
+        .collect::<Vec<_>>();
+    let accelerated_time = start.elapsed();
+
+    let hits = brute_force.iter().filter(|t| t.is_some()).count();
+    let mismatches = brute_force.iter().zip(&accelerated).filter(|(a, b)| a != b).count();
+
+    println!("Cast {} rays, {} of them hit the model", rays.len(), hits);
+    println!("Brute force: {:?}", brute_force_time);
+    println!("BVH:         {:?} ({:.1}x faster)", accelerated_time, brute_force_time.as_secs_f64() / accelerated_time.as_secs_f64());
+    println!("Mismatching hits: {}", mismatches);
+}
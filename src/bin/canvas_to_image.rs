@@ -1,7 +1,9 @@
+use std::io;
+
 use punytracer::canvas::Canvas;
 use punytracer::color::Color;
 
-fn main() {
+fn main() -> io::Result<()> {
     let mut canvas = Canvas::new(800, 600);
 
     for y in (0..600).step_by(10) {
@@ -10,5 +12,5 @@ fn main() {
         }
     }
 
-    canvas.save_to(&"traced.png");
+    canvas.save_to(&"traced.png")
 }
\ No newline at end of file
@@ -39,7 +39,7 @@ fn main() {
 
     while p.position.y() > 0.0 {
         p = e.tick(&p);
-        counter = counter + 1;
+        counter += 1;
         println!("Current position of the projectile: {:?}", &p.position);
     }
 
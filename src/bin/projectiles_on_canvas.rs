@@ -1,3 +1,5 @@
+use std::io;
+
 use punytracer::canvas::Canvas;
 use punytracer::color::Color;
 use punytracer::core::point::Point;
@@ -33,7 +35,7 @@ impl Environment {
     }
 }
 
-fn main() {
+fn main() -> io::Result<()> {
     let v = Vector::new(1.0, 1.8, 0.0).normalize() * 11.25;
     let g = Vector::new(0.0, -0.1, 0.0);
     let w = Vector::new(-0.01, 0.0, 0.0);
@@ -49,10 +51,10 @@ fn main() {
         let (x, y) = (p.position.x() as usize, (height as f64 - p.position.y()).floor() as usize);
         c[(x, y)] = Color::new(1.0, 0.0, 0.0);
         p = e.tick(&p);
-        counter = counter + 1;
+        counter += 1;
         println!("Current position of the projectile: {:?}", &p.position);
     }
 
     println!("It took {} ticks for the projectile to reach the ground!", counter);
-    c.save_to(&"projectile.png");
+    c.save_to(&"projectile.png")
 }
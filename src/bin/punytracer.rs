@@ -0,0 +1,264 @@
+use std::env;
+use std::error::Error;
+use std::fs::File;
+use std::io::{self, BufWriter};
+use std::path::Path;
+use std::process::ExitCode;
+use std::thread;
+use std::time::Instant;
+
+use punytracer::animation::{render_frames, Frames};
+use punytracer::integrator::mis::Strategy;
+use punytracer::integrator::path::PathTracer;
+use punytracer::integrator::photon::PhotonMap;
+use punytracer::integrator::Integrator;
+use punytracer::random;
+use punytracer::render::{render, RenderSettings};
+use punytracer::scene::Scene;
+use punytracer::sequence::apng::ApngWriter;
+use punytracer::sequence::gif::GifWriter;
+use punytracer::sequence::numbered::NumberedPngWriter;
+use punytracer::sequence::y4m::Y4mWriter;
+use punytracer::sequence::SequenceWriter;
+
+const USAGE: &str = "\
+Usage: punytracer render <scene.yml> [options]
+       punytracer animate <scene.yml> --end <seconds> [options]
+
+Options:
+  -o, --output <file>                 PNG file to write [default: out.png]
+                                      When animating, an .apng, .gif or .y4m file, - to stream
+                                      YUV4MPEG2 to stdout, or PNG files numbered by replacing a
+                                      run of # with the frame number [default: frame-####.png]
+  -r, --resolution <width>x<height>   Overrides the size set by the camera in the scene
+  -s, --samples <n>                   Rays per pixel [default: 1]
+  -t, --threads <n>                   Rendering threads [default: all cores]
+  -i, --integrator <name>             whitted for Phong shading, or path for path tracing
+                                      [default: whitted]
+      --depth <n>                     Bounces of a path before it's cut off [default: 16]
+      --mis <strategy>                How path tracing finds light from lights with an area:
+                                      light, bsdf, balance or power [default: power]
+      --spectral                      Path traces light of a few wavelengths at a time, which
+                                      materials with dispersion split into its colors
+      --photons <n>                   Photons sent out from the lights for path tracing to
+                                      find the caustics of mirrors and glass with
+      --photon-radius <r>             How far around a point photons are gathered from
+                                      [default: 0.1]
+      --seed <n>                      Seeds the random numbers so that renders repeat exactly
+
+Animation options:
+      --start <seconds>               Time of the first frame [default: 0]
+      --end <seconds>                 Time the animation ends, which gets no frame of its own
+      --fps <n>                       Frames per second [default: 24]";
+
+struct Options {
+    scene: String,
+    output: Option<String>,
+    resolution: Option<(usize, usize)>,
+    settings: RenderSettings,
+    /// The photons and gathering radius of the caustics, if any.
+    caustics: Option<(usize, f64)>,
+    /// The frames to render, if animating.
+    frames: Option<Frames>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Options, String> {
+    let animate = match args.next().as_deref() {
+        Some("render")  => false,
+        Some("animate") => true,
+        Some(command)   => return Err(format!("unknown command `{}`", command)),
+        None            => return Err("missing command".to_string()),
+    };
+
+    let mut options = Options {
+        scene: String::new(),
+        output: None,
+        resolution: None,
+        settings: RenderSettings {
+            samples: 1,
+            threads: thread::available_parallelism().map_or(1, |n| n.get()),
+            ..RenderSettings::default()
+        },
+        caustics: None,
+        frames: None,
+    };
+    let mut frames = Frames::new(0.0, f64::NAN, 24.0);
+    let mut integrator = "whitted".to_string();
+    let mut tracer = PathTracer::default();
+    let mut photons = None;
+    let mut photon_radius = 0.1;
+
+    while let Some(arg) = args.next() {
+        let mut value = || args.next().ok_or_else(|| format!("missing value for `{}`", arg));
+
+        match arg.as_str() {
+            "-o" | "--output"     => options.output = Some(value()?),
+            "-r" | "--resolution" => options.resolution = Some(parse_resolution(&value()?)?),
+            "-s" | "--samples"    => options.settings.samples = parse_count(&value()?)?,
+            "-t" | "--threads"    => options.settings.threads = parse_count(&value()?)?,
+            "-i" | "--integrator" => integrator = value()?,
+            "--depth"             => tracer.max_depth = parse_count(&value()?)?,
+            "--mis"               => tracer.strategy = parse_strategy(&value()?)?,
+            "--spectral"          => tracer.spectral = true,
+            "--photons"           => photons = Some(parse_count(&value()?)?),
+            "--photon-radius"     => photon_radius = parse_length(&value()?)?,
+            "--seed"              => options.settings.seed = Some(parse_seed(&value()?)?),
+            "--start" if animate  => frames.start = parse_time(&value()?)?,
+            "--end" if animate    => frames.end = parse_time(&value()?)?,
+            "--fps" if animate    => frames.fps = parse_time(&value()?)?,
+            a if a.starts_with('-') => return Err(format!("unknown option `{}`", a)),
+            _ if options.scene.is_empty() => options.scene = arg,
+            _ => return Err(format!("unexpected argument `{}`", arg)),
+        }
+    }
+
+    if options.scene.is_empty() {
+        return Err("missing scene file".to_string());
+    }
+    options.settings.integrator = match integrator.as_str() {
+        "whitted" => Integrator::Whitted,
+        "path"    => Integrator::Path(tracer),
+        name      => return Err(format!("unknown integrator `{}`", name)),
+    };
+    if tracer.spectral && options.settings.integrator == Integrator::Whitted {
+        return Err("spectral mode only works with path tracing".to_string());
+    }
+    if let Some(photons) = photons {
+        if options.settings.integrator == Integrator::Whitted {
+            return Err("photons only work with path tracing".to_string());
+        }
+        options.caustics = Some((photons, photon_radius));
+    }
+    if animate {
+        if frames.end.is_nan() {
+            return Err("missing `--end`".to_string());
+        }
+        if frames.fps <= 0.0 {
+            return Err("the frame rate has to be positive".to_string());
+        }
+        options.frames = Some(frames);
+    }
+
+    Ok(options)
+}
+
+fn parse_resolution(value: &str) -> Result<(usize, usize), String> {
+    let (width, height) = value.split_once('x')
+        .ok_or_else(|| format!("expected a resolution like 800x600, found `{}`", value))?;
+
+    Ok((parse_count(width)?, parse_count(height)?))
+}
+
+fn parse_count(value: &str) -> Result<usize, String> {
+    match value.parse::<usize>() {
+        Ok(n) if n > 0 => Ok(n),
+        _              => Err(format!("expected a positive number, found `{}`", value)),
+    }
+}
+
+fn parse_strategy(value: &str) -> Result<Strategy, String> {
+    match value {
+        "light"   => Ok(Strategy::Light),
+        "bsdf"    => Ok(Strategy::Bsdf),
+        "balance" => Ok(Strategy::Balance),
+        "power"   => Ok(Strategy::Power),
+        _         => Err(format!("unknown strategy `{}`", value)),
+    }
+}
+
+fn parse_length(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(n) if n.is_finite() && n > 0.0 => Ok(n),
+        _                                 => Err(format!("expected a positive number, found `{}`", value)),
+    }
+}
+
+fn parse_seed(value: &str) -> Result<u64, String> {
+    value.parse::<u64>().map_err(|_| format!("expected a whole number, found `{}`", value))
+}
+
+fn parse_time(value: &str) -> Result<f64, String> {
+    match value.parse::<f64>() {
+        Ok(n) if n.is_finite() => Ok(n),
+        _                      => Err(format!("expected a number, found `{}`", value)),
+    }
+}
+
+/// The writer for the frames of an animation, chosen by the extension of `output`.
+fn sequence_writer(output: &str, frames: &Frames) -> io::Result<Box<dyn SequenceWriter>> {
+    if output == "-" {
+        return Ok(Box::new(Y4mWriter::new(BufWriter::new(io::stdout().lock()), frames.fps)));
+    }
+
+    let file = || File::create(output).map(BufWriter::new);
+    Ok(match Path::new(output).extension().and_then(|e| e.to_str()) {
+        Some("apng") => Box::new(ApngWriter::new(file()?, frames.count() as u32, frames.fps)),
+        Some("gif")  => Box::new(GifWriter::new(file()?, frames.fps)),
+        Some("y4m")  => Box::new(Y4mWriter::new(file()?, frames.fps)),
+        _            => Box::new(NumberedPngWriter::new(output)),
+    })
+}
+
+fn main() -> ExitCode {
+    let options = match parse_args(env::args().skip(1)) {
+        Ok(options) => options,
+        Err(error)  => {
+            eprintln!("error: {}\n\n{}", error, USAGE);
+            return ExitCode::from(2);
+        }
+    };
+
+    let load = |time| -> Result<Scene, Box<dyn Error>> {
+        let mut scene = Scene::load_at(&options.scene, time)?;
+        if let Some((width, height)) = options.resolution {
+            scene.camera.resize(width, height);
+        }
+        if let Some((photons, radius)) = options.caustics {
+            if let Some(seed) = options.settings.seed {
+                random::seed(seed);
+            }
+            scene.world.caustics = Some(PhotonMap::trace(&scene.world, photons, radius));
+        }
+        Ok(scene)
+    };
+
+    let start = Instant::now();
+    let result = match &options.frames {
+        Some(frames) => {
+            let output = options.output.as_deref().unwrap_or("frame-####.png");
+            sequence_writer(output, frames)
+                .map_err(Box::from)
+                .and_then(|mut writer| render_frames(frames, &options.settings, writer.as_mut(), load))
+                .map(|()| {
+                    // Keep stdout clean when the frames are streamed to it.
+                    let message = format!(
+                        "Rendered {} frames with {} samples on {} threads in {:?} to {}",
+                        frames.count(), options.settings.samples, options.settings.threads, start.elapsed(), output,
+                    );
+                    match output {
+                        "-" => eprintln!("{}", message),
+                        _   => println!("{}", message),
+                    }
+                })
+        }
+        None => load(0.0).and_then(|scene| {
+            let output = options.output.as_deref().unwrap_or("out.png");
+            let image = render(scene.camera.as_ref(), &scene.world, &options.settings);
+            image.save_to(&output).map_err(|e| format!("could not write {}: {}", output, e))?;
+
+            println!(
+                "Rendered {}x{} pixels with {} samples on {} threads in {:?} to {}",
+                scene.camera.hsize(), scene.camera.vsize(), options.settings.samples, options.settings.threads, start.elapsed(), output,
+            );
+            Ok(())
+        }),
+    };
+
+    match result {
+        Ok(())     => ExitCode::SUCCESS,
+        Err(error) => {
+            eprintln!("error: {}", error);
+            ExitCode::FAILURE
+        }
+    }
+}
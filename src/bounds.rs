@@ -0,0 +1,386 @@
+use crate::core::matrix::Matrix4;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::ray::Ray;
+
+/// An axis-aligned bounding box. A box without any points in it is empty, with its minimum at
+/// positive infinity and its maximum at negative infinity, so adding points to it or merging it
+/// with other boxes just works.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BoundingBox {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl BoundingBox {
+    pub fn new(min: Point, max: Point) -> Self {
+        Self { min, max, }
+    }
+
+    pub fn empty() -> Self {
+        Self::new(
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+        )
+    }
+
+    /// A box that contains all of space, for shapes such as planes that have no bounds.
+    pub fn infinite() -> Self {
+        Self::new(
+            Point::new(f64::NEG_INFINITY, f64::NEG_INFINITY, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, f64::INFINITY, f64::INFINITY),
+        )
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.min.x() > self.max.x() || self.min.y() > self.max.y() || self.min.z() > self.max.z()
+    }
+
+    pub fn is_finite(&self) -> bool {
+        [self.min.x(), self.min.y(), self.min.z(), self.max.x(), self.max.y(), self.max.z()]
+            .iter()
+            .all(|c| c.is_finite())
+    }
+
+    pub fn add_point(&mut self, point: Point) {
+        self.min = Point::new(self.min.x().min(point.x()), self.min.y().min(point.y()), self.min.z().min(point.z()));
+        self.max = Point::new(self.max.x().max(point.x()), self.max.y().max(point.y()), self.max.z().max(point.z()));
+    }
+
+    pub fn merge(&self, other: &BoundingBox) -> Self {
+        let mut merged = *self;
+        merged.add_point(other.min);
+        merged.add_point(other.max);
+
+        merged
+    }
+
+    /// The region covered by both boxes, which is empty if they do not overlap.
+    pub fn overlap(&self, other: &BoundingBox) -> Self {
+        let overlap = Self::new(
+            Point::new(self.min.x().max(other.min.x()), self.min.y().max(other.min.y()), self.min.z().max(other.min.z())),
+            Point::new(self.max.x().min(other.max.x()), self.max.y().min(other.max.y()), self.max.z().min(other.max.z())),
+        );
+
+        match overlap.is_empty() {
+            true  => Self::empty(),
+            false => overlap,
+        }
+    }
+
+    pub fn contains_point(&self, point: Point) -> bool {
+        (self.min.x()..=self.max.x()).contains(&point.x())
+            && (self.min.y()..=self.max.y()).contains(&point.y())
+            && (self.min.z()..=self.max.z()).contains(&point.z())
+    }
+
+    pub fn contains_box(&self, other: &BoundingBox) -> bool {
+        self.contains_point(other.min) && self.contains_point(other.max)
+    }
+
+    pub fn centroid(&self) -> Point {
+        Point::new(
+            (self.min.x() + self.max.x()) / 2.0,
+            (self.min.y() + self.max.y()) / 2.0,
+            (self.min.z() + self.max.z()) / 2.0,
+        )
+    }
+
+    pub fn surface_area(&self) -> f64 {
+        if self.is_empty() {
+            return 0.0;
+        }
+
+        let d = self.max - self.min;
+        2.0 * (d.x() * d.y() + d.y() * d.z() + d.z() * d.x())
+    }
+
+    /// The axis (0 for x, 1 for y, 2 for z) along which the box is the longest.
+    pub fn longest_axis(&self) -> usize {
+        let d = self.max - self.min;
+
+        match (d.x(), d.y(), d.z()) {
+            (x, y, z) if x >= y && x >= z => 0,
+            (_, y, z) if y >= z           => 1,
+            _                             => 2,
+        }
+    }
+
+    /// The box in the space of `transform`, large enough to contain all eight transformed
+    /// corners of this box. Boxes extending into infinity stay infinite.
+    pub fn transform(&self, transform: Matrix4) -> Self {
+        if self.is_empty() {
+            return *self;
+        }
+        if !self.is_finite() {
+            return Self::infinite();
+        }
+
+        let mut transformed = Self::empty();
+        for corner in self.corners() {
+            transformed.add_point(transform * corner);
+        }
+
+        transformed
+    }
+
+    /// The box in the space of a transform that changes over time, large enough to contain the
+    /// box wherever it goes from time 0.0 to 1.0. The motion is followed in steps, with room for
+    /// the paths of the corners to curve away in between.
+    pub fn sweep(&self, transform_at: impl Fn(f64) -> Matrix4) -> Self {
+        const STEPS: usize = 32;
+
+        if self.is_empty() || !self.is_finite() {
+            return self.transform(transform_at(0.0));
+        }
+
+        let corners = self.corners();
+        let mut previous = corners.map(|c| transform_at(0.0) * c);
+        let mut swept = Self::empty();
+        let mut step_length = 0.0_f64;
+        for corner in previous {
+            swept.add_point(corner);
+        }
+        for i in 1..=STEPS {
+            let transform = transform_at(i as f64 / STEPS as f64);
+            for (corner, previous) in corners.iter().zip(previous.iter_mut()) {
+                let moved = transform * *corner;
+                step_length = step_length.max((moved - *previous).magnitude());
+                swept.add_point(moved);
+                *previous = moved;
+            }
+        }
+
+        // Between two steps, a corner stays within one step length of where it was.
+        let margin = Vector::new(step_length, step_length, step_length);
+        Self::new(swept.min - margin, swept.max + margin)
+    }
+
+    fn corners(&self) -> [Point; 8] {
+        let (min, max) = (self.min, self.max);
+
+        [
+            min,
+            Point::new(min.x(), min.y(), max.z()),
+            Point::new(min.x(), max.y(), min.z()),
+            Point::new(min.x(), max.y(), max.z()),
+            Point::new(max.x(), min.y(), min.z()),
+            Point::new(max.x(), min.y(), max.z()),
+            Point::new(max.x(), max.y(), min.z()),
+            max,
+        ]
+    }
+
+    /// Whether `ray` passes through the box, using the slab method.
+    pub fn intersects(&self, ray: &Ray) -> bool {
+        match self.intersection_range(ray) {
+            Some((tmin, tmax)) => tmax >= 0.0 && tmin <= tmax,
+            None               => false,
+        }
+    }
+
+    /// The range of `t` over which `ray` is inside the box, if it hits the box at all.
+    pub fn intersection_range(&self, ray: &Ray) -> Option<(f64, f64)> {
+        if self.is_empty() {
+            return None;
+        }
+
+        let (xmin, xmax) = check_axis(ray.origin.x(), ray.direction.x(), self.min.x(), self.max.x());
+        let (ymin, ymax) = check_axis(ray.origin.y(), ray.direction.y(), self.min.y(), self.max.y());
+        let (zmin, zmax) = check_axis(ray.origin.z(), ray.direction.z(), self.min.z(), self.max.z());
+
+        let tmin = xmin.max(ymin).max(zmin);
+        let tmax = xmax.min(ymax).min(zmax);
+
+        match tmin <= tmax {
+            true  => Some((tmin, tmax)),
+            false => None,
+        }
+    }
+}
+
+fn check_axis(origin: f64, direction: f64, min: f64, max: f64) -> (f64, f64) {
+    let tmin = (min - origin) / direction;
+    let tmax = (max - origin) / direction;
+
+    // An origin exactly on a slab of a box parallel to the ray gives 0 / 0, which counts as inside.
+    let (tmin, tmax) = match (tmin.is_nan(), tmax.is_nan()) {
+        (false, false) => (tmin, tmax),
+        _              => (f64::NEG_INFINITY, f64::INFINITY),
+    };
+
+    match tmin > tmax {
+        true  => (tmax, tmin),
+        false => (tmin, tmax),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI, SQRT_2};
+
+    use crate::core::vector::Vector;
+
+    use super::*;
+
+    #[test]
+    fn creating_an_empty_bounding_box() {
+        // Given
+        let b = BoundingBox::empty();
+
+        // Then
+        assert!(b.is_empty());
+        assert_eq!(b.surface_area(), 0.0);
+    }
+
+    #[test]
+    fn adding_points_to_an_empty_bounding_box() {
+        // Given
+        let mut b = BoundingBox::empty();
+
+        // When
+        b.add_point(Point::new(-5.0, 2.0, 0.0));
+        b.add_point(Point::new(7.0, 0.0, -3.0));
+
+        // Then
+        assert_eq!(b.min, Point::new(-5.0, 0.0, -3.0));
+        assert_eq!(b.max, Point::new(7.0, 2.0, 0.0));
+    }
+
+    #[test]
+    fn merging_two_bounding_boxes() {
+        // Given
+        let b1 = BoundingBox::new(Point::new(-5.0, -2.0, 0.0), Point::new(7.0, 4.0, 4.0));
+        let b2 = BoundingBox::new(Point::new(8.0, -7.0, -2.0), Point::new(14.0, 2.0, 8.0));
+
+        // When
+        let merged = b1.merge(&b2);
+
+        // Then
+        assert_eq!(merged.min, Point::new(-5.0, -7.0, -2.0));
+        assert_eq!(merged.max, Point::new(14.0, 4.0, 8.0));
+    }
+
+    #[test]
+    fn overlap_of_two_bounding_boxes() {
+        // Given
+        let b1 = BoundingBox::new(Point::new(-5.0, -2.0, 0.0), Point::new(7.0, 4.0, 4.0));
+        let b2 = BoundingBox::new(Point::new(3.0, -7.0, -2.0), Point::new(14.0, 2.0, 8.0));
+        let b3 = BoundingBox::new(Point::new(8.0, -7.0, -2.0), Point::new(14.0, 2.0, 8.0));
+
+        // When
+        let overlap = b1.overlap(&b2);
+
+        // Then
+        assert_eq!(overlap.min, Point::new(3.0, -2.0, 0.0));
+        assert_eq!(overlap.max, Point::new(7.0, 2.0, 4.0));
+        assert!(b1.overlap(&b3).is_empty());
+        assert_eq!(BoundingBox::infinite().overlap(&b1), b1);
+    }
+
+    #[test]
+    fn checking_whether_a_box_contains_a_point() {
+        // Given
+        let b = BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+
+        // Then
+        assert!(b.contains_point(Point::new(5.0, -2.0, 0.0)));
+        assert!(b.contains_point(Point::new(11.0, 4.0, 7.0)));
+        assert!(b.contains_point(Point::new(8.0, 1.0, 3.0)));
+        assert!(!b.contains_point(Point::new(3.0, 0.0, 3.0)));
+        assert!(!b.contains_point(Point::new(8.0, -4.0, 3.0)));
+        assert!(!b.contains_point(Point::new(8.0, 1.0, -1.0)));
+        assert!(!b.contains_point(Point::new(13.0, 1.0, 3.0)));
+        assert!(!b.contains_point(Point::new(8.0, 5.0, 3.0)));
+        assert!(!b.contains_point(Point::new(8.0, 1.0, 8.0)));
+    }
+
+    #[test]
+    fn checking_whether_a_box_contains_another_box() {
+        // Given
+        let b = BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0));
+
+        // Then
+        assert!(b.contains_box(&BoundingBox::new(Point::new(5.0, -2.0, 0.0), Point::new(11.0, 4.0, 7.0))));
+        assert!(b.contains_box(&BoundingBox::new(Point::new(6.0, -1.0, 1.0), Point::new(10.0, 3.0, 6.0))));
+        assert!(!b.contains_box(&BoundingBox::new(Point::new(4.0, -3.0, -1.0), Point::new(10.0, 3.0, 6.0))));
+        assert!(!b.contains_box(&BoundingBox::new(Point::new(6.0, -1.0, 1.0), Point::new(12.0, 5.0, 8.0))));
+    }
+
+    #[test]
+    fn transforming_a_bounding_box() {
+        // Given
+        let b = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let m = Matrix4::rotation_x(PI / 4.0) * Matrix4::rotation_y(PI / 4.0);
+
+        // When
+        let b2 = b.transform(m);
+
+        // Then
+        assert_eq!(b2.min, Point::new(-SQRT_2, -1.0 - FRAC_1_SQRT_2, -1.0 - FRAC_1_SQRT_2));
+        assert_eq!(b2.max, Point::new(SQRT_2, 1.0 + FRAC_1_SQRT_2, 1.0 + FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn transforming_an_infinite_bounding_box() {
+        // Given
+        let b = BoundingBox::infinite();
+
+        // When
+        let b2 = b.transform(Matrix4::translation(1.0, 2.0, 3.0));
+
+        // Then
+        assert!(!b2.is_finite());
+        assert_eq!(b2.min.x(), f64::NEG_INFINITY);
+        assert_eq!(b2.max.z(), f64::INFINITY);
+    }
+
+    #[test]
+    fn surface_area_and_longest_axis() {
+        // Given
+        let b = BoundingBox::new(Point::new(0.0, 0.0, 0.0), Point::new(1.0, 2.0, 3.0));
+
+        // Then
+        assert_eq!(b.surface_area(), 22.0);
+        assert_eq!(b.longest_axis(), 2);
+        assert_eq!(b.centroid(), Point::new(0.5, 1.0, 1.5));
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_bounding_box_at_the_origin() {
+        // Given
+        let b = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let cases = [
+            (Point::new(5.0, 0.5, 0.0), Vector::new(-1.0, 0.0, 0.0), true),
+            (Point::new(-5.0, 0.5, 0.0), Vector::new(1.0, 0.0, 0.0), true),
+            (Point::new(0.5, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0), true),
+            (Point::new(0.5, -5.0, 0.0), Vector::new(0.0, 1.0, 0.0), true),
+            (Point::new(0.5, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0), true),
+            (Point::new(0.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), true),
+            (Point::new(0.0, 0.5, 0.0), Vector::new(0.0, 0.0, 1.0), true),
+            (Point::new(-2.0, 0.0, 0.0), Vector::new(2.0, 4.0, 6.0), false),
+            (Point::new(0.0, -2.0, 0.0), Vector::new(6.0, 2.0, 4.0), false),
+            (Point::new(0.0, 0.0, -2.0), Vector::new(4.0, 6.0, 2.0), false),
+            (Point::new(2.0, 0.0, 2.0), Vector::new(0.0, 0.0, -1.0), false),
+            (Point::new(0.0, 2.0, 2.0), Vector::new(0.0, -1.0, 0.0), false),
+            (Point::new(2.0, 2.0, 0.0), Vector::new(-1.0, 0.0, 0.0), false),
+        ];
+
+        // Then
+        for (origin, direction, expected) in cases {
+            let r = Ray::new(origin, direction.normalize());
+            assert_eq!(b.intersects(&r), expected, "{:?}", r);
+        }
+    }
+
+    #[test]
+    fn ray_pointing_away_from_a_bounding_box_misses() {
+        // Given
+        let b = BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, -1.0));
+
+        // Then
+        assert!(!b.intersects(&r));
+    }
+}
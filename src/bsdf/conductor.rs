@@ -0,0 +1,84 @@
+use crate::bsdf::fresnel;
+use crate::bsdf::microfacet::Ggx;
+use crate::bsdf::{same_hemisphere, BsdfSample};
+use crate::color::Color;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+
+/// A metal, which reflects light with the Fresnel reflectance of its complex index of refraction
+/// `eta + i k`, given for each channel. A `roughness` of 0 makes it a perfect mirror.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conductor {
+    pub eta: Color,
+    pub k: Color,
+    pub roughness: f64,
+}
+
+impl Conductor {
+    pub fn new(eta: Color, k: Color, roughness: f64) -> Self {
+        Self { eta, k, roughness }
+    }
+
+    /// Gold, with the index of refraction at the wavelengths of red, green and blue light.
+    pub fn gold(roughness: f64) -> Self {
+        Self::new(Color::new(0.143, 0.374, 1.442), Color::new(3.983, 2.385, 1.603), roughness)
+    }
+
+    /// Copper, with the index of refraction at the wavelengths of red, green and blue light.
+    pub fn copper(roughness: f64) -> Self {
+        Self::new(Color::new(0.200, 0.924, 1.102), Color::new(3.912, 2.452, 2.142), roughness)
+    }
+
+    /// Aluminium, with the index of refraction at the wavelengths of red, green and blue light.
+    pub fn aluminium(roughness: f64) -> Self {
+        Self::new(Color::new(1.657, 0.880, 0.521), Color::new(9.224, 6.270, 4.837), roughness)
+    }
+
+    fn distribution(&self) -> Ggx {
+        Ggx::from_roughness(self.roughness)
+    }
+
+    pub fn is_specular(&self) -> bool {
+        self.distribution().is_smooth()
+    }
+
+    pub fn evaluate(&self, wo: Vector, wi: Vector) -> Color {
+        let ggx = self.distribution();
+        if ggx.is_smooth() || !same_hemisphere(wo, wi) || wo.z() == 0.0 || wi.z() == 0.0 {
+            return Color::default();
+        }
+        let m = (wo + wi).normalize();
+        let m = m * m.z().signum();
+
+        fresnel::conductor(wo.dot(m).abs(), self.eta, self.k) * (ggx.d(m) * ggx.g(wo, wi) / (4.0 * wo.z() * wi.z()).abs())
+    }
+
+    pub fn sample(&self, wo: Vector, u: f64, v: f64) -> Option<BsdfSample> {
+        let ggx = self.distribution();
+        if ggx.is_smooth() {
+            let wi = Vector::new(-wo.x(), -wo.y(), wo.z());
+            let f = fresnel::conductor(wo.z().abs(), self.eta, self.k);
+            return BsdfSample::specular(wi, f, 1.0);
+        }
+
+        let m = ggx.sample_visible_normal(wo, u, v);
+        let wi = (-wo).reflect(m);
+        if !same_hemisphere(wo, wi) {
+            return None;
+        }
+
+        BsdfSample::new(wi, self.evaluate(wo, wi), self.pdf(wo, wi))
+    }
+
+    pub fn pdf(&self, wo: Vector, wi: Vector) -> f64 {
+        let ggx = self.distribution();
+        if ggx.is_smooth() || !same_hemisphere(wo, wi) {
+            return 0.0;
+        }
+        let m = (wo + wi).normalize();
+        let m = m * m.z().signum();
+
+        ggx.visible_normal_pdf(wo, m) / (4.0 * wo.dot(m).abs())
+    }
+}
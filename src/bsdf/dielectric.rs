@@ -0,0 +1,190 @@
+use crate::bsdf::fresnel;
+use crate::bsdf::microfacet::Ggx;
+use crate::bsdf::{refract, same_hemisphere, BsdfSample};
+use crate::color::Color;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+
+/// A transparent material like glass or water, which reflects and refracts light in the
+/// proportions given by the Fresnel equations for its index of refraction `ior`. The outside is
+/// the side the normal points to, which is assumed to be air. A `roughness` of 0 makes the
+/// surface perfectly smooth, larger values give frosted glass.
+///
+/// Radiance is scaled by the change in solid angle as it crosses the boundary, which makes the
+/// BSDF reciprocal only in the generalized sense: `f(wo, wi) / η_o² = f(wi, wo) / η_i²`, with
+/// the indices of the sides `wo` and `wi` are on.
+///
+/// With a `dispersion`, the index changes with the wavelength of the light, which splits white
+/// light into its colors in spectral mode. Otherwise `ior` is its value for yellow light.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Dielectric {
+    pub ior: f64,
+    pub roughness: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub dispersion: Option<Dispersion>,
+}
+
+impl Dielectric {
+    pub fn new(ior: f64, roughness: f64) -> Self {
+        Self { ior, roughness, dispersion: None, }
+    }
+
+    pub fn with_dispersion(dispersion: Dispersion, roughness: f64) -> Self {
+        Self { ior: dispersion.ior(Dispersion::YELLOW), roughness, dispersion: Some(dispersion), }
+    }
+
+    /// The material for light of the wavelength `lambda` in nm.
+    pub fn at(&self, lambda: f64) -> Self {
+        match self.dispersion {
+            Some(dispersion) => Self { ior: dispersion.ior(lambda), ..*self },
+            None             => *self,
+        }
+    }
+
+    fn distribution(&self) -> Ggx {
+        Ggx::from_roughness(self.roughness)
+    }
+
+    pub fn is_specular(&self) -> bool {
+        self.ior == 1.0 || self.distribution().is_smooth()
+    }
+
+    /// The normal of the facet that scatters `wo` into `wi`, facing outwards, with the index of
+    /// the side of `wi` relative to that of `wo`. None for facets seen from behind.
+    fn facet(&self, wo: Vector, wi: Vector) -> Option<(Vector, f64)> {
+        let (cos_o, cos_i) = (wo.z(), wi.z());
+        if cos_o == 0.0 || cos_i == 0.0 {
+            return None;
+        }
+        let eta = match (same_hemisphere(wo, wi), cos_o > 0.0) {
+            (true, _)      => 1.0,
+            (false, true)  => self.ior,
+            (false, false) => 1.0 / self.ior,
+        };
+
+        let m = wi * eta + wo;
+        if m.magnitude() == 0.0 {
+            return None;
+        }
+        let m = m.normalize();
+        let m = m * m.z().signum();
+        if m.dot(wi) * cos_i < 0.0 || m.dot(wo) * cos_o < 0.0 {
+            return None;
+        }
+
+        Some((m, eta))
+    }
+
+    pub fn evaluate(&self, wo: Vector, wi: Vector) -> Color {
+        if self.is_specular() {
+            return Color::default();
+        }
+        let Some((m, eta)) = self.facet(wo, wi) else { return Color::default() };
+        let ggx = self.distribution();
+        let f = fresnel::dielectric(wo.dot(m), self.ior);
+        let (cos_o, cos_i) = (wo.z(), wi.z());
+
+        let value = match same_hemisphere(wo, wi) {
+            true  => ggx.d(m) * ggx.g(wo, wi) * f / (4.0 * cos_i * cos_o).abs(),
+            false => {
+                let denominator = (wi.dot(m) + wo.dot(m) / eta).powi(2) * cos_i * cos_o;
+                ggx.d(m) * ggx.g(wo, wi) * (1.0 - f) * (wi.dot(m) * wo.dot(m) / denominator).abs() / (eta * eta)
+            }
+        };
+
+        Color::new(value, value, value)
+    }
+
+    pub fn sample(&self, wo: Vector, choice: f64, u: f64, v: f64) -> Option<BsdfSample> {
+        if self.is_specular() {
+            let r = fresnel::dielectric(wo.z(), self.ior);
+            return match choice < r {
+                true  => BsdfSample::specular(Vector::new(-wo.x(), -wo.y(), wo.z()), Color::new(r, r, r), r),
+                false => {
+                    let (wi, eta) = refract(wo, Vector::new(0.0, 0.0, 1.0), self.ior)?;
+                    let t = (1.0 - r) / (eta * eta);
+                    BsdfSample::specular(wi, Color::new(t, t, t), 1.0 - r)
+                }
+            };
+        }
+
+        let m = self.distribution().sample_visible_normal(wo, u, v);
+        let r = fresnel::dielectric(wo.dot(m), self.ior);
+        let wi = match choice < r {
+            true  => Some((-wo).reflect(m)).filter(|&wi| same_hemisphere(wo, wi)),
+            false => refract(wo, m, self.ior).map(|(wi, _)| wi).filter(|&wi| !same_hemisphere(wo, wi)),
+        }?;
+
+        BsdfSample::new(wi, self.evaluate(wo, wi), self.pdf(wo, wi))
+    }
+
+    pub fn pdf(&self, wo: Vector, wi: Vector) -> f64 {
+        if self.is_specular() {
+            return 0.0;
+        }
+        let Some((m, eta)) = self.facet(wo, wi) else { return 0.0 };
+        let visible = self.distribution().visible_normal_pdf(wo, m);
+        let r = fresnel::dielectric(wo.dot(m), self.ior);
+
+        match same_hemisphere(wo, wi) {
+            true  => visible / (4.0 * wo.dot(m).abs()) * r,
+            false => visible * wi.dot(m).abs() / (wi.dot(m) + wo.dot(m) / eta).powi(2) * (1.0 - r),
+        }
+    }
+}
+
+/// How the index of refraction of a material changes with the wavelength of light, with the
+/// wavelengths in µm.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Dispersion {
+    /// Cauchy's equation `n = a + b / λ²`, which holds well enough for glass in visible light.
+    Cauchy { a: f64, b: f64 },
+    /// The Sellmeier equation `n² = 1 + Σ bᵢ λ² / (λ² - cᵢ)`, which glass makers give the
+    /// coefficients of.
+    Sellmeier { b: [f64; 3], c: [f64; 3] },
+}
+
+impl Dispersion {
+    /// The Fraunhofer d line of helium in nm, at which indices of refraction are usually given.
+    pub const YELLOW: f64 = 587.56;
+
+    /// Borosilicate crown glass, the most common optical glass, as given by Schott.
+    pub const BK7: Dispersion = Dispersion::Sellmeier {
+        b: [1.03961212, 0.231792344, 1.01046945],
+        c: [0.00600069867, 0.0200179144, 103.560653],
+    };
+
+    /// Diamond, which spreads colors out much more than glass.
+    pub const DIAMOND: Dispersion = Dispersion::Sellmeier { b: [0.3306, 4.3356, 0.0], c: [0.030625, 0.011236, 0.0] };
+
+    /// The index of refraction for light of the wavelength `lambda` in nm.
+    pub fn ior(&self, lambda: f64) -> f64 {
+        let l2 = (lambda / 1000.0).powi(2);
+
+        match self {
+            Dispersion::Cauchy { a, b }    => a + b / l2,
+            Dispersion::Sellmeier { b, c } => (1.0 + (0..3).map(|i| b[i] * l2 / (l2 - c[i])).sum::<f64>()).sqrt(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn glass_bends_blue_light_more_than_red() {
+        // Given
+        let bk7 = Dielectric::with_dispersion(Dispersion::BK7, 0.0);
+        let cauchy = Dispersion::Cauchy { a: 1.5046, b: 0.0042 };
+
+        // Then
+        assert!((bk7.ior - 1.5168).abs() < 1e-4);
+        assert!((Dispersion::DIAMOND.ior(Dispersion::YELLOW) - 2.417).abs() < 2e-3);
+        assert!((cauchy.ior(500.0) - 1.5214).abs() < 1e-9);
+        assert!(bk7.at(450.0).ior > bk7.at(650.0).ior);
+        assert_eq!(Dielectric::new(1.5, 0.0).at(450.0).ior, 1.5);
+    }
+}
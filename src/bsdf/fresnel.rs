@@ -0,0 +1,86 @@
+use crate::color::Color;
+
+/// The fraction of unpolarized light reflected at the boundary between two dielectrics, for light
+/// arriving at an angle with the cosine `cos_i` to the normal, where `eta` is the index of
+/// refraction on the far side of the normal divided by the one on its side. A negative cosine
+/// means the light arrives from the far side. Everything is reflected past the critical angle.
+pub fn dielectric(cos_i: f64, eta: f64) -> f64 {
+    let (cos_i, eta) = match cos_i < 0.0 {
+        true  => ((-cos_i).min(1.0), 1.0 / eta),
+        false => (cos_i.min(1.0), eta),
+    };
+
+    let sin2_t = (1.0 - cos_i * cos_i) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return 1.0;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+
+    let parallel = (eta * cos_i - cos_t) / (eta * cos_i + cos_t);
+    let perpendicular = (cos_i - eta * cos_t) / (cos_i + eta * cos_t);
+    (parallel * parallel + perpendicular * perpendicular) / 2.0
+}
+
+/// The fraction of unpolarized light reflected by a conductor with the complex index of
+/// refraction `eta + i k` in each channel, for light arriving at an angle with the cosine `cos_i`
+/// to the normal.
+pub fn conductor(cos_i: f64, eta: Color, k: Color) -> Color {
+    Color::new(complex(cos_i, eta.r, k.r), complex(cos_i, eta.g, k.g), complex(cos_i, eta.b, k.b))
+}
+
+fn complex(cos_i: f64, eta: f64, k: f64) -> f64 {
+    let cos2 = cos_i.clamp(0.0, 1.0).powi(2);
+    let sin2 = 1.0 - cos2;
+
+    let t0 = eta * eta - k * k - sin2;
+    let a2_plus_b2 = (t0 * t0 + 4.0 * eta * eta * k * k).sqrt();
+    let t1 = a2_plus_b2 + cos2;
+    let a = (0.5 * (a2_plus_b2 + t0)).max(0.0).sqrt();
+    let t2 = 2.0 * cos_i.clamp(0.0, 1.0) * a;
+    let s = (t1 - t2) / (t1 + t2);
+
+    let t3 = cos2 * a2_plus_b2 + sin2 * sin2;
+    let t4 = t2 * sin2;
+    let p = s * (t3 - t4) / (t3 + t4);
+
+    (s + p) / 2.0
+}
+
+/// Schlick's approximation of the reflectance for light arriving at an angle with the cosine `cos`
+/// to the normal, given the reflectance `f0` head-on.
+pub fn schlick(cos: f64, f0: Color) -> Color {
+    f0 + (Color::new(1.0, 1.0, 1.0) - f0) * (1.0 - cos.clamp(0.0, 1.0)).powi(5)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::float::ApproxEq;
+
+    use super::*;
+
+    #[test]
+    fn reflectance_of_glass() {
+        // Then
+        assert!(dielectric(1.0, 1.5).approx_eq(0.04));
+        assert!(dielectric(-1.0, 1.5).approx_eq(0.04));
+        assert!(dielectric(0.0, 1.5).approx_eq(1.0));
+        assert_eq!(dielectric(-0.5, 1.5), 1.0);
+        assert!(dielectric(0.5, 1.5) > 0.04 && dielectric(0.5, 1.5) < 0.2);
+    }
+
+    #[test]
+    fn a_conductor_without_absorption_is_a_dielectric() {
+        // Given
+        let eta = Color::new(1.5, 1.5, 1.5);
+
+        for cos in [1.0, 0.7, 0.2] {
+            // When
+            let f = conductor(cos, eta, Color::default());
+
+            // Then
+            assert!(f.r.approx_eq(dielectric(cos, 1.5)));
+        }
+        assert_eq!(schlick(1.0, Color::new(0.04, 0.5, 1.0)), Color::new(0.04, 0.5, 1.0));
+        assert_eq!(schlick(0.0, Color::new(0.04, 0.5, 1.0)), Color::new(1.0, 1.0, 1.0));
+    }
+}
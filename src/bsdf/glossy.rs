@@ -0,0 +1,90 @@
+use std::f64::consts::FRAC_1_PI;
+
+use crate::bsdf::fresnel;
+use crate::bsdf::microfacet::Ggx;
+use crate::bsdf::{same_hemisphere, BsdfSample};
+use crate::color::Color;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::sampling::{cosine_hemisphere, cosine_hemisphere_pdf};
+
+/// The reflectance head-on of the coating of a surface that isn't metallic, which is about that of
+/// most plastics, paints and stones.
+const DIELECTRIC_F0: f64 = 0.04;
+
+/// A Cook–Torrance surface with GGX facets, in the metallic workflow: a matte base of the given
+/// `color` under a glossy coating when `metallic` is 0, and a metal tinted by `color` when it's 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Glossy {
+    pub color: Color,
+    pub roughness: f64,
+    pub metallic: f64,
+}
+
+impl Glossy {
+    pub fn new(color: Color, roughness: f64, metallic: f64) -> Self {
+        Self { color, roughness, metallic }
+    }
+
+    fn distribution(&self) -> Ggx {
+        Ggx::from_roughness(self.roughness).regularized()
+    }
+
+    /// The reflectance of the coating head-on.
+    fn f0(&self) -> Color {
+        let metallic = self.metallic.clamp(0.0, 1.0);
+
+        Color::new(DIELECTRIC_F0, DIELECTRIC_F0, DIELECTRIC_F0) * (1.0 - metallic) + self.color * metallic
+    }
+
+    /// The chance of sampling the coating rather than the base.
+    fn specular_probability(&self) -> f64 {
+        (1.0 + self.metallic.clamp(0.0, 1.0)) / 2.0
+    }
+
+    pub fn evaluate(&self, wo: Vector, wi: Vector) -> Color {
+        if !same_hemisphere(wo, wi) || wo.z() == 0.0 || wi.z() == 0.0 {
+            return Color::default();
+        }
+        let ggx = self.distribution();
+        let m = (wo + wi).normalize();
+        let m = m * m.z().signum();
+        let f = fresnel::schlick(wo.dot(m).abs(), self.f0());
+
+        // The base only gets the light the coating lets through, on the way in and on the way out.
+        let white = Color::new(1.0, 1.0, 1.0);
+        let through = (white - fresnel::schlick(wo.z().abs(), self.f0())) * (white - fresnel::schlick(wi.z().abs(), self.f0()));
+        let specular = f * (ggx.d(m) * ggx.g(wo, wi) / (4.0 * wo.z() * wi.z()).abs());
+        let diffuse = through * self.color * ((1.0 - self.metallic.clamp(0.0, 1.0)) * FRAC_1_PI);
+
+        specular + diffuse
+    }
+
+    pub fn sample(&self, wo: Vector, choice: f64, u: f64, v: f64) -> Option<BsdfSample> {
+        let wi = match choice < self.specular_probability() {
+            true  => (-wo).reflect(self.distribution().sample_visible_normal(wo, u, v)),
+            false => {
+                let wi = cosine_hemisphere(u, v);
+                Vector::new(wi.x(), wi.y(), wi.z().copysign(wo.z()))
+            }
+        };
+        if !same_hemisphere(wo, wi) {
+            return None;
+        }
+
+        BsdfSample::new(wi, self.evaluate(wo, wi), self.pdf(wo, wi))
+    }
+
+    pub fn pdf(&self, wo: Vector, wi: Vector) -> f64 {
+        if !same_hemisphere(wo, wi) {
+            return 0.0;
+        }
+        let m = (wo + wi).normalize();
+        let m = m * m.z().signum();
+        let specular = self.distribution().visible_normal_pdf(wo, m) / (4.0 * wo.dot(m).abs());
+        let p = self.specular_probability();
+
+        p * specular + (1.0 - p) * cosine_hemisphere_pdf(wi.z().abs())
+    }
+}
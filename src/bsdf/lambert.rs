@@ -0,0 +1,42 @@
+use std::f64::consts::FRAC_1_PI;
+
+use crate::bsdf::{same_hemisphere, BsdfSample};
+use crate::color::Color;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::sampling::{cosine_hemisphere, cosine_hemisphere_pdf};
+
+/// A perfectly matte surface, which scatters the fraction `albedo` of the light arriving at it
+/// evenly in every direction.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Lambert {
+    pub albedo: Color,
+}
+
+impl Lambert {
+    pub fn new(albedo: Color) -> Self {
+        Self { albedo }
+    }
+
+    pub fn evaluate(&self, wo: Vector, wi: Vector) -> Color {
+        match same_hemisphere(wo, wi) {
+            true  => self.albedo * FRAC_1_PI,
+            false => Color::default(),
+        }
+    }
+
+    pub fn sample(&self, wo: Vector, u: f64, v: f64) -> Option<BsdfSample> {
+        let wi = cosine_hemisphere(u, v);
+        let wi = Vector::new(wi.x(), wi.y(), wi.z().copysign(wo.z()));
+
+        BsdfSample::new(wi, self.evaluate(wo, wi), self.pdf(wo, wi))
+    }
+
+    pub fn pdf(&self, wo: Vector, wi: Vector) -> f64 {
+        match same_hemisphere(wo, wi) {
+            true  => cosine_hemisphere_pdf(wi.z().abs()),
+            false => 0.0,
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+
+/// The GGX (Trowbridge–Reitz) distribution of the normals of the microscopic facets making up a
+/// rough surface, in the shading frame. Its only parameter is the width `alpha` of the
+/// distribution.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ggx {
+    pub alpha: f64,
+}
+
+impl Ggx {
+    /// Below this width the surface is as good as smooth, and is better treated as a perfect
+    /// mirror than sampled.
+    const SMOOTH: f64 = 1e-3;
+
+    /// The distribution for a perceptual roughness from 0 to 1, which is the square root of the
+    /// width.
+    pub fn from_roughness(roughness: f64) -> Self {
+        Self { alpha: roughness.clamp(0.0, 1.0).powi(2) }
+    }
+
+    pub fn is_smooth(&self) -> bool {
+        self.alpha < Self::SMOOTH
+    }
+
+    /// The same distribution made wide enough that sampling it stays well-behaved.
+    pub fn regularized(&self) -> Self {
+        Self { alpha: self.alpha.max(Self::SMOOTH) }
+    }
+
+    /// The density of facets with the normal `m`, with respect to solid angle.
+    pub fn d(&self, m: Vector) -> f64 {
+        let cos2 = m.z() * m.z();
+        if cos2 == 0.0 {
+            return 0.0;
+        }
+        let a2 = self.alpha * self.alpha;
+        let t = cos2 * (a2 - 1.0) + 1.0;
+
+        a2 / (PI * t * t)
+    }
+
+    /// Smith's auxiliary function, which measures how much of the surface is hidden when looked at
+    /// from `w`.
+    pub fn lambda(&self, w: Vector) -> f64 {
+        let cos2 = w.z() * w.z();
+        if cos2 == 0.0 {
+            return f64::INFINITY;
+        }
+        let tan2 = (1.0 - cos2).max(0.0) / cos2;
+
+        ((1.0 + self.alpha * self.alpha * tan2).sqrt() - 1.0) / 2.0
+    }
+
+    /// The fraction of the facets that can be seen from `w`.
+    pub fn g1(&self, w: Vector) -> f64 {
+        1.0 / (1.0 + self.lambda(w))
+    }
+
+    /// The fraction of the facets that can be seen from both `wo` and `wi`.
+    pub fn g(&self, wo: Vector, wi: Vector) -> f64 {
+        1.0 / (1.0 + self.lambda(wo) + self.lambda(wi))
+    }
+
+    /// The density of the normals of the facets seen from `w`, with respect to solid angle.
+    pub fn visible_normal_pdf(&self, w: Vector, m: Vector) -> f64 {
+        match w.z() {
+            0.0 => 0.0,
+            z   => self.g1(w) / z.abs() * self.d(m) * w.dot(m).abs(),
+        }
+    }
+
+    /// Maps a pair of numbers in `0..1` to the normal of a facet seen from `w`, as described by
+    /// Heitz in "Sampling the GGX Distribution of Visible Normals". The normal is always above the
+    /// surface, whichever side `w` is on.
+    pub fn sample_visible_normal(&self, w: Vector, u: f64, v: f64) -> Vector {
+        // Stretch the view direction into the configuration where the facets form a hemisphere.
+        let mut wh = Vector::new(self.alpha * w.x(), self.alpha * w.y(), w.z()).normalize();
+        if wh.z() < 0.0 {
+            wh = -wh;
+        }
+        let t1 = match wh.z() < 0.99999 {
+            true  => Vector::new(0.0, 0.0, 1.0).cross(wh).normalize(),
+            false => Vector::new(1.0, 0.0, 0.0),
+        };
+        let t2 = wh.cross(t1);
+
+        // A point on the projection of the hemisphere, squashed towards the visible part of it.
+        let r = u.sqrt();
+        let (sin, cos) = (v * TAU).sin_cos();
+        let (px, py) = (r * cos, r * sin);
+        let h = (1.0 - px * px).sqrt();
+        let py = h + (py - h) * (1.0 + wh.z()) / 2.0;
+        let pz = (1.0 - px * px - py * py).max(0.0).sqrt();
+
+        // Lift it back onto the hemisphere and unstretch it.
+        let nh = t1 * px + t2 * py + wh * pz;
+        Vector::new(self.alpha * nh.x(), self.alpha * nh.y(), nh.z().max(1e-6)).normalize()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::float::ApproxEq;
+
+    use super::*;
+
+    #[test]
+    fn facet_normals_cover_the_surface_once() {
+        // Given
+        let ggx = Ggx::from_roughness(0.5);
+        let n = 200;
+
+        // When: the projected area of the facets, integrated over the hemisphere.
+        let area = (0..n * n)
+            .map(|i| {
+                let cos = ((i / n) as f64 + 0.5) / n as f64;
+                let phi = ((i % n) as f64 + 0.5) / n as f64 * TAU;
+                let sin = (1.0 - cos * cos).sqrt();
+                ggx.d(Vector::new(sin * phi.cos(), sin * phi.sin(), cos)) * cos
+            })
+            .sum::<f64>() * TAU / (n * n) as f64;
+
+        // Then
+        assert!((area - 1.0).abs() < 1e-2);
+        assert!(ggx.g1(Vector::new(0.0, 0.0, 1.0)).approx_eq(1.0));
+        assert!(ggx.g1(Vector::new(0.99, 0.0, 0.141)) < 1.0);
+    }
+
+    #[test]
+    fn visible_normals_face_the_viewer() {
+        // Given
+        let ggx = Ggx::from_roughness(0.7);
+        let w = Vector::new(0.6, 0.0, 0.8);
+
+        for (u, v) in [(0.1, 0.2), (0.5, 0.5), (0.9, 0.7), (0.99, 0.01)] {
+            // When
+            let m = ggx.sample_visible_normal(w, u, v);
+            let flipped = ggx.sample_visible_normal(-w, u, v);
+
+            // Then
+            assert!(m.magnitude().approx_eq(1.0) && m.z() > 0.0);
+            assert!(m.dot(w) > 0.0);
+            assert_eq!(m, flipped);
+            assert!(ggx.visible_normal_pdf(w, m) > 0.0);
+        }
+    }
+}
@@ -0,0 +1,267 @@
+pub mod conductor;
+pub mod dielectric;
+pub mod fresnel;
+pub mod glossy;
+pub mod lambert;
+pub mod microfacet;
+
+use crate::bsdf::conductor::Conductor;
+use crate::bsdf::dielectric::Dielectric;
+use crate::bsdf::glossy::Glossy;
+use crate::bsdf::lambert::Lambert;
+use crate::color::Color;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::spectrum::Wavelengths;
+
+/// How a surface scatters light, in a shading frame with the surface normal as its z axis (see
+/// `sampling::Frame`). The direction `wo` towards the viewer and the direction `wi` the light
+/// arrives from both point away from the surface, and may be on either side of it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "kebab-case"))]
+pub enum Bsdf {
+    Lambert(Lambert),
+    Glossy(Glossy),
+    Dielectric(Dielectric),
+    Conductor(Conductor),
+}
+
+impl Bsdf {
+    /// Whether the surface only scatters light in single directions, like a mirror or clear glass
+    /// does. Such a BSDF is zero everywhere else, so the lights can't be sampled for it.
+    pub fn is_specular(&self) -> bool {
+        match self {
+            Bsdf::Lambert(_) | Bsdf::Glossy(_) => false,
+            Bsdf::Dielectric(dielectric)       => dielectric.is_specular(),
+            Bsdf::Conductor(conductor)         => conductor.is_specular(),
+        }
+    }
+
+    /// Whether the index of refraction changes with the wavelength of light.
+    pub fn is_dispersive(&self) -> bool {
+        matches!(self, Bsdf::Dielectric(dielectric) if dielectric.dispersion.is_some())
+    }
+
+    /// The BSDF for light of the given wavelengths, with its colors in the channels of each
+    /// wavelength. The index of refraction of a dispersive one is that of the first wavelength.
+    pub fn at(&self, wavelengths: &Wavelengths) -> Bsdf {
+        match self {
+            Bsdf::Lambert(lambert)       => Bsdf::Lambert(Lambert::new(wavelengths.spectrum(lambert.albedo))),
+            Bsdf::Glossy(glossy)         => Bsdf::Glossy(Glossy { color: wavelengths.spectrum(glossy.color), ..*glossy }),
+            Bsdf::Dielectric(dielectric) => Bsdf::Dielectric(dielectric.at(wavelengths.lambda[0])),
+            Bsdf::Conductor(conductor)   => Bsdf::Conductor(Conductor {
+                eta: wavelengths.interpolate(conductor.eta),
+                k: wavelengths.interpolate(conductor.k),
+                ..*conductor
+            }),
+        }
+    }
+
+    /// The fraction of the light arriving from `wi` that is scattered towards `wo`, per unit of
+    /// projected solid angle.
+    pub fn evaluate(&self, wo: Vector, wi: Vector) -> Color {
+        match self {
+            Bsdf::Lambert(lambert)       => lambert.evaluate(wo, wi),
+            Bsdf::Glossy(glossy)         => glossy.evaluate(wo, wi),
+            Bsdf::Dielectric(dielectric) => dielectric.evaluate(wo, wi),
+            Bsdf::Conductor(conductor)   => conductor.evaluate(wo, wi),
+        }
+    }
+
+    /// Maps three numbers in `0..1` to a direction light scattered towards `wo` arrives from,
+    /// chosen roughly in proportion to how much it contributes. `choice` picks between the lobes
+    /// of the BSDF, `u` and `v` a direction within one. None if no light is scattered.
+    pub fn sample(&self, wo: Vector, choice: f64, u: f64, v: f64) -> Option<BsdfSample> {
+        match self {
+            Bsdf::Lambert(lambert)       => lambert.sample(wo, u, v),
+            Bsdf::Glossy(glossy)         => glossy.sample(wo, choice, u, v),
+            Bsdf::Dielectric(dielectric) => dielectric.sample(wo, choice, u, v),
+            Bsdf::Conductor(conductor)   => conductor.sample(wo, u, v),
+        }
+    }
+
+    /// The density with which `sample` picks `wi`, with respect to solid angle. Zero for the
+    /// directions of a specular BSDF.
+    pub fn pdf(&self, wo: Vector, wi: Vector) -> f64 {
+        match self {
+            Bsdf::Lambert(lambert)       => lambert.pdf(wo, wi),
+            Bsdf::Glossy(glossy)         => glossy.pdf(wo, wi),
+            Bsdf::Dielectric(dielectric) => dielectric.pdf(wo, wi),
+            Bsdf::Conductor(conductor)   => conductor.pdf(wo, wi),
+        }
+    }
+}
+
+/// A direction sampled by a BSDF.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BsdfSample {
+    /// The direction the light arrives from, in the shading frame.
+    pub direction: Vector,
+    /// The BSDF for the direction.
+    pub value: Color,
+    /// The density with which the direction was picked or, for a specular direction, the chance
+    /// of picking it.
+    pub pdf: f64,
+    /// Whether the direction is the only one scattering light towards the viewer in its lobe.
+    pub specular: bool,
+}
+
+impl BsdfSample {
+    /// A sample of a BSDF that spreads light out, or None if it can't be used.
+    pub fn new(direction: Vector, value: Color, pdf: f64) -> Option<Self> {
+        (pdf > 0.0 && direction.z() != 0.0).then_some(Self { direction, value, pdf, specular: false, })
+    }
+
+    /// A specular sample scattering the fraction `f` of the light, chosen with probability `p`.
+    pub fn specular(direction: Vector, f: Color, p: f64) -> Option<Self> {
+        (p > 0.0 && direction.z() != 0.0).then(|| Self {
+            direction,
+            value: f * (1.0 / direction.z().abs()),
+            pdf: p,
+            specular: true,
+        })
+    }
+
+    /// The factor the light arriving from the direction is scaled by on its way to the viewer,
+    /// divided by the density of the sample: `f |cos θ| / pdf`.
+    pub fn weight(&self) -> Color {
+        self.value * (self.direction.z().abs() / self.pdf)
+    }
+}
+
+/// Whether `a` and `b` are on the same side of the surface.
+pub(crate) fn same_hemisphere(a: Vector, b: Vector) -> bool {
+    a.z() * b.z() > 0.0
+}
+
+/// The direction `w` is refracted into at a boundary with the normal `n`, where `eta` is the
+/// index of refraction on the far side of the normal divided by the one on its side, together with
+/// the index on the side of the refracted direction relative to that of `w`. None past the
+/// critical angle, where all of the light is reflected.
+pub(crate) fn refract(w: Vector, n: Vector, eta: f64) -> Option<(Vector, f64)> {
+    let (cos_i, n, eta) = match n.dot(w) {
+        cos if cos < 0.0 => (-cos, -n, 1.0 / eta),
+        cos              => (cos, n, eta),
+    };
+
+    let sin2_t = (1.0 - cos_i * cos_i).max(0.0) / (eta * eta);
+    if sin2_t >= 1.0 {
+        return None;
+    }
+    let cos_t = (1.0 - sin2_t).sqrt();
+
+    Some((-w / eta + n * (cos_i / eta - cos_t), eta))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_PI, TAU};
+
+    use crate::core::float::ApproxEq;
+
+    use super::*;
+
+    fn bsdfs() -> Vec<Bsdf> {
+        vec![
+            Bsdf::Lambert(Lambert::new(Color::new(0.8, 0.5, 0.2))),
+            Bsdf::Glossy(Glossy::new(Color::new(1.0, 1.0, 1.0), 0.3, 0.0)),
+            Bsdf::Glossy(Glossy::new(Color::new(1.0, 0.8, 0.3), 0.6, 1.0)),
+            Bsdf::Dielectric(Dielectric::new(1.5, 0.0)),
+            Bsdf::Dielectric(Dielectric::new(1.5, 0.4)),
+            Bsdf::Conductor(Conductor::gold(0.0)),
+            Bsdf::Conductor(Conductor::copper(0.5)),
+        ]
+    }
+
+    fn direction(theta: f64, phi: f64) -> Vector {
+        Vector::new(theta.sin() * phi.cos(), theta.sin() * phi.sin(), theta.cos())
+    }
+
+    /// The index of the side `w` is on, relative to the outside, for the given dielectric.
+    fn side(bsdf: &Bsdf, w: Vector) -> f64 {
+        match (bsdf, w.z() < 0.0) {
+            (Bsdf::Dielectric(dielectric), true) => dielectric.ior,
+            _                                    => 1.0,
+        }
+    }
+
+    #[test]
+    fn refracting_into_glass() {
+        // Given
+        let n = Vector::new(0.0, 0.0, 1.0);
+        let w = direction(TAU / 8.0, 0.0);
+
+        // When
+        let (t, eta) = refract(w, n, 1.5).unwrap();
+        let (back, back_eta) = refract(t, n, 1.5).unwrap();
+
+        // Then
+        assert!(eta.approx_eq(1.5) && back_eta.approx_eq(1.0 / 1.5));
+        assert!((w.x() * w.x() + w.y() * w.y()).sqrt().approx_eq(1.5 * t.x().hypot(t.y())));
+        assert!(t.z() < 0.0 && t.magnitude().approx_eq(1.0));
+        assert_eq!(back, w);
+        assert!(refract(direction(1.2, 0.0), -n, 1.5).is_none());
+    }
+
+    #[test]
+    fn bsdfs_are_reciprocal() {
+        // Given
+        let directions = [
+            direction(0.3, 0.1),
+            direction(1.1, 2.0),
+            direction(0.7, 4.0),
+            direction(2.5, 1.0),
+            direction(2.0, 3.5),
+        ];
+
+        for bsdf in bsdfs() {
+            for wo in directions {
+                for wi in directions {
+                    // When
+                    let forward = bsdf.evaluate(wo, wi) * (1.0 / side(&bsdf, wo).powi(2));
+                    let backward = bsdf.evaluate(wi, wo) * (1.0 / side(&bsdf, wi).powi(2));
+
+                    // Then
+                    assert_eq!(forward, backward, "{:?} from {:?} to {:?}", bsdf, wi, wo);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn bsdfs_conserve_energy() {
+        // Given
+        let n = 128;
+
+        for bsdf in bsdfs() {
+            for wo in [direction(0.0, 0.0), direction(1.0, 0.5), direction(1.45, 2.0), direction(2.6, 1.0)] {
+                // When: the fraction of the light scattered towards wo, from the outgoing flux.
+                let mut reflected = Color::default();
+                for i in 0..n * n {
+                    let u = ((i / n) as f64 + 0.5) / n as f64;
+                    let v = ((i % n) as f64 + 0.5) / n as f64;
+                    let choice = ((i * 7919) % (n * n)) as f64 / (n * n) as f64;
+                    let Some(sample) = bsdf.sample(wo, choice, u, v) else { continue };
+                    let flux = (side(&bsdf, sample.direction) / side(&bsdf, wo)).powi(2);
+
+                    // Then: samples agree with evaluating the BSDF.
+                    if !sample.specular {
+                        assert_eq!(sample.value, bsdf.evaluate(wo, sample.direction));
+                        assert!(sample.pdf.approx_eq_low_precision(bsdf.pdf(wo, sample.direction)));
+                    }
+                    reflected = reflected + sample.weight() * (flux / (n * n) as f64);
+                }
+
+                // Then
+                let most = reflected.r.max(reflected.g).max(reflected.b);
+                assert!(most < 1.01, "{:?} scatters {:?} towards {:?}", bsdf, reflected, wo);
+                match bsdf {
+                    Bsdf::Lambert(lambert)                      => assert_eq!(reflected, lambert.albedo),
+                    Bsdf::Dielectric(d) if d.roughness == 0.0   => assert!(reflected.r.approx_eq_low_precision(1.0)),
+                    _                                           => assert!(most > 0.3, "{:?} absorbs too much", bsdf),
+                }
+            }
+        }
+        assert!(Bsdf::Lambert(Lambert::new(Color::new(1.0, 1.0, 1.0))).pdf(direction(0.0, 0.0), direction(0.0, 0.0)).approx_eq(FRAC_1_PI));
+    }
+}
@@ -0,0 +1,251 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::image;
+use crate::noise::fbm;
+use crate::shape::Shape;
+
+/// How far apart the noise is looked at to find out how steep it is, in the space of the noise.
+const NOISE_STEP: f64 = 1e-4;
+
+/// Detail bent into the shading normal of a surface that its geometry doesn't have, like the grain
+/// of wood or the mortar between bricks. Maps are looked up at the surface coordinates of the
+/// shape, and bend the normal along the directions they grow in.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Bump {
+    /// A normal map in the space of the surface: its red channel leans the normal from the
+    /// direction `u` shrinks in at 0 to the one it grows in at 1, green does the same along `v`,
+    /// and blue is how far it points out of the surface. The flat blue of `(0.5, 0.5, 1)` leaves
+    /// it as it is.
+    Normal(ImageMap),
+    /// A grayscale height map, which raises the surface by `scale` where it's white.
+    Height { map: ImageMap, scale: f64 },
+    /// Bumps up to `scale` high from fractal noise with `octaves` layers and features about
+    /// `1 / frequency` apart in the space of the shape.
+    Noise { frequency: f64, octaves: usize, scale: f64 },
+}
+
+impl Bump {
+    /// The shading normal at a point given in world space on `shape`, where the unit vector
+    /// `normal` is its surface normal, with the shape where it is at `time`.
+    pub fn normal_at(&self, shape: &dyn Shape, point: Point, time: f64, normal: Vector) -> Vector {
+        match self {
+            Bump::Normal(map) => {
+                let surface = shape.surface_at(point, time);
+                let Some(frame) = surface.frame(normal) else { return normal };
+                let c = map.at(surface.u, surface.v);
+
+                frame.to_world(Vector::new(2.0 * c.r - 1.0, 2.0 * c.g - 1.0, 2.0 * c.b - 1.0)).normalize()
+            }
+            Bump::Height { map, scale } => {
+                let surface = shape.surface_at(point, time);
+                let Some(frame) = surface.frame(normal) else { return normal };
+                // The height a texel further along u and along v.
+                let (du, dv) = (1.0 / map.width() as f64, 1.0 / map.height() as f64);
+                let height = |u: f64, v: f64| map.at(u, v).luminance() * scale;
+                let here = height(surface.u, surface.v);
+                let slope_u = (height(surface.u + du, surface.v) - here) / (du * surface.dpdu.magnitude());
+                let slope_v = (height(surface.u, surface.v + dv) - here) / (dv * surface.dpdv.magnitude());
+
+                bend(normal, frame.tangent * slope_u + frame.bitangent * slope_v)
+            }
+            Bump::Noise { frequency, octaves, scale } => {
+                let inverse = shape.data().world_inverse_at(time);
+                let local = inverse * point;
+                let at = |dx: f64, dy: f64, dz: f64| {
+                    fbm(Point::new(local.x() * frequency + dx, local.y() * frequency + dy, local.z() * frequency + dz), *octaves)
+                };
+                let slope = |dx: f64, dy: f64, dz: f64| {
+                    (at(dx, dy, dz) - at(-dx, -dy, -dz)) / (2.0 * NOISE_STEP) * frequency * scale
+                };
+                let gradient = Vector::new(slope(NOISE_STEP, 0.0, 0.0), slope(0.0, NOISE_STEP, 0.0), slope(0.0, 0.0, NOISE_STEP));
+
+                // Like normals, gradients go to world space through the transpose of the inverse.
+                bend(normal, inverse.transpose() * gradient)
+            }
+        }
+    }
+}
+
+/// The normal of a surface with the unit vector `normal` after it's raised by a height that grows
+/// along `gradient`, which leans it away from where the surface goes up.
+fn bend(normal: Vector, gradient: Vector) -> Vector {
+    let along = gradient - normal * gradient.dot(normal);
+
+    (normal - along).normalize()
+}
+
+/// An image looked up at surface coordinates, which repeat it every whole number: `u` goes across
+/// it from the left, and `v` up from the bottom like in most modelling tools. Values are blended
+/// between the middles of the pixels.
+#[derive(Clone)]
+pub struct ImageMap {
+    /// The file the map was loaded from, if any.
+    path: Option<PathBuf>,
+    image: Arc<Canvas>,
+}
+
+impl ImageMap {
+    pub fn new(image: Canvas) -> Self {
+        Self { path: None, image: Arc::new(image), }
+    }
+
+    /// Loads a map from an image with the values of the pixels as they are stored, which for
+    /// normal and height maps is what they mean.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let map = Self::new(image::load(&path)?);
+
+        Ok(Self { path: Some(path.as_ref().to_path_buf()), ..map })
+    }
+
+    pub fn width(&self) -> usize {
+        self.image.width() as usize
+    }
+
+    pub fn height(&self) -> usize {
+        self.image.height() as usize
+    }
+
+    pub fn at(&self, u: f64, v: f64) -> Color {
+        let (width, height) = (self.width(), self.height());
+        let x = u.rem_euclid(1.0) * width as f64 - 0.5;
+        let y = (1.0 - v.rem_euclid(1.0)) * height as f64 - 0.5;
+        let (x0, y0) = (x.floor(), y.floor());
+        let (tx, ty) = (x - x0, y - y0);
+        let pixel = |x: f64, y: f64| self.image[((x as i64).rem_euclid(width as i64) as usize, (y as i64).rem_euclid(height as i64) as usize)];
+
+        pixel(x0, y0).lerp(pixel(x0 + 1.0, y0), tx).lerp(pixel(x0, y0 + 1.0).lerp(pixel(x0 + 1.0, y0 + 1.0), tx), ty)
+    }
+}
+
+impl PartialEq for ImageMap {
+    fn eq(&self, other: &Self) -> bool {
+        self.path == other.path && Arc::ptr_eq(&self.image, &other.image)
+    }
+}
+
+impl fmt::Debug for ImageMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ImageMap")
+            .field("path", &self.path)
+            .field("width", &self.width())
+            .field("height", &self.height())
+            .finish()
+    }
+}
+
+/// What is stored of an image map: the file it's loaded from again.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ImageMapRepr {
+    file: PathBuf,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for ImageMap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let file = self.path.clone()
+            .ok_or_else(|| serde::ser::Error::custom("only image maps loaded from a file can be stored"))?;
+
+        ImageMapRepr { file }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for ImageMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = ImageMapRepr::deserialize(deserializer)?;
+
+        Self::load(&repr.file).map_err(|e| serde::de::Error::custom(format!("could not read {}: {}", repr.file.display(), e)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::matrix::Matrix4;
+    use crate::shape::plane::Plane;
+    use crate::shape::sphere::Sphere;
+
+    use super::*;
+
+    /// A map `width` pixels wide and one high with the colors `pixel` gives each column.
+    fn map(width: usize, pixel: impl Fn(usize) -> Color) -> ImageMap {
+        let mut canvas = Canvas::new(width, 1);
+        for x in 0..width {
+            canvas[(x, 0)] = pixel(x);
+        }
+
+        ImageMap::new(canvas)
+    }
+
+    #[test]
+    fn image_maps_repeat_and_blend_between_pixels() {
+        // Given
+        let ramp = map(4, |x| Color::new(x as f64, 0.0, 0.0));
+
+        // Then
+        assert_eq!(ramp.at(0.125, 0.5), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(ramp.at(0.5, 0.3), Color::new(1.5, 0.0, 0.0));
+        assert_eq!(ramp.at(2.5, -4.0), Color::new(1.5, 0.0, 0.0));
+        assert_eq!(ramp.at(0.0, 0.0), Color::new(1.5, 0.0, 0.0));
+    }
+
+    #[test]
+    fn normal_maps_lean_the_normal_along_the_surface() {
+        // Given
+        let plane = Plane::new();
+        let flat = Bump::Normal(map(1, |_| Color::new(0.5, 0.5, 1.0)));
+        let leaning = Bump::Normal(map(1, |_| Color::new(1.0, 0.5, 1.0)));
+        let normal = Vector::new(0.0, 1.0, 0.0);
+
+        // When
+        let unchanged = flat.normal_at(&plane, Point::new(0.3, 0.0, 0.7), 0.0, normal);
+        let bent = leaning.normal_at(&plane, Point::new(0.3, 0.0, 0.7), 0.0, normal);
+
+        // Then: u grows along x on a plane.
+        assert_eq!(unchanged, normal);
+        assert_eq!(bent, Vector::new(1.0, 1.0, 0.0).normalize());
+    }
+
+    #[test]
+    fn height_maps_lean_the_normal_away_from_where_they_rise() {
+        // Given: a map rising by 1 across the plane from x = 0 to 1, raised half as high.
+        let plane = Plane::new();
+        let bump = Bump::Height { map: map(4, |x| Color::new(x as f64 / 4.0, x as f64 / 4.0, x as f64 / 4.0)), scale: 0.5 };
+
+        // When
+        let normal = bump.normal_at(&plane, Point::new(0.3, 0.0, 0.2), 0.0, Vector::new(0.0, 1.0, 0.0));
+
+        // Then
+        assert_eq!(normal, Vector::new(-0.5, 1.0, 0.0).normalize());
+    }
+
+    #[test]
+    fn noise_bends_normals_all_over_the_place() {
+        // Given
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let bump = Bump::Noise { frequency: 3.0, octaves: 2, scale: 0.1 };
+
+        // When
+        let normals = (0..200)
+            .map(|i| {
+                let (theta, phi) = (i as f64 * 0.31, i as f64 * 0.17 % 3.0 + 0.07);
+                let normal = Vector::new(phi.sin() * theta.sin(), phi.cos(), phi.sin() * theta.cos());
+                (normal, bump.normal_at(&sphere, Point::zero() + normal * 2.0, 0.0, normal))
+            })
+            .collect::<Vec<_>>();
+
+        // Then
+        assert!(normals.iter().all(|(normal, bent)| (bent.magnitude() - 1.0).abs() < 1e-9 && bent.dot(*normal) > 0.5));
+        assert!(normals.iter().filter(|(normal, bent)| bent.dot(*normal) < 0.999).count() > 100);
+    }
+}
@@ -0,0 +1,353 @@
+use crate::bounds::BoundingBox;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::intersection::Intersection;
+use crate::ray::Ray;
+use crate::shape::Shape;
+
+const BINS: usize = 12;
+const MAX_LEAF_SIZE: usize = 8;
+const TRAVERSAL_COST: f64 = 0.125;
+
+/// A bounding volume hierarchy over a slice of shapes, built with the surface area heuristic.
+///
+/// The hierarchy only stores indices into the slice it was built from, so the same slice has to
+/// be passed back in when intersecting it. Shapes without finite bounds, such as planes, are kept
+/// aside and always tested.
+#[derive(Clone, Debug, Default)]
+pub struct Bvh {
+    nodes: Vec<Node>,
+    indices: Vec<usize>,
+    unbounded: Vec<usize>,
+    shape_count: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Node {
+    bounds: BoundingBox,
+    kind: NodeKind,
+}
+
+#[derive(Clone, Copy, Debug)]
+enum NodeKind {
+    Leaf { start: usize, count: usize },
+    Interior { left: usize, right: usize },
+}
+
+#[derive(Clone, Copy, Debug)]
+struct Primitive {
+    index: usize,
+    bounds: BoundingBox,
+    centroid: Point,
+}
+
+impl Bvh {
+    pub fn build(shapes: &[Box<dyn Shape>]) -> Self {
+        let mut bvh = Self { shape_count: shapes.len(), ..Self::default() };
+        let mut primitives = Vec::with_capacity(shapes.len());
+
+        for (index, shape) in shapes.iter().enumerate() {
+            let bounds = shape.parent_space_bounds();
+            match bounds.is_finite() {
+                true  => primitives.push(Primitive { index, bounds, centroid: bounds.centroid() }),
+                false => bvh.unbounded.push(index),
+            }
+        }
+
+        if !primitives.is_empty() {
+            bvh.build_node(&mut primitives, 0);
+        }
+        bvh.indices = primitives.iter().map(|p| p.index).collect();
+
+        bvh
+    }
+
+    /// The number of shapes the hierarchy was built for.
+    pub fn len(&self) -> usize {
+        self.shape_count
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shape_count == 0
+    }
+
+    pub fn bounds(&self) -> BoundingBox {
+        match (self.nodes.first(), self.unbounded.is_empty()) {
+            (_, false)      => BoundingBox::infinite(),
+            (Some(root), _) => root.bounds,
+            (None, _)       => BoundingBox::empty(),
+        }
+    }
+
+    /// All intersections of `ray` with `shapes`, which must be the slice the hierarchy was built
+    /// from. Intersections are returned in no particular order, and may leave out intersections
+    /// behind the origin of the ray.
+    pub fn intersect<'a>(&self, shapes: &'a [Box<dyn Shape>], ray: &Ray) -> Vec<Intersection<'a>> {
+        debug_assert_eq!(shapes.len(), self.shape_count, "BVH was built for a different set of shapes");
+
+        let mut xs = self.unbounded.iter()
+            .flat_map(|&i| shapes[i].intersect(ray))
+            .collect::<Vec<_>>();
+
+        if self.nodes.is_empty() {
+            return xs;
+        }
+
+        let mut stack = vec![0];
+        while let Some(i) = stack.pop() {
+            let node = &self.nodes[i];
+            if !node.bounds.intersects(ray) {
+                continue;
+            }
+
+            match node.kind {
+                NodeKind::Leaf { start, count } => {
+                    for &index in &self.indices[start..start + count] {
+                        xs.extend(shapes[index].intersect(ray));
+                    }
+                }
+                NodeKind::Interior { left, right } => {
+                    stack.push(right);
+                    stack.push(left);
+                }
+            }
+        }
+
+        xs
+    }
+
+    /// Builds the node for `primitives`, which start at `offset` in the final leaf order, and
+    /// returns its index.
+    fn build_node(&mut self, primitives: &mut [Primitive], offset: usize) -> usize {
+        let bounds = primitives.iter().fold(BoundingBox::empty(), |b, p| b.merge(&p.bounds));
+        let node = self.nodes.len();
+        self.nodes.push(Node { bounds, kind: NodeKind::Leaf { start: offset, count: primitives.len() } });
+
+        if primitives.len() <= 2 {
+            return node;
+        }
+
+        let mid = match split(primitives, &bounds) {
+            Some(mid) => mid,
+            None      => return node,
+        };
+
+        let (left_primitives, right_primitives) = primitives.split_at_mut(mid);
+        let left = self.build_node(left_primitives, offset);
+        let right = self.build_node(right_primitives, offset + mid);
+        self.nodes[node].kind = NodeKind::Interior { left, right };
+
+        node
+    }
+}
+
+/// Partitions `primitives` along the split with the lowest surface area heuristic cost, and
+/// returns the number of primitives on the left side. Returns `None` when keeping all
+/// primitives in a single leaf is cheaper.
+fn split(primitives: &mut [Primitive], bounds: &BoundingBox) -> Option<usize> {
+    let centroid_bounds = primitives.iter().fold(BoundingBox::empty(), |mut b, p| {
+        b.add_point(p.centroid);
+        b
+    });
+    let axis = centroid_bounds.longest_axis();
+    let (min, max) = (axis_of(centroid_bounds.min, axis), axis_of(centroid_bounds.max, axis));
+
+    // All centroids are in the same spot, no split is going to separate them.
+    if max - min <= f64::EPSILON {
+        return match primitives.len() > MAX_LEAF_SIZE {
+            true  => Some(primitives.len() / 2),
+            false => None,
+        };
+    }
+
+    let bin_of = |p: &Primitive| {
+        let bin = ((axis_of(p.centroid, axis) - min) / (max - min) * BINS as f64) as usize;
+        bin.min(BINS - 1)
+    };
+
+    let mut bins = [(BoundingBox::empty(), 0usize); BINS];
+    for p in primitives.iter() {
+        let bin = &mut bins[bin_of(p)];
+        bin.0 = bin.0.merge(&p.bounds);
+        bin.1 += 1;
+    }
+
+    // Cost of splitting after every bin, sweeping from both sides.
+    let mut costs = [0.0; BINS - 1];
+    let (mut left_bounds, mut left_count) = (BoundingBox::empty(), 0);
+    for i in 0..BINS - 1 {
+        left_bounds = left_bounds.merge(&bins[i].0);
+        left_count += bins[i].1;
+        costs[i] = left_bounds.surface_area() * left_count as f64;
+    }
+    let (mut right_bounds, mut right_count) = (BoundingBox::empty(), 0);
+    for i in (1..BINS).rev() {
+        right_bounds = right_bounds.merge(&bins[i].0);
+        right_count += bins[i].1;
+        costs[i - 1] += right_bounds.surface_area() * right_count as f64;
+    }
+
+    let (best_bin, best_cost) = costs.iter()
+        .enumerate()
+        .min_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(i, &c)| (i, TRAVERSAL_COST + c / bounds.surface_area()))
+        .unwrap();
+    let leaf_cost = primitives.len() as f64;
+
+    if best_cost >= leaf_cost && primitives.len() <= MAX_LEAF_SIZE {
+        return None;
+    }
+
+    let mid = partition(primitives, |p| bin_of(p) <= best_bin);
+    match mid {
+        0                          => Some(primitives.len() / 2),
+        m if m == primitives.len() => Some(primitives.len() / 2),
+        m                          => Some(m),
+    }
+}
+
+/// Moves all primitives matching `pred` to the front, returning how many there are.
+fn partition(primitives: &mut [Primitive], pred: impl Fn(&Primitive) -> bool) -> usize {
+    let mut mid = 0;
+    for i in 0..primitives.len() {
+        if pred(&primitives[i]) {
+            primitives.swap(i, mid);
+            mid += 1;
+        }
+    }
+
+    mid
+}
+
+pub(crate) fn axis_of(point: Point, axis: usize) -> f64 {
+    match axis {
+        0 => point.x(),
+        1 => point.y(),
+        _ => point.z(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{Rng, SeedableRng};
+    use rand::rngs::StdRng;
+
+    use crate::core::matrix::Matrix4;
+    use crate::core::vector::Vector;
+    use crate::intersection::hit;
+    use crate::shape::sphere::Sphere;
+    use crate::shape::triangle::Triangle;
+
+    use super::*;
+
+    fn random_scene(count: usize) -> Vec<Box<dyn Shape>> {
+        let mut rng = StdRng::seed_from_u64(7);
+
+        (0..count)
+            .map(|i| {
+                let center = Point::new(rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0), rng.gen_range(-10.0..10.0));
+                let shape: Box<dyn Shape> = match i % 2 {
+                    0 => {
+                        let mut s = Sphere::new();
+                        let r = rng.gen_range(0.05..0.5);
+                        s.set_transform(Matrix4::translation(center.x(), center.y(), center.z()) * Matrix4::scaling(r, r, r));
+                        Box::new(s)
+                    }
+                    _ => Box::new(Triangle::new(
+                        center,
+                        center + Vector::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)),
+                        center + Vector::new(rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0), rng.gen_range(-1.0..1.0)),
+                    )),
+                };
+                shape
+            })
+            .collect()
+    }
+
+    fn sorted_ts(xs: &[Intersection]) -> Vec<f64> {
+        let mut ts = xs.iter().filter(|i| i.t >= 0.0).map(|i| i.t).collect::<Vec<_>>();
+        ts.sort_by(|a, b| a.total_cmp(b));
+        ts
+    }
+
+    #[test]
+    fn building_a_bvh_over_no_shapes() {
+        // Given
+        let shapes: Vec<Box<dyn Shape>> = vec![];
+
+        // When
+        let bvh = Bvh::build(&shapes);
+
+        // Then
+        assert!(bvh.is_empty());
+        assert!(bvh.bounds().is_empty());
+        assert!(bvh.intersect(&shapes, &Ray::new(Point::zero(), Vector::new(0.0, 0.0, 1.0))).is_empty());
+    }
+
+    #[test]
+    fn bvh_bounds_contain_all_shapes() {
+        // Given
+        let shapes = random_scene(200);
+
+        // When
+        let bvh = Bvh::build(&shapes);
+
+        // Then
+        assert_eq!(bvh.len(), 200);
+        assert!(shapes.iter().all(|s| bvh.bounds().contains_box(&s.parent_space_bounds())));
+    }
+
+    #[test]
+    fn every_shape_ends_up_in_exactly_one_leaf() {
+        // Given
+        let shapes = random_scene(500);
+
+        // When
+        let bvh = Bvh::build(&shapes);
+        let mut indices = bvh.indices.clone();
+        indices.sort();
+
+        // Then
+        assert_eq!(indices, (0..500).collect::<Vec<_>>());
+        assert!(bvh.nodes.len() > 1);
+    }
+
+    #[test]
+    fn bvh_returns_the_same_hits_as_brute_force() {
+        // Given
+        let shapes = random_scene(500);
+        let bvh = Bvh::build(&shapes);
+        let mut rng = StdRng::seed_from_u64(42);
+
+        for _ in 0..500 {
+            let origin = Point::new(rng.gen_range(-15.0..15.0), rng.gen_range(-15.0..15.0), -20.0);
+            let direction = Vector::new(rng.gen_range(-0.5..0.5), rng.gen_range(-0.5..0.5), 1.0).normalize();
+            let r = Ray::new(origin, direction);
+
+            // When
+            let brute_force = shapes.iter().flat_map(|s| s.intersect(&r)).collect::<Vec<_>>();
+            let accelerated = bvh.intersect(&shapes, &r);
+
+            // Then
+            assert_eq!(sorted_ts(&accelerated), sorted_ts(&brute_force));
+            assert_eq!(hit(&accelerated).map(|i| i.t), hit(&brute_force).map(|i| i.t));
+        }
+    }
+
+    #[test]
+    fn identical_shapes_are_split_into_leaves() {
+        // Given
+        let shapes = (0..100).map(|_| Box::new(Sphere::new()) as Box<dyn Shape>).collect::<Vec<_>>();
+
+        // When
+        let bvh = Bvh::build(&shapes);
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // Then
+        assert!(bvh.nodes.iter().all(|n| match n.kind {
+            NodeKind::Leaf { count, .. } => count <= MAX_LEAF_SIZE,
+            NodeKind::Interior { .. }    => true,
+        }));
+        assert_eq!(bvh.intersect(&shapes, &r).len(), 200);
+    }
+}
@@ -0,0 +1,73 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::camera::{Camera, CameraData};
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::ray::Ray;
+
+/// A 360° panorama: the width of the canvas covers a full turn around the vertical axis, with the
+/// view direction in the middle, and the height goes from straight up to straight down. Canvases
+/// twice as wide as they are high have square pixels.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct EquirectangularCamera {
+    data: CameraData,
+}
+
+impl EquirectangularCamera {
+    pub fn new(hsize: usize, vsize: usize) -> Self {
+        Self {
+            data: CameraData::new(hsize, vsize),
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "equirectangular"))]
+impl Camera for EquirectangularCamera {
+    fn data(&self) -> &CameraData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut CameraData {
+        &mut self.data
+    }
+
+    fn local_ray(&self, x: f64, y: f64) -> Option<Ray> {
+        let longitude = (x / self.data.hsize as f64 - 0.5) * TAU;
+        let latitude = (0.5 - y / self.data.vsize as f64) * PI;
+        let (sin_longitude, cos_longitude) = longitude.sin_cos();
+        let (sin_latitude, cos_latitude) = latitude.sin_cos();
+        let direction = Vector::new(-sin_longitude * cos_latitude, sin_latitude, -cos_longitude * cos_latitude);
+
+        Some(Ray::new(Point::zero(), direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::core::matrix::Matrix4;
+
+    use super::*;
+
+    #[test]
+    fn the_canvas_covers_every_direction() {
+        // Given
+        let mut c = EquirectangularCamera::new(200, 100);
+
+        // Then
+        assert_eq!(c.ray_through(100.0, 50.0).unwrap().direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(c.ray_through(150.0, 50.0).unwrap().direction, Vector::new(-1.0, 0.0, 0.0));
+        assert_eq!(c.ray_through(0.0, 50.0).unwrap().direction, Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(c.ray_through(100.0, 0.0).unwrap().direction, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(c.ray_through(125.0, 25.0).unwrap().direction, Vector::new(-0.5, FRAC_1_SQRT_2, -0.5));
+
+        // When
+        c.set_transform(Matrix4::translation(0.0, -2.0, 0.0));
+
+        // Then
+        assert_eq!(c.ray_for_pixel(10, 10).unwrap().origin, Point::new(0.0, 2.0, 0.0));
+    }
+}
@@ -0,0 +1,90 @@
+use crate::camera::{Camera, CameraData};
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::ray::Ray;
+
+/// An equidistant fisheye: the distance from the center of the canvas is proportional to the
+/// angle away from the view direction. The image is a circle filling the shorter side of the
+/// canvas that spans `field_of_view`, which may be more than half a turn, and the camera sees
+/// nothing outside of it.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FisheyeCamera {
+    data: CameraData,
+    field_of_view: f64,
+}
+
+impl FisheyeCamera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        Self {
+            data: CameraData::new(hsize, vsize),
+            field_of_view,
+        }
+    }
+
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "fisheye"))]
+impl Camera for FisheyeCamera {
+    fn data(&self) -> &CameraData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut CameraData {
+        &mut self.data
+    }
+
+    fn local_ray(&self, x: f64, y: f64) -> Option<Ray> {
+        let radius = self.data.hsize.min(self.data.vsize) as f64 / 2.0;
+        let dx = (x - self.data.hsize as f64 / 2.0) / radius;
+        let dy = (y - self.data.vsize as f64 / 2.0) / radius;
+        let distance = (dx * dx + dy * dy).sqrt();
+        if distance > 1.0 {
+            return None;
+        }
+
+        let (sin, cos) = (distance * self.field_of_view / 2.0).sin_cos();
+        let direction = match distance > 0.0 {
+            true  => Vector::new(-dx / distance * sin, -dy / distance * sin, -cos),
+            false => Vector::new(0.0, 0.0, -1.0),
+        };
+
+        Some(Ray::new(Point::zero(), direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI, TAU};
+
+    use super::*;
+
+    #[test]
+    fn the_angle_grows_with_the_distance_from_the_center() {
+        // Given
+        let c = FisheyeCamera::new(101, 101, PI);
+
+        // Then
+        assert_eq!(c.ray_through(50.5, 50.5).unwrap().direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(c.ray_through(101.0, 50.5).unwrap().direction, Vector::new(-1.0, 0.0, 0.0));
+        assert_eq!(c.ray_through(50.5, 0.0).unwrap().direction, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(c.ray_through(50.5, 25.25).unwrap().direction, Vector::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn nothing_is_seen_outside_the_image_circle() {
+        // Given
+        let narrow = FisheyeCamera::new(200, 100, PI);
+        let wide = FisheyeCamera::new(100, 100, TAU);
+
+        // Then
+        assert!(narrow.ray_for_pixel(0, 0).is_none());
+        assert!(narrow.ray_for_pixel(30, 50).is_none());
+        assert!(narrow.ray_for_pixel(60, 50).is_some());
+        assert_eq!(wide.ray_through(50.0, 100.0).unwrap().direction, Vector::new(0.0, 0.0, 1.0));
+    }
+}
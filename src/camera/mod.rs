@@ -0,0 +1,159 @@
+pub mod equirectangular;
+pub mod fisheye;
+pub mod orthographic;
+pub mod perspective;
+pub mod stereo;
+
+use std::fmt::Debug;
+
+use crate::canvas::Canvas;
+use crate::core::matrix::Matrix4;
+use crate::random::random;
+use crate::ray::Ray;
+use crate::render::{render, RenderSettings};
+use crate::world::World;
+
+/// State shared by all cameras: the size of the canvas they render and where they are.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "CameraDataRepr"))]
+pub struct CameraData {
+    hsize: usize,
+    vsize: usize,
+    transform: Matrix4,
+    shutter: (f64, f64),
+    #[cfg_attr(feature = "serde", serde(skip))]
+    inverse: Matrix4,
+}
+
+impl CameraData {
+    pub fn new(hsize: usize, vsize: usize) -> Self {
+        Self {
+            hsize,
+            vsize,
+            transform: Matrix4::identity(),
+            shutter: (0.0, 1.0),
+            inverse: Matrix4::identity(),
+        }
+    }
+
+    /// Half the width and height of a view that is `half_view` across half of the longer side
+    /// of the canvas.
+    pub fn half_extent(&self, half_view: f64) -> (f64, f64) {
+        let aspect = self.hsize as f64 / self.vsize as f64;
+
+        match aspect >= 1.0 {
+            true  => (half_view, half_view / aspect),
+            false => (half_view * aspect, half_view),
+        }
+    }
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.transform = transform;
+        self.inverse = transform.invert();
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CameraDataRepr {
+    hsize: usize,
+    vsize: usize,
+    #[serde(default = "Matrix4::identity")]
+    transform: Matrix4,
+    #[serde(default = "default_shutter")]
+    shutter: (f64, f64),
+}
+
+#[cfg(feature = "serde")]
+fn default_shutter() -> (f64, f64) {
+    (0.0, 1.0)
+}
+
+#[cfg(feature = "serde")]
+impl From<CameraDataRepr> for CameraData {
+    fn from(repr: CameraDataRepr) -> Self {
+        let mut data = CameraData::new(repr.hsize, repr.vsize);
+        data.set_transform(repr.transform);
+        data.shutter = repr.shutter;
+
+        data
+    }
+}
+
+/// Sends rays into the world through the points of a canvas. Implementors only deal with camera
+/// space through `local_ray`, where the eye is at the origin looking down the negative z axis
+/// with y up, and the left of the canvas is towards positive x. The transform, usually a
+/// `Matrix4::view_transform`, takes world space to camera space.
+#[cfg_attr(feature = "serde", typetag::serde(tag = "type"))]
+pub trait Camera: Debug + Send + Sync {
+    fn data(&self) -> &CameraData;
+    fn data_mut(&mut self) -> &mut CameraData;
+
+    /// The ray in camera space through a point on the canvas, measured in pixels from its top
+    /// left corner, or `None` if the camera sees nothing there.
+    fn local_ray(&self, x: f64, y: f64) -> Option<Ray>;
+
+    fn hsize(&self) -> usize {
+        self.data().hsize
+    }
+
+    fn vsize(&self) -> usize {
+        self.data().vsize
+    }
+
+    /// Changes the size of the canvas while keeping what the camera sees across its longer side.
+    fn resize(&mut self, hsize: usize, vsize: usize) {
+        self.data_mut().hsize = hsize;
+        self.data_mut().vsize = vsize;
+    }
+
+    fn transform(&self) -> &Matrix4 {
+        &self.data().transform
+    }
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.data_mut().set_transform(transform);
+    }
+
+    /// When the shutter opens and closes, as times from 0.0 to 1.0 over which moving shapes go
+    /// from their transform to their end transform. Every ray is cast at a random time in
+    /// between, so shapes that move while the shutter is open are blurred along their way. By
+    /// default the shutter is open all the time.
+    fn shutter(&self) -> (f64, f64) {
+        self.data().shutter
+    }
+
+    fn set_shutter(&mut self, open: f64, close: f64) {
+        self.data_mut().shutter = (open, close);
+    }
+
+    /// The ray from the camera through the center of the pixel at (`px`, `py`).
+    fn ray_for_pixel(&self, px: usize, py: usize) -> Option<Ray> {
+        self.ray_through(px as f64 + 0.5, py as f64 + 0.5)
+    }
+
+    /// The ray in world space through a point on the canvas, measured in pixels from its top
+    /// left corner, or `None` if the camera sees nothing there.
+    fn ray_through(&self, x: f64, y: f64) -> Option<Ray> {
+        let ray = self.local_ray(x, y)?;
+        let inverse = self.data().inverse;
+        let (open, close) = self.shutter();
+        let time = match close > open {
+            true  => open + random::<f64>() * (close - open),
+            false => open,
+        };
+
+        Some(Ray::new(inverse * ray.origin, (inverse * ray.direction).normalize()).with_time(time))
+    }
+
+    /// Focuses on the first thing in `world` straight ahead of the camera, if the camera has a
+    /// lens that can be focused and it sees anything there.
+    fn autofocus(&mut self, _world: &World) {}
+
+    fn render(&self, world: &World) -> Canvas
+    where
+        Self: Sized,
+    {
+        render(self, world, &RenderSettings::default())
+    }
+}
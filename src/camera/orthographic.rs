@@ -0,0 +1,75 @@
+use crate::camera::{Camera, CameraData};
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::ray::Ray;
+
+/// A camera without perspective, sending parallel rays from a canvas that is `view_size` units
+/// across its longer side, so that things look the same size however far away they are.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct OrthographicCamera {
+    data: CameraData,
+    view_size: f64,
+}
+
+impl OrthographicCamera {
+    pub fn new(hsize: usize, vsize: usize, view_size: f64) -> Self {
+        Self {
+            data: CameraData::new(hsize, vsize),
+            view_size,
+        }
+    }
+
+    pub fn view_size(&self) -> f64 {
+        self.view_size
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "orthographic"))]
+impl Camera for OrthographicCamera {
+    fn data(&self) -> &CameraData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut CameraData {
+        &mut self.data
+    }
+
+    fn local_ray(&self, x: f64, y: f64) -> Option<Ray> {
+        let (half_width, half_height) = self.data.half_extent(self.view_size / 2.0);
+        let pixel_size = half_width * 2.0 / self.data.hsize as f64;
+        let origin = Point::new(half_width - x * pixel_size, half_height - y * pixel_size, 0.0);
+
+        Some(Ray::new(origin, Vector::new(0.0, 0.0, -1.0)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI, SQRT_2};
+
+    use crate::core::matrix::Matrix4;
+
+    use super::*;
+
+    #[test]
+    fn rays_are_parallel() {
+        // Given
+        let mut c = OrthographicCamera::new(200, 100, 4.0);
+
+        // When
+        let center = c.ray_through(100.0, 50.0).unwrap();
+        let corner = c.ray_for_pixel(0, 0).unwrap();
+        c.set_transform(Matrix4::rotation_y(PI / 4.0) * Matrix4::translation(0.0, -2.0, 5.0));
+        let transformed = c.ray_through(0.0, 50.0).unwrap();
+
+        // Then
+        assert_eq!(center.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(center.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(corner.origin, Point::new(1.99, 0.99, 0.0));
+        assert_eq!(corner.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(transformed.origin, Point::new(SQRT_2, 2.0, SQRT_2 - 5.0));
+        assert_eq!(transformed.direction, Vector::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2));
+    }
+}
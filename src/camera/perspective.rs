@@ -0,0 +1,260 @@
+use std::f64::consts::{FRAC_PI_2, TAU};
+
+use crate::camera::{Camera, CameraData};
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::intersection::hit;
+use crate::random::random;
+use crate::ray::Ray;
+use crate::world::World;
+
+/// The shape of the opening of a lens, which is the shape out of focus highlights take.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum ApertureShape {
+    /// A perfectly round opening.
+    #[default]
+    Disc,
+    /// A regular polygon formed by the given number of straight blades, with a corner at the top.
+    Blades(usize),
+}
+
+impl ApertureShape {
+    /// Maps a pair of numbers in `0..1` to a point on an aperture of radius 1, such that
+    /// uniformly distributed pairs cover it uniformly.
+    pub fn sample(&self, u: f64, v: f64) -> (f64, f64) {
+        match *self {
+            ApertureShape::Disc => {
+                let (sin, cos) = (v * TAU).sin_cos();
+                let r = u.sqrt();
+                (r * cos, r * sin)
+            }
+            ApertureShape::Blades(n) => {
+                // Pick one of the triangles between the center and two neighbouring corners, and
+                // then a point on it.
+                let u = u * n as f64;
+                let i = (u.floor() as usize).min(n - 1);
+                let corner = |i: usize| (FRAC_PI_2 + TAU * i as f64 / n as f64).sin_cos();
+                let ((y0, x0), (y1, x1)) = (corner(i), corner(i + 1));
+                let r = (u - i as f64).sqrt();
+                (r * (x0 + v * (x1 - x0)), r * (y0 + v * (y1 - y0)))
+            }
+        }
+    }
+}
+
+/// A camera one unit away from a canvas that spans `field_of_view` across its longer side.
+///
+/// By default it is a pinhole camera, with everything in focus. Giving it an `aperture` turns it
+/// into a thin lens: every ray starts from a random point on the lens, and only what is
+/// `focal_distance` away from the camera is sharp, while things nearer or further away are
+/// blurred the more the larger the aperture. It takes many samples per pixel to smooth the blur.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PerspectiveCamera {
+    data: CameraData,
+    field_of_view: f64,
+    /// The radius of the lens.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aperture: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub aperture_shape: ApertureShape,
+    /// The distance along the view direction at which things are in focus.
+    #[cfg_attr(feature = "serde", serde(default = "default_focal_distance"))]
+    pub focal_distance: f64,
+}
+
+#[cfg(feature = "serde")]
+fn default_focal_distance() -> f64 {
+    1.0
+}
+
+impl PerspectiveCamera {
+    pub fn new(hsize: usize, vsize: usize, field_of_view: f64) -> Self {
+        Self {
+            data: CameraData::new(hsize, vsize),
+            field_of_view,
+            aperture: 0.0,
+            aperture_shape: ApertureShape::Disc,
+            focal_distance: 1.0,
+        }
+    }
+
+    pub fn field_of_view(&self) -> f64 {
+        self.field_of_view
+    }
+
+    pub fn pixel_size(&self) -> f64 {
+        let (half_width, _) = self.data.half_extent((self.field_of_view / 2.0).tan());
+        half_width * 2.0 / self.data.hsize as f64
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "perspective"))]
+impl Camera for PerspectiveCamera {
+    fn data(&self) -> &CameraData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut CameraData {
+        &mut self.data
+    }
+
+    fn local_ray(&self, x: f64, y: f64) -> Option<Ray> {
+        let (half_width, half_height) = self.data.half_extent((self.field_of_view / 2.0).tan());
+        let pixel_size = self.pixel_size();
+        let pixel = Vector::new(half_width - x * pixel_size, half_height - y * pixel_size, -1.0);
+        if self.aperture <= 0.0 {
+            return Some(Ray::new(Point::zero(), pixel.normalize()));
+        }
+
+        // Rays from anywhere on the lens meet again on the plane in focus.
+        let (lx, ly) = self.aperture_shape.sample(random(), random());
+        let lens = Point::new(lx * self.aperture, ly * self.aperture, 0.0);
+        let focus = Point::zero() + pixel * self.focal_distance;
+
+        Some(Ray::new(lens, (focus - lens).normalize()))
+    }
+
+    fn autofocus(&mut self, world: &World) {
+        let inverse = self.data.inverse;
+        let ray = Ray::new(inverse * Point::zero(), (inverse * Vector::new(0.0, 0.0, -1.0)).normalize());
+        let xs = world.intersect(&ray);
+
+        if let Some(hit) = hit(&xs) {
+            self.focal_distance = -(*self.transform() * ray.position(hit.t)).z();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI, TAU};
+
+    use crate::color::Color;
+    use crate::core::float::ApproxEq;
+    use crate::core::matrix::Matrix4;
+    use crate::world::tests::default_world;
+
+    use super::*;
+
+    #[test]
+    fn constructing_a_camera() {
+        // Given
+        let c = PerspectiveCamera::new(160, 120, PI / 2.0);
+
+        // Then
+        assert_eq!(c.hsize(), 160);
+        assert_eq!(c.vsize(), 120);
+        assert_eq!(c.field_of_view(), PI / 2.0);
+        assert_eq!(*c.transform(), Matrix4::identity());
+    }
+
+    #[test]
+    fn pixel_size_for_horizontal_and_vertical_canvases() {
+        // Then
+        assert!(PerspectiveCamera::new(200, 125, PI / 2.0).pixel_size().approx_eq(0.01));
+        assert!(PerspectiveCamera::new(125, 200, PI / 2.0).pixel_size().approx_eq(0.01));
+    }
+
+    #[test]
+    fn constructing_rays_through_the_canvas() {
+        // Given
+        let mut c = PerspectiveCamera::new(201, 101, PI / 2.0);
+
+        // When
+        let center = c.ray_for_pixel(100, 50).unwrap();
+        let corner = c.ray_for_pixel(0, 0).unwrap();
+        c.set_transform(Matrix4::rotation_y(PI / 4.0) * Matrix4::translation(0.0, -2.0, 5.0));
+        let transformed = c.ray_for_pixel(100, 50).unwrap();
+
+        // Then
+        assert_eq!(center.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(center.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(corner.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(corner.direction, Vector::new(0.66519, 0.33259, -0.66851));
+        assert_eq!(transformed.origin, Point::new(0.0, 2.0, -5.0));
+        assert_eq!(transformed.direction, Vector::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn rendering_a_world_with_a_camera() {
+        // Given
+        let w = default_world();
+        let mut c = PerspectiveCamera::new(11, 11, PI / 2.0);
+        let from = Point::new(0.0, 0.0, -5.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+        c.set_transform(Matrix4::view_transform(from, to, up));
+
+        // When
+        let image = c.render(&w);
+
+        // Then
+        assert_eq!(image[(5, 5)], Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn sampling_aperture_shapes() {
+        // Given
+        let disc = ApertureShape::Disc;
+        let hexagon = ApertureShape::Blades(6);
+
+        // Then
+        let (x, y) = disc.sample(1.0, 0.25);
+        assert!(x.approx_eq(0.0) && y.approx_eq(1.0));
+        assert_eq!(disc.sample(0.0, 0.7), (0.0, 0.0));
+        let (x, y) = hexagon.sample(1.0, 1.0);
+        assert!(x.approx_eq(0.0) && y.approx_eq(1.0));
+        for i in 0..20 {
+            for j in 0..20 {
+                let (x, y) = hexagon.sample(i as f64 / 19.0, j as f64 / 19.0);
+                for k in 0..6 {
+                    let (sin, cos) = (PI / 2.0 + TAU * (k as f64 + 0.5) / 6.0).sin_cos();
+                    assert!(x * cos + y * sin <= (PI / 6.0).cos() + 1e-9);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn rays_through_a_lens_meet_in_the_focal_plane() {
+        // Given
+        let pinhole = PerspectiveCamera::new(11, 11, PI / 2.0);
+        let mut c = pinhole.clone();
+        c.aperture = 0.5;
+        c.focal_distance = 3.0;
+
+        // When
+        let expected = pinhole.ray_for_pixel(2, 3).unwrap();
+        let focus = expected.position(-3.0 / expected.direction.z());
+        let rays = (0..20).map(|_| c.ray_for_pixel(2, 3).unwrap()).collect::<Vec<_>>();
+
+        // Then
+        assert!(rays.iter().any(|r| r.origin != rays[0].origin));
+        for r in rays {
+            assert!(r.origin.z().approx_eq(0.0));
+            assert!((r.origin - Point::zero()).magnitude() <= 0.5);
+            assert_eq!(r.position((focus.z() - r.origin.z()) / r.direction.z()), focus);
+        }
+    }
+
+    #[test]
+    fn autofocusing_on_what_is_ahead() {
+        // Given
+        let w = default_world();
+        let mut c = PerspectiveCamera::new(11, 11, PI / 2.0);
+        c.set_transform(Matrix4::view_transform(Point::new(0.0, 0.0, -5.0), Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)));
+        let mut away = c.clone();
+        away.set_transform(Matrix4::view_transform(Point::new(0.0, 0.0, -5.0), Point::new(0.0, 0.0, -6.0), Vector::new(0.0, 1.0, 0.0)));
+
+        // When
+        c.autofocus(&w);
+        away.autofocus(&w);
+
+        // Then
+        assert!(c.focal_distance.approx_eq(4.0));
+        assert_eq!(away.focal_distance, 1.0);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::camera::{Camera, CameraData};
+use crate::core::matrix::Matrix4;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::ray::Ray;
+use crate::world::World;
+
+/// Two copies of a camera for the left and right eye, rendered side by side: the left eye on the
+/// left half of the canvas and the right eye on the right half.
+///
+/// The eyes are `separation` apart along the x axis of the camera. For panoramas, set
+/// `omnidirectional` to instead move them apart at right angles to each ray, as if the viewer
+/// turned their head to look along it, so that the depth is right in every direction. They come
+/// together as the ray turns towards straight up or down, where there is no right angle to pick.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct StereoCamera {
+    data: CameraData,
+    eye: Box<dyn Camera>,
+    pub separation: f64,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub omnidirectional: bool,
+}
+
+impl StereoCamera {
+    /// A stereo rig twice as wide as `eye`, and with its transform.
+    pub fn new(eye: Box<dyn Camera>, separation: f64) -> Self {
+        let mut data = CameraData::new(eye.hsize() * 2, eye.vsize());
+        data.set_transform(*eye.transform());
+
+        Self { data, eye, separation, omnidirectional: false }
+    }
+
+    /// The camera used for each eye, which has the same transform as the rig.
+    pub fn eye(&self) -> &dyn Camera {
+        self.eye.as_ref()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "stereo"))]
+impl Camera for StereoCamera {
+    fn data(&self) -> &CameraData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut CameraData {
+        &mut self.data
+    }
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.data.set_transform(transform);
+        self.eye.set_transform(transform);
+    }
+
+    fn autofocus(&mut self, world: &World) {
+        self.eye.autofocus(world);
+    }
+
+    fn resize(&mut self, hsize: usize, vsize: usize) {
+        self.eye.resize(hsize / 2, vsize);
+        self.data.hsize = self.eye.hsize() * 2;
+        self.data.vsize = vsize;
+    }
+
+    fn local_ray(&self, x: f64, y: f64) -> Option<Ray> {
+        // The left of the canvas is towards positive x.
+        let half = self.eye.hsize() as f64;
+        let (side, x) = match x < half {
+            true  => (1.0, x),
+            false => (-1.0, x - half),
+        };
+        let ray = self.eye.local_ray(x, y)?;
+
+        let across = match self.omnidirectional {
+            true  => ray.direction.cross(Vector::new(0.0, 1.0, 0.0)),
+            false => Vector::new(1.0, 0.0, 0.0),
+        };
+
+        Some(Ray::new(ray.origin + across * (side * self.separation / 2.0), ray.direction))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::camera::equirectangular::EquirectangularCamera;
+    use crate::camera::perspective::PerspectiveCamera;
+    use crate::core::point::Point;
+
+    use super::*;
+
+    #[test]
+    fn the_eyes_are_side_by_side() {
+        // Given
+        let mut c = StereoCamera::new(Box::new(PerspectiveCamera::new(10, 10, PI / 2.0)), 0.1);
+
+        // When
+        let left = c.ray_for_pixel(5, 5).unwrap();
+        let right = c.ray_for_pixel(15, 5).unwrap();
+        c.set_transform(Matrix4::translation(0.0, 0.0, 5.0));
+        let moved = c.ray_for_pixel(15, 5).unwrap();
+
+        // Then
+        assert_eq!(c.hsize(), 20);
+        assert_eq!(left.origin, Point::new(0.05, 0.0, 0.0));
+        assert_eq!(right.origin, Point::new(-0.05, 0.0, 0.0));
+        assert_eq!(left.direction, right.direction);
+        assert_eq!(moved.origin, Point::new(-0.05, 0.0, -5.0));
+    }
+
+    #[test]
+    fn resizing_resizes_both_eyes() {
+        // Given
+        let mut c = StereoCamera::new(Box::new(PerspectiveCamera::new(10, 10, PI / 2.0)), 0.1);
+
+        // When
+        c.resize(41, 20);
+
+        // Then
+        assert_eq!((c.hsize(), c.vsize()), (40, 20));
+        assert_eq!((c.eye().hsize(), c.eye().vsize()), (20, 20));
+    }
+
+    #[test]
+    fn omnidirectional_eyes_are_apart_across_each_ray() {
+        // Given
+        let mut c = StereoCamera::new(Box::new(EquirectangularCamera::new(200, 100)), 0.1);
+        c.omnidirectional = true;
+
+        // When
+        let ahead = c.ray_through(100.0, 50.0).unwrap();
+        let right = c.ray_through(150.0, 50.0).unwrap();
+        let up = c.ray_through(100.0, 0.0).unwrap();
+        let raised = c.ray_through(100.0, 100.0 / 6.0).unwrap();
+
+        // Then
+        assert_eq!(ahead.origin, Point::new(0.05, 0.0, 0.0));
+        assert_eq!(right.origin, Point::new(0.0, 0.0, -0.05));
+        assert_eq!(up.origin, Point::new(0.0, 0.0, 0.0));
+        assert_eq!(raised.origin, Point::new(0.025, 0.0, 0.0));
+    }
+}
@@ -1,5 +1,5 @@
 use std::fs::File;
-use std::io::BufWriter;
+use std::io::{self, BufWriter, Write};
 use std::path::Path;
 
 use crate::color::Color;
@@ -27,19 +27,27 @@ impl Canvas {
         self.height as u32
     }
 
-    pub fn save_to(&self, path: &impl AsRef<Path>) {
-        let file = File::create(path).unwrap();
-        let ref mut w = BufWriter::new(file);
+    pub fn save_to(&self, path: &impl AsRef<Path>) -> io::Result<()> {
+        let file = File::create(path)?;
+        self.write_png(BufWriter::new(file))
+    }
 
-        let mut encoder = png::Encoder::new(w, self.width as u32, self.height as u32);
+    /// Encodes the canvas as a PNG image.
+    pub fn write_png(&self, output: impl Write) -> io::Result<()> {
+        let mut encoder = png::Encoder::new(output, self.width as u32, self.height as u32);
         encoder.set_color(png::ColorType::Rgb);
         encoder.set_depth(png::BitDepth::Eight);
 
-        let mut writer = encoder.write_header().unwrap();
-        let pixel_data = self.pixels.iter()
+        let mut writer = encoder.write_header()?;
+        writer.write_image_data(&self.to_rgb())?;
+        Ok(writer.finish()?)
+    }
+
+    /// The pixels as 8-bit RGB values, row by row from the top.
+    pub fn to_rgb(&self) -> Vec<u8> {
+        self.pixels.iter()
             .flat_map(|c| c.to_rgb())
-            .collect::<Vec<u8>>();
-        writer.write_image_data(&pixel_data).unwrap()
+            .collect()
     }
 }
 
@@ -57,6 +65,7 @@ impl std::ops::IndexMut<(usize, usize)> for Canvas {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -82,4 +91,17 @@ mod tests {
         // Then
         assert_eq!(canvas[(1,2)], Color::new(1.0, 0.0, 0.0));
     }
+
+    #[test]
+    fn saving_to_a_missing_directory_is_an_error() {
+        // Given
+        let canvas = Canvas::new(1, 1);
+        let path = std::env::temp_dir().join("punytracer-missing-directory").join("out.png");
+
+        // When
+        let result = canvas.save_to(&path);
+
+        // Then
+        assert_eq!(result.unwrap_err().kind(), io::ErrorKind::NotFound);
+    }
 }
\ No newline at end of file
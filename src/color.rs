@@ -1,6 +1,15 @@
 use crate::core::float::ApproxEq;
+use crate::spectrum::{color_matching, LAMBDA_MAX, LAMBDA_MIN};
 
-#[derive(Clone, Debug, Default)]
+/// The white point of sRGB, D65, in CIE XYZ.
+pub const D65: [f64; 3] = [0.95047, 1.0, 1.08883];
+
+/// Where the cube root of CIE L*a*b* gives way to a straight line, `(6 / 29)³`, and its cube root.
+const LAB_EPSILON: f64 = 216.0 / 24389.0;
+const LAB_DELTA: f64 = 6.0 / 29.0;
+
+#[derive(Clone, Copy, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Color {
     pub r: f64,
     pub g: f64,
@@ -31,6 +40,224 @@ impl Color {
 
         [r, g, b]
     }
+
+    /// Whether the color is exactly `other`, unlike `==` which allows for rounding errors.
+    pub fn exact_eq(&self, other: &Self) -> bool {
+        self.r == other.r && self.g == other.g && self.b == other.b
+    }
+
+    /// The luminance of the color taken as linear sRGB, which is the Y of its CIE XYZ.
+    pub fn luminance(&self) -> f64 {
+        0.2126729 * self.r + 0.7151522 * self.g + 0.0721750 * self.b
+    }
+
+    /// The color a fraction `t` of the way from this one to `other`.
+    pub fn lerp(&self, other: Self, t: f64) -> Self {
+        *self + (other - *self) * t
+    }
+
+    pub fn min(&self, other: Self) -> Self {
+        Self::new(self.r.min(other.r), self.g.min(other.g), self.b.min(other.b))
+    }
+
+    pub fn max(&self, other: Self) -> Self {
+        Self::new(self.r.max(other.r), self.g.max(other.g), self.b.max(other.b))
+    }
+
+    /// Each channel of the color kept between the channels of `min` and `max`.
+    pub fn clamp(&self, min: Self, max: Self) -> Self {
+        self.max(min).min(max)
+    }
+
+    /// The color with the sRGB transfer function applied, as it's stored in 8-bit images, from
+    /// linear sRGB.
+    pub fn to_srgb(&self) -> Self {
+        let encode = |c: f64| match c <= 0.0031308 {
+            true  => 12.92 * c,
+            false => 1.055 * c.powf(1.0 / 2.4) - 0.055,
+        };
+
+        Self::new(encode(self.r), encode(self.g), encode(self.b))
+    }
+
+    /// The linear sRGB color of a color with the sRGB transfer function applied.
+    pub fn from_srgb(srgb: Self) -> Self {
+        let decode = |c: f64| match c <= 0.04045 {
+            true  => c / 12.92,
+            false => ((c + 0.055) / 1.055).powf(2.4),
+        };
+
+        Self::new(decode(srgb.r), decode(srgb.g), decode(srgb.b))
+    }
+
+    /// The CIE XYZ of the color taken as linear sRGB.
+    pub fn to_xyz(&self) -> [f64; 3] {
+        [
+            0.4124564 * self.r + 0.3575761 * self.g + 0.1804375 * self.b,
+            0.2126729 * self.r + 0.7151522 * self.g + 0.0721750 * self.b,
+            0.0193339 * self.r + 0.1191920 * self.g + 0.9503041 * self.b,
+        ]
+    }
+
+    /// The linear sRGB color of the CIE XYZ color `(x, y, z)`, which is out of gamut where any
+    /// channel is negative.
+    pub fn from_xyz(x: f64, y: f64, z: f64) -> Self {
+        Self::new(
+            3.2404542 * x - 1.5371385 * y - 0.4985314 * z,
+            -0.9692660 * x + 1.8760108 * y + 0.0415560 * z,
+            0.0556434 * x - 0.2040259 * y + 1.0572252 * z,
+        )
+    }
+
+    /// The CIE L*a*b* of the color taken as linear sRGB, relative to the D65 white of sRGB, with
+    /// a lightness from 0 to 100.
+    pub fn to_lab(&self) -> [f64; 3] {
+        let f = |t: f64| match t > LAB_EPSILON {
+            true  => t.cbrt(),
+            false => t / (3.0 * LAB_DELTA * LAB_DELTA) + 4.0 / 29.0,
+        };
+        let [x, y, z] = self.to_xyz();
+        let [fx, fy, fz] = [f(x / D65[0]), f(y / D65[1]), f(z / D65[2])];
+
+        [116.0 * fy - 16.0, 500.0 * (fx - fy), 200.0 * (fy - fz)]
+    }
+
+    /// The linear sRGB color of the CIE L*a*b* color `(l, a, b)`.
+    pub fn from_lab(l: f64, a: f64, b: f64) -> Self {
+        let f = |t: f64| match t > LAB_DELTA {
+            true  => t * t * t,
+            false => 3.0 * LAB_DELTA * LAB_DELTA * (t - 4.0 / 29.0),
+        };
+        let fy = (l + 16.0) / 116.0;
+
+        Self::from_xyz(f(fy + a / 500.0) * D65[0], f(fy) * D65[1], f(fy - b / 200.0) * D65[2])
+    }
+
+    /// The hue in degrees from 0 to 360, saturation and value of the channels of the color as
+    /// they are.
+    pub fn to_hsv(&self) -> [f64; 3] {
+        let (max, min) = (self.r.max(self.g).max(self.b), self.r.min(self.g).min(self.b));
+        let saturation = match max > 0.0 {
+            true  => (max - min) / max,
+            false => 0.0,
+        };
+
+        [self.hue(), saturation, max]
+    }
+
+    /// The color with the hue `h` in degrees, saturation `s` and value `v`.
+    pub fn from_hsv(h: f64, s: f64, v: f64) -> Self {
+        let chroma = v * s;
+
+        Self::from_hue(h, chroma, v - chroma)
+    }
+
+    /// The hue in degrees from 0 to 360, saturation and lightness of the channels of the color as
+    /// they are.
+    pub fn to_hsl(&self) -> [f64; 3] {
+        let (max, min) = (self.r.max(self.g).max(self.b), self.r.min(self.g).min(self.b));
+        let lightness = (max + min) / 2.0;
+        let saturation = match max > min {
+            true  => (max - min) / (1.0 - (2.0 * lightness - 1.0).abs()),
+            false => 0.0,
+        };
+
+        [self.hue(), saturation, lightness]
+    }
+
+    /// The color with the hue `h` in degrees, saturation `s` and lightness `l`.
+    pub fn from_hsl(h: f64, s: f64, l: f64) -> Self {
+        let chroma = (1.0 - (2.0 * l - 1.0).abs()) * s;
+
+        Self::from_hue(h, chroma, l - chroma / 2.0)
+    }
+
+    /// The color of a hex string like `#ff8000` or `#f80`, the way colors are written for the web:
+    /// sRGB with its transfer function applied, so the color is decoded to linear sRGB.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        let digits = hex.strip_prefix('#').unwrap_or(hex);
+        if !digits.bytes().all(|b| b.is_ascii_hexdigit()) {
+            return None;
+        }
+        let channel = |i: usize, width: usize| {
+            let value = u8::from_str_radix(&digits[i * width..(i + 1) * width], 16).ok()?;
+            Some(match width {
+                1 => value * 17,
+                _ => value,
+            } as f64 / 255.0)
+        };
+        let width = match digits.len() {
+            3 => 1,
+            6 => 2,
+            _ => return None,
+        };
+
+        Some(Self::from_srgb(Self::new(channel(0, width)?, channel(1, width)?, channel(2, width)?)))
+    }
+
+    /// The hex string like `#ff8000` of the color taken as linear sRGB, clamped to between black
+    /// and white.
+    pub fn to_hex(&self) -> String {
+        let srgb = self.to_srgb();
+        let byte = |c: f64| (c.clamp(0.0, 1.0) * 255.0).round() as u8;
+
+        format!("#{:02x}{:02x}{:02x}", byte(srgb.r), byte(srgb.g), byte(srgb.b))
+    }
+
+    /// The color of the light a black body gives off at a temperature of `kelvin`, worked out
+    /// from Planck's law and the color matching functions, with a luminance of 1. Colors of
+    /// temperatures that are out of the gamut of sRGB are clamped to it.
+    pub fn blackbody(kelvin: f64) -> Self {
+        // The second radiation constant in nm K.
+        const C2: f64 = 1.438777e7;
+        let mut xyz = [0.0; 3];
+        let mut lambda = LAMBDA_MIN;
+        while lambda <= LAMBDA_MAX {
+            let radiance = 1.0 / (lambda.powi(5) * ((C2 / (lambda * kelvin)).exp() - 1.0));
+            for (total, matching) in xyz.iter_mut().zip(color_matching(lambda)) {
+                *total += matching * radiance;
+            }
+            lambda += 1.0;
+        }
+        if xyz[1] <= 0.0 {
+            return Self::default();
+        }
+        let color = Self::from_xyz(xyz[0] / xyz[1], 1.0, xyz[2] / xyz[1]).max(Self::default());
+
+        color * (1.0 / color.luminance())
+    }
+
+    /// The hue in degrees of the channels of the color as they are, 0 for grays.
+    fn hue(&self) -> f64 {
+        let (max, min) = (self.r.max(self.g).max(self.b), self.r.min(self.g).min(self.b));
+        let chroma = max - min;
+        if chroma <= 0.0 {
+            return 0.0;
+        }
+        let sixths = match max {
+            max if max == self.r => ((self.g - self.b) / chroma).rem_euclid(6.0),
+            max if max == self.g => (self.b - self.r) / chroma + 2.0,
+            _                    => (self.r - self.g) / chroma + 4.0,
+        };
+
+        sixths * 60.0
+    }
+
+    /// The color with the hue `h` in degrees and `chroma`, raised by `m` on every channel.
+    fn from_hue(h: f64, chroma: f64, m: f64) -> Self {
+        let sixths = (h / 60.0).rem_euclid(6.0);
+        let x = chroma * (1.0 - (sixths % 2.0 - 1.0).abs());
+        let (r, g, b) = match sixths as u8 {
+            0 => (chroma, x, 0.0),
+            1 => (x, chroma, 0.0),
+            2 => (0.0, chroma, x),
+            3 => (0.0, x, chroma),
+            4 => (x, 0.0, chroma),
+            _ => (chroma, 0.0, x),
+        };
+
+        Self::new(r + m, g + m, b + m)
+    }
 }
 
 impl PartialEq for Color {
@@ -73,6 +300,37 @@ impl std::ops::Mul<f64> for Color {
     }
 }
 
+impl std::ops::Div for Color {
+    type Output = Self;
+
+    fn div(self, rhs: Self) -> Self::Output {
+        Self::new(self.r / rhs.r, self.g / rhs.g, self.b / rhs.b)
+    }
+}
+
+impl std::ops::Div<f64> for Color {
+    type Output = Self;
+
+    fn div(self, rhs: f64) -> Self::Output {
+        Self::new(self.r / rhs, self.g / rhs, self.b / rhs)
+    }
+}
+
+impl std::ops::Neg for Color {
+    type Output = Self;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.r, -self.g, -self.b)
+    }
+}
+
+impl std::iter::Sum for Color {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::default(), |acc, c| acc + c)
+    }
+}
+
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -125,4 +383,131 @@ mod tests {
         // Then
         assert_eq!(Color::new(0.9, 0.2, 0.04), c1 * c2);
     }
+
+    #[test]
+    fn divide_and_negate_colors() {
+        // Given
+        let c1 = Color::new(0.9, 0.2, 0.4);
+        let c2 = Color::new(0.3, 0.4, 2.0);
+
+        // Then
+        assert_eq!(Color::new(3.0, 0.5, 0.2), c1 / c2);
+        assert_eq!(Color::new(0.45, 0.1, 0.2), c1 / 2.0);
+        assert_eq!(Color::new(-0.9, -0.2, -0.4), -c1);
+        assert_eq!(Color::new(1.2, 0.6, 2.4), [c1, c2].into_iter().sum());
+    }
+
+    #[test]
+    fn colors_in_between_and_within_others() {
+        // Given
+        let c1 = Color::new(0.9, 0.2, 0.4);
+        let c2 = Color::new(0.3, 0.4, 2.0);
+
+        // Then
+        assert_eq!(Color::new(0.75, 0.25, 0.8), c1.lerp(c2, 0.25));
+        assert_eq!(Color::new(0.3, 0.2, 0.4), c1.min(c2));
+        assert_eq!(Color::new(0.9, 0.4, 2.0), c1.max(c2));
+        assert_eq!(Color::new(0.5, 0.2, 1.0), c2.clamp(Color::new(0.5, 0.0, 0.0), Color::new(1.0, 0.2, 1.0)));
+        assert!((Color::new(1.0, 1.0, 1.0).luminance() - 1.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn exact_equality_sees_small_differences() {
+        // Given
+        let c1 = Color::new(0.5, 0.5, 0.5);
+        let c2 = Color::new(0.5, 0.5, 0.5001);
+
+        // Then
+        assert_eq!(c1, c2);
+        assert!(!c1.exact_eq(&c2));
+        assert!(c1.exact_eq(&Color::new(0.5, 0.5, 0.5)));
+    }
+
+    #[test]
+    fn convert_between_linear_and_encoded_srgb() {
+        // Given
+        let color = Color::new(0.0, 0.002, 0.5);
+
+        // When
+        let encoded = color.to_srgb();
+
+        // Then
+        assert_eq!(Color::new(0.0, 0.02584, 0.73536), encoded);
+        assert_eq!(color, Color::from_srgb(encoded));
+    }
+
+    #[test]
+    fn convert_to_and_from_xyz_and_lab() {
+        // Given
+        let red = Color::new(1.0, 0.0, 0.0);
+        let white = Color::new(1.0, 1.0, 1.0);
+
+        // When
+        let [x, y, z] = red.to_xyz();
+        let [l, a, b] = red.to_lab();
+        let [lw, aw, bw] = white.to_lab();
+
+        // Then
+        assert_eq!(Color::new(0.4124, 0.2126, 0.0193), Color::new(x, y, z));
+        for (actual, expected) in [(l, 53.24), (a, 80.09), (b, 67.20)] {
+            assert!((actual - expected).abs() < 0.05, "{} against {}", actual, expected);
+        }
+        assert_eq!(red, Color::from_xyz(x, y, z));
+        assert_eq!(red, Color::from_lab(l, a, b));
+        assert_eq!(Color::new(100.0, 0.0, 0.0), Color::new(lw, aw, bw));
+        assert_eq!(Color::new(0.0, 0.0, 0.0), Color::from_lab(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn convert_to_and_from_hsv_and_hsl() {
+        // Given
+        let orange = Color::new(1.0, 0.5, 0.0);
+        let teal = Color::new(0.2, 0.6, 0.6);
+
+        // When
+        let [h, s, v] = orange.to_hsv();
+        let [th, ts, tl] = teal.to_hsl();
+
+        // Then
+        assert_eq!(Color::new(30.0, 1.0, 1.0), Color::new(h, s, v));
+        assert_eq!(Color::new(180.0, 0.5, 0.4), Color::new(th, ts, tl));
+        assert_eq!(orange, Color::from_hsv(h, s, v));
+        assert_eq!(teal, Color::from_hsl(th, ts, tl));
+        assert_eq!(Color::new(1.0, 0.0, 0.5), Color::from_hsv(-30.0, 1.0, 1.0));
+        assert_eq!(Color::new(0.3, 0.0, 0.0).to_hsv()[0], 0.0);
+        assert_eq!(Color::new(0.3, 0.3, 0.3).to_hsl(), [0.0, 0.0, 0.3]);
+    }
+
+    #[test]
+    fn convert_to_and_from_hex_strings() {
+        // Given
+        let color = Color::from_hex("#ff8000").unwrap();
+
+        // Then
+        assert_eq!(Color::new(1.0, 0.21586, 0.0), color);
+        assert_eq!("#ff8000", color.to_hex());
+        assert_eq!(Color::from_hex("f80"), Color::from_hex("#ff8800"));
+        assert_eq!("#ffffff", Color::new(2.0, 1.0, 1.5).to_hex());
+        assert_eq!(None, Color::from_hex("#ff80"));
+        assert_eq!(None, Color::from_hex("#gg8000"));
+        assert_eq!(None, Color::from_hex("#ff80é"));
+        assert_eq!(None, Color::from_hex("#+f+f+f"));
+        assert_eq!(None, Color::from_hex("+f+f+f"));
+    }
+
+    #[test]
+    fn black_bodies_go_from_red_to_blue_as_they_heat_up() {
+        // When
+        let candle = Color::blackbody(1900.0);
+        let daylight = Color::blackbody(6504.0);
+        let sky = Color::blackbody(12000.0);
+
+        // Then
+        assert!((candle.luminance() - 1.0).abs() < 1e-9);
+        assert!(candle.r > candle.g && candle.g > candle.b);
+        assert!(sky.b > sky.g && sky.g > sky.r);
+        for c in [daylight.r, daylight.g, daylight.b] {
+            assert!((c - 1.0).abs() < 0.1, "{:?}", daylight);
+        }
+    }
 }
\ No newline at end of file
@@ -1,6 +1,9 @@
 const EPSILON: f64 = 1.0e-7;
 const EPSILON_LOW: f64 = 1.0e-3;
 
+/// Distance by which hit points are nudged off a surface to avoid self-intersection ("acne").
+pub const EPSILON_OFFSET: f64 = 1.0e-5;
+
 pub trait ApproxEq<Rhs = Self> {
     fn approx_eq(self, other: Rhs) -> bool;
     fn approx_eq_low_precision(self, other: Rhs) -> bool;
@@ -8,16 +11,10 @@ pub trait ApproxEq<Rhs = Self> {
 
 impl ApproxEq for f64 {
     fn approx_eq(self, other: Self) -> bool {
-        match (self - other).abs()  {
-            n if n < EPSILON => true,
-            _                => false,
-        }
+        (self - other).abs() < EPSILON
     }
 
     fn approx_eq_low_precision(self, other: Self) -> bool {
-        match (self - other).abs()  {
-            n if n < EPSILON_LOW => true,
-            _                    => false,
-        }
+        (self - other).abs() < EPSILON_LOW
     }
 }
@@ -1,12 +1,26 @@
 use crate::core::float::ApproxEq;
+use crate::core::point::Point;
+use crate::core::quaternion::Quaternion;
 use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
 
 pub type Matrix2 = Matrix<2>;
 pub type Matrix3 = Matrix<3>;
 pub type Matrix4 = Matrix<4>;
 
+/// An affine transform split into the parts it applies in turn: a scale, then a shear, then a
+/// rotation and finally a translation, so that `Matrix4::compose` gives back the transform.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Decomposition {
+    pub translation: Vector,
+    pub rotation: Quaternion,
+    pub scale: Vector,
+    /// The `xy`, `xz` and `yz` arguments of `Matrix4::shearing`, the others being 0.
+    pub shear: [f64; 3],
+}
+
 #[derive(Clone, Copy, Debug)]
-struct Matrix<const N: usize> {
+pub struct Matrix<const N: usize> {
     data: [[f64; N]; N]
 }
 
@@ -32,6 +46,37 @@ impl<const N: usize> Matrix<N> {
     }
 }
 
+impl<const N: usize> Default for Matrix<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// Matrices are (de)serialized as a list of rows.
+#[cfg(feature = "serde")]
+impl<const N: usize> serde::Serialize for Matrix<N> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.data.iter().map(|row| &row[..]))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> serde::Deserialize<'de> for Matrix<N> {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let rows = Vec::<Vec<f64>>::deserialize(deserializer)?;
+        if rows.len() != N || rows.iter().any(|row| row.len() != N) {
+            return Err(serde::de::Error::custom(format!("expected a {}x{} matrix", N, N)));
+        }
+
+        let mut matrix = Self::new();
+        for (y, row) in rows.into_iter().enumerate() {
+            matrix.data[y].copy_from_slice(&row);
+        }
+
+        Ok(matrix)
+    }
+}
+
 impl Matrix<2> {
     pub fn identity() -> Self {
         Self {
@@ -157,6 +202,169 @@ impl Matrix<4> {
 
         m2
     }
+
+    pub fn translation(x: f64, y: f64, z: f64) -> Self {
+        Self {
+            data: [[1.0, 0.0, 0.0, x],
+                   [0.0, 1.0, 0.0, y],
+                   [0.0, 0.0, 1.0, z],
+                   [0.0, 0.0, 0.0, 1.0]],
+        }
+    }
+
+    pub fn scaling(x: f64, y: f64, z: f64) -> Self {
+        Self {
+            data: [[x, 0.0, 0.0, 0.0],
+                   [0.0, y, 0.0, 0.0],
+                   [0.0, 0.0, z, 0.0],
+                   [0.0, 0.0, 0.0, 1.0]],
+        }
+    }
+
+    pub fn rotation_x(radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+
+        Self {
+            data: [[1.0, 0.0, 0.0, 0.0],
+                   [0.0, cos, -sin, 0.0],
+                   [0.0, sin, cos, 0.0],
+                   [0.0, 0.0, 0.0, 1.0]],
+        }
+    }
+
+    pub fn rotation_y(radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+
+        Self {
+            data: [[cos, 0.0, sin, 0.0],
+                   [0.0, 1.0, 0.0, 0.0],
+                   [-sin, 0.0, cos, 0.0],
+                   [0.0, 0.0, 0.0, 1.0]],
+        }
+    }
+
+    pub fn rotation_z(radians: f64) -> Self {
+        let (sin, cos) = radians.sin_cos();
+
+        Self {
+            data: [[cos, -sin, 0.0, 0.0],
+                   [sin, cos, 0.0, 0.0],
+                   [0.0, 0.0, 1.0, 0.0],
+                   [0.0, 0.0, 0.0, 1.0]],
+        }
+    }
+
+    pub fn shearing(xy: f64, xz: f64, yx: f64, yz: f64, zx: f64, zy: f64) -> Self {
+        Self {
+            data: [[1.0, xy, xz, 0.0],
+                   [yx, 1.0, yz, 0.0],
+                   [zx, zy, 1.0, 0.0],
+                   [0.0, 0.0, 0.0, 1.0]],
+        }
+    }
+
+    pub fn view_transform(from: Point, to: Point, up: Vector) -> Self {
+        let forward = (to - from).normalize();
+        let left = forward.cross(up.normalize());
+        let true_up = left.cross(forward);
+
+        let orientation = Self {
+            data: [[left.x(), left.y(), left.z(), 0.0],
+                   [true_up.x(), true_up.y(), true_up.z(), 0.0],
+                   [-forward.x(), -forward.y(), -forward.z(), 0.0],
+                   [0.0, 0.0, 0.0, 1.0]],
+        };
+
+        orientation * Self::translation(-from.x(), -from.y(), -from.z())
+    }
+
+    /// Splits an affine transform, one with a bottom row of `0 0 0 1`, into a translation,
+    /// rotation, shear and scale.
+    ///
+    /// A mirroring transform gets a negative x scale, and anything else that looks like a
+    /// negative scale is a rotation. Singular transforms get a scale of 0 for each axis they
+    /// flatten, with no shear along it, and are not mirrored.
+    pub fn decompose(&self) -> Decomposition {
+        let columns = [0, 1, 2].map(|c| Vector::new(self[(0, c)], self[(1, c)], self[(2, c)]));
+        // Columns shorter than this add no new direction, relative to the size of the transform.
+        let tolerance = columns.iter().map(Vector::magnitude).fold(0.0, f64::max) * 1.0e-9;
+
+        // Gram-Schmidt: the columns are orthonormal axes times an upper triangular matrix. The axes
+        // of columns that add no new direction are left at zero until all the others are known.
+        let mut axes = [Vector::zero(); 3];
+        let mut upper = [[0.0; 3]; 3];
+        for j in 0..3 {
+            let mut residual = columns[j];
+            for i in 0..j {
+                upper[i][j] = axes[i].dot(residual);
+                residual = residual - axes[i] * upper[i][j];
+            }
+
+            let length = residual.magnitude();
+            if length > tolerance {
+                axes[j] = residual / length;
+                upper[j][j] = length;
+            }
+        }
+        for j in 0..3 {
+            if upper[j][j] == 0.0 {
+                axes[j] = orthogonal_axis(&axes);
+            }
+        }
+
+        // Turn a reflection into a negative scale, preferably on an axis that is flattened anyway.
+        if axes[0].dot(axes[1].cross(axes[2])) < 0.0 {
+            let k = (0..3).find(|&k| upper[k][k] == 0.0).unwrap_or(0);
+            axes[k] = -axes[k];
+            for value in &mut upper[k][k..] {
+                *value = -*value;
+            }
+        }
+
+        let per = |value: f64, scale: f64| match scale == 0.0 {
+            true  => 0.0,
+            false => value / scale,
+        };
+        let rotation = Self::from([
+            [axes[0].x(), axes[1].x(), axes[2].x(), 0.0],
+            [axes[0].y(), axes[1].y(), axes[2].y(), 0.0],
+            [axes[0].z(), axes[1].z(), axes[2].z(), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ]);
+
+        Decomposition {
+            translation: Vector::new(self[(0, 3)], self[(1, 3)], self[(2, 3)]),
+            rotation: Quaternion::from_matrix(&rotation),
+            scale: Vector::new(upper[0][0], upper[1][1], upper[2][2]),
+            shear: [per(upper[0][1], upper[1][1]), per(upper[0][2], upper[2][2]), per(upper[1][2], upper[2][2])],
+        }
+    }
+
+    /// The transform that `decompose` splits up.
+    pub fn compose(decomposition: &Decomposition) -> Self {
+        let Decomposition { translation: t, rotation, scale: s, shear: [xy, xz, yz] } = *decomposition;
+
+        Self::translation(t.x(), t.y(), t.z())
+            * rotation.to_matrix()
+            * Self::shearing(xy, xz, 0.0, yz, 0.0, 0.0)
+            * Self::scaling(s.x(), s.y(), s.z())
+    }
+}
+
+/// A unit vector orthogonal to `axes`, which are unit vectors orthogonal to each other or zero,
+/// and of which at most two are not zero.
+fn orthogonal_axis(axes: &[Vector]) -> Vector {
+    [Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0), Vector::new(0.0, 0.0, 1.0)]
+        .map(|mut v| {
+            for axis in axes {
+                v = v - *axis * axis.dot(v);
+            }
+            v
+        })
+        .into_iter()
+        .max_by(|a, b| a.magnitude().total_cmp(&b.magnitude()))
+        .expect("there are three candidates")
+        .normalize()
 }
 
 impl<const N: usize> PartialEq for Matrix<N> {
@@ -219,8 +427,10 @@ impl<T> std::ops::Mul<T> for Matrix<4>
     }
 }
 
+#[cfg(test)]
 mod tests {
-    use crate::core::point::Point;
+    use std::f64::consts::PI;
+
     use super::*;
 
     #[test]
@@ -533,7 +743,7 @@ mod tests {
 
         // Then
         assert_eq!(m.determinant(), -2120.0);
-        assert_eq!(m.is_invertible(), true);
+        assert!(m.is_invertible());
     }
 
     #[test]
@@ -548,7 +758,7 @@ mod tests {
 
         // Then
         assert_eq!(m.determinant(), 0.0);
-        assert_eq!(m.is_invertible(), false);
+        assert!(!m.is_invertible());
     }
 
     #[test]
@@ -632,4 +842,204 @@ mod tests {
         assert_eq!(m1 * m2 * m2.invert(), m1);
     }
 
+    #[test]
+    fn multiplying_by_a_translation_matrix() {
+        // Given
+        let transform = Matrix4::translation(5.0, -3.0, 2.0);
+        let p = Point::new(-3.0, 4.0, 5.0);
+
+        // Then
+        assert_eq!(transform * p, Point::new(2.0, 1.0, 7.0));
+    }
+
+    #[test]
+    fn multiplying_by_the_inverse_of_a_translation_matrix() {
+        // Given
+        let transform = Matrix4::translation(5.0, -3.0, 2.0);
+        let p = Point::new(-3.0, 4.0, 5.0);
+
+        // Then
+        assert_eq!(transform.invert() * p, Point::new(-8.0, 7.0, 3.0));
+    }
+
+    #[test]
+    fn translation_does_not_affect_vectors() {
+        // Given
+        let transform = Matrix4::translation(5.0, -3.0, 2.0);
+        let v = Vector::new(-3.0, 4.0, 5.0);
+
+        // Then
+        assert_eq!(transform * v, v);
+    }
+
+    #[test]
+    fn scaling_matrix_applied_to_a_point_and_vector() {
+        // Given
+        let transform = Matrix4::scaling(2.0, 3.0, 4.0);
+
+        // Then
+        assert_eq!(transform * Point::new(-4.0, 6.0, 8.0), Point::new(-8.0, 18.0, 32.0));
+        assert_eq!(transform * Vector::new(-4.0, 6.0, 8.0), Vector::new(-8.0, 18.0, 32.0));
+        assert_eq!(transform.invert() * Vector::new(-4.0, 6.0, 8.0), Vector::new(-2.0, 2.0, 2.0));
+    }
+
+    #[test]
+    fn rotating_a_point_around_each_axis() {
+        // Given
+        let p = Point::new(0.0, 1.0, 0.0);
+        let q = Point::new(0.0, 0.0, 1.0);
+
+        // Then
+        assert_eq!(Matrix4::rotation_x(PI / 4.0) * p, Point::new(0.0, 2f64.sqrt() / 2.0, 2f64.sqrt() / 2.0));
+        assert_eq!(Matrix4::rotation_x(PI / 2.0) * p, Point::new(0.0, 0.0, 1.0));
+        assert_eq!(Matrix4::rotation_y(PI / 2.0) * q, Point::new(1.0, 0.0, 0.0));
+        assert_eq!(Matrix4::rotation_z(PI / 2.0) * p, Point::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn shearing_moves_components_in_proportion() {
+        // Given
+        let p = Point::new(2.0, 3.0, 4.0);
+
+        // Then
+        assert_eq!(Matrix4::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0) * p, Point::new(5.0, 3.0, 4.0));
+        assert_eq!(Matrix4::shearing(0.0, 0.0, 0.0, 0.0, 0.0, 1.0) * p, Point::new(2.0, 3.0, 7.0));
+    }
+
+    #[test]
+    fn chained_transformations_apply_in_reverse_order() {
+        // Given
+        let p = Point::new(1.0, 0.0, 1.0);
+        let t = Matrix4::translation(10.0, 5.0, 7.0)
+            * Matrix4::scaling(5.0, 5.0, 5.0)
+            * Matrix4::rotation_x(PI / 2.0);
+
+        // Then
+        assert_eq!(t * p, Point::new(15.0, 0.0, 7.0));
+    }
+
+    #[test]
+    fn view_transform_for_default_orientation() {
+        // Given
+        let from = Point::new(0.0, 0.0, 0.0);
+        let to = Point::new(0.0, 0.0, -1.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        // Then
+        assert_eq!(Matrix4::view_transform(from, to, up), Matrix4::identity());
+    }
+
+    #[test]
+    fn view_transform_moves_the_world() {
+        // Given
+        let from = Point::new(0.0, 0.0, 8.0);
+        let to = Point::new(0.0, 0.0, 0.0);
+        let up = Vector::new(0.0, 1.0, 0.0);
+
+        // Then
+        assert_eq!(Matrix4::view_transform(from, to, up), Matrix4::translation(0.0, 0.0, -8.0));
+    }
+
+    #[test]
+    fn arbitrary_view_transform() {
+        // Given
+        let from = Point::new(1.0, 3.0, 2.0);
+        let to = Point::new(4.0, -2.0, 8.0);
+        let up = Vector::new(1.0, 1.0, 0.0);
+
+        // Then
+        assert_eq!(Matrix4::view_transform(from, to, up), Matrix4::from(
+            [[ -0.50709, 0.50709, 0.67612, -2.36643 ],
+             [ 0.76772, 0.60609, 0.12122, -2.82843 ],
+             [ -0.35857, 0.59761, -0.71714, 0.00000 ],
+             [ 0.00000, 0.00000, 0.00000, 1.00000 ]]
+        ));
+    }
+
+    #[test]
+    fn decomposing_a_transform() {
+        // Given
+        let t = Matrix4::translation(5.0, -3.0, 2.0)
+            * Matrix4::rotation_y(0.5)
+            * Matrix4::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0)
+            * Matrix4::scaling(2.0, 3.0, 4.0);
+
+        // When
+        let d = t.decompose();
+
+        // Then
+        assert_eq!(d.translation, Vector::new(5.0, -3.0, 2.0));
+        assert_eq!(d.rotation.to_matrix(), Matrix4::rotation_y(0.5));
+        assert_eq!(d.scale, Vector::new(2.0, 3.0, 4.0));
+        assert!(d.shear[0].approx_eq(1.0) && d.shear[1].approx_eq(0.0) && d.shear[2].approx_eq(0.0));
+        assert_eq!(Matrix4::compose(&d), t);
+    }
+
+    #[test]
+    fn decomposing_a_reflection_gives_a_negative_scale() {
+        // Given
+        let t = Matrix4::rotation_z(1.0) * Matrix4::scaling(-1.0, 2.0, 3.0);
+
+        // When
+        let d = t.decompose();
+
+        // Then
+        assert_eq!(d.scale, Vector::new(-1.0, 2.0, 3.0));
+        assert_eq!(d.rotation.to_matrix(), Matrix4::rotation_z(1.0));
+        assert_eq!(Matrix4::compose(&d), t);
+        assert_eq!(Matrix4::scaling(1.0, -1.0, -1.0).decompose().rotation.to_matrix(), Matrix4::rotation_x(PI));
+    }
+
+    #[test]
+    fn decomposing_and_composing_the_transforms_of_the_other_tests() {
+        // Given
+        let transforms = [
+            Matrix4::identity(),
+            Matrix4::translation(5.0, -3.0, 2.0),
+            Matrix4::scaling(2.0, 3.0, 4.0),
+            Matrix4::scaling(-1.0, 1.0, 1.0),
+            Matrix4::rotation_x(PI / 4.0),
+            Matrix4::rotation_y(PI / 2.0),
+            Matrix4::rotation_z(PI),
+            Matrix4::shearing(1.0, 0.0, 0.0, 0.0, 0.0, 0.0),
+            Matrix4::shearing(0.0, 1.0, 0.0, 0.0, 0.0, 0.0),
+            Matrix4::shearing(0.0, 0.0, 1.0, 0.0, 0.0, 0.0),
+            Matrix4::shearing(0.0, 0.0, 0.0, 1.0, 0.0, 0.0),
+            Matrix4::shearing(0.0, 0.0, 0.0, 0.0, 1.0, 0.0),
+            Matrix4::shearing(0.0, 0.0, 0.0, 0.0, 0.0, 1.0),
+            Matrix4::translation(10.0, 5.0, 7.0) * Matrix4::scaling(5.0, 5.0, 5.0) * Matrix4::rotation_x(PI / 2.0),
+            Matrix4::view_transform(Point::new(1.0, 3.0, 2.0), Point::new(4.0, -2.0, 8.0), Vector::new(1.0, 1.0, 0.0)),
+        ];
+
+        for t in transforms {
+            // When
+            let d = t.decompose();
+
+            // Then
+            assert!(d.rotation.magnitude().approx_eq(1.0));
+            assert_eq!(Matrix4::compose(&d), t);
+        }
+    }
+
+    #[test]
+    fn decomposing_singular_transforms() {
+        // Given
+        let transforms = [
+            Matrix4::scaling(0.0, 2.0, 3.0),
+            Matrix4::rotation_y(1.0) * Matrix4::scaling(2.0, 0.0, -3.0),
+            Matrix4::scaling(0.0, 0.0, 0.0),
+            Matrix4::rotation_z(0.5) * Matrix4::scaling(1.0e-12, 1.0, 1.0),
+        ];
+
+        for t in transforms {
+            // When
+            let d = t.decompose();
+
+            // Then
+            assert!(d.rotation.magnitude().approx_eq(1.0));
+            assert!(d.shear.iter().all(|s| s.is_finite()));
+            assert_eq!(Matrix4::compose(&d), t);
+        }
+        assert_eq!(Matrix4::scaling(2.0, 0.0, -3.0).decompose().scale, Vector::new(2.0, 0.0, 3.0));
+    }
 }
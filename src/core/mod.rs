@@ -2,4 +2,5 @@ pub mod float;
 pub mod point;
 pub mod tuple;
 pub mod vector;
-pub mod matrix;
\ No newline at end of file
+pub mod matrix;
+pub mod quaternion;
\ No newline at end of file
@@ -3,6 +3,7 @@ use crate::core::tuple::Tuple;
 use crate::core::vector::Vector;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Point {
     x: f64,
     y: f64,
@@ -105,6 +106,7 @@ impl std::ops::Div<f64> for Point {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -129,7 +131,7 @@ mod tests {
         let p = Point::new(1.0, -2.0, 3.0);
 
         // Then
-        assert_eq!(Point::new(3.5, -7.0, 10.5), p.clone() * 3.5);
+        assert_eq!(Point::new(3.5, -7.0, 10.5), p * 3.5);
         assert_eq!(Point::new(3.5, -7.0, 10.5), 3.5 * p);
     }
 
@@ -0,0 +1,295 @@
+use crate::core::float::ApproxEq;
+use crate::core::matrix::Matrix4;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+
+/// A rotation, stored as a unit quaternion `w + xi + yj + zk`.
+///
+/// Like matrices, quaternions compose from right to left: `a * b` rotates by `b` first and then
+/// by `a`, and multiplying a point or vector by a quaternion rotates it about the origin. `q` and
+/// `-q` are the same rotation, but they do not compare equal.
+#[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Quaternion {
+    pub w: f64,
+    pub x: f64,
+    pub y: f64,
+    pub z: f64,
+}
+
+impl Quaternion {
+    pub fn new(w: f64, x: f64, y: f64, z: f64) -> Self {
+        Self { w, x, y, z, }
+    }
+
+    /// The rotation that leaves everything where it is.
+    pub fn identity() -> Self {
+        Self::new(1.0, 0.0, 0.0, 0.0)
+    }
+
+    /// A rotation by `radians` about `axis`, in the same direction as `Matrix4::rotation_x` and
+    /// friends rotate about theirs.
+    pub fn from_axis_angle(axis: Vector, radians: f64) -> Self {
+        let axis = axis.normalize();
+        let (sin, cos) = (radians / 2.0).sin_cos();
+
+        Self::new(cos, axis.x() * sin, axis.y() * sin, axis.z() * sin)
+    }
+
+    /// A rotation about the x axis, then the y axis, then the z axis, like the transforms
+    /// `[rotate-x, x]`, `[rotate-y, y]`, `[rotate-z, z]` listed in that order in a scene file.
+    pub fn from_euler(x: f64, y: f64, z: f64) -> Self {
+        Self::from_axis_angle(Vector::new(0.0, 0.0, 1.0), z)
+            * Self::from_axis_angle(Vector::new(0.0, 1.0, 0.0), y)
+            * Self::from_axis_angle(Vector::new(1.0, 0.0, 0.0), x)
+    }
+
+    /// The rotation of a matrix whose upper left 3x3 part is a rotation, that is orthonormal with
+    /// a determinant of 1. Any translation is ignored.
+    pub fn from_matrix(m: &Matrix4) -> Self {
+        // Pick the largest of w, x, y and z to divide by, to stay away from dividing by zero.
+        let trace = m[(0, 0)] + m[(1, 1)] + m[(2, 2)];
+        let q = if trace > 0.0 {
+            let s = (trace + 1.0).sqrt() * 2.0;
+            Self::new(s / 4.0, (m[(2, 1)] - m[(1, 2)]) / s, (m[(0, 2)] - m[(2, 0)]) / s, (m[(1, 0)] - m[(0, 1)]) / s)
+        } else if m[(0, 0)] > m[(1, 1)] && m[(0, 0)] > m[(2, 2)] {
+            let s = (1.0 + m[(0, 0)] - m[(1, 1)] - m[(2, 2)]).sqrt() * 2.0;
+            Self::new((m[(2, 1)] - m[(1, 2)]) / s, s / 4.0, (m[(0, 1)] + m[(1, 0)]) / s, (m[(0, 2)] + m[(2, 0)]) / s)
+        } else if m[(1, 1)] > m[(2, 2)] {
+            let s = (1.0 + m[(1, 1)] - m[(0, 0)] - m[(2, 2)]).sqrt() * 2.0;
+            Self::new((m[(0, 2)] - m[(2, 0)]) / s, (m[(0, 1)] + m[(1, 0)]) / s, s / 4.0, (m[(1, 2)] + m[(2, 1)]) / s)
+        } else {
+            let s = (1.0 + m[(2, 2)] - m[(0, 0)] - m[(1, 1)]).sqrt() * 2.0;
+            Self::new((m[(1, 0)] - m[(0, 1)]) / s, (m[(0, 2)] + m[(2, 0)]) / s, (m[(1, 2)] + m[(2, 1)]) / s, s / 4.0)
+        };
+
+        q.normalize()
+    }
+
+    pub fn to_matrix(&self) -> Matrix4 {
+        let Self { w, x, y, z } = self.normalize();
+
+        Matrix4::from([
+            [1.0 - 2.0 * (y * y + z * z), 2.0 * (x * y - w * z), 2.0 * (x * z + w * y), 0.0],
+            [2.0 * (x * y + w * z), 1.0 - 2.0 * (x * x + z * z), 2.0 * (y * z - w * x), 0.0],
+            [2.0 * (x * z - w * y), 2.0 * (y * z + w * x), 1.0 - 2.0 * (x * x + y * y), 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    pub fn dot(&self, other: Quaternion) -> f64 {
+        self.w * other.w + self.x * other.x + self.y * other.y + self.z * other.z
+    }
+
+    pub fn magnitude(&self) -> f64 {
+        self.dot(*self).sqrt()
+    }
+
+    pub fn normalize(&self) -> Self {
+        let magnitude = self.magnitude();
+        Self::new(self.w / magnitude, self.x / magnitude, self.y / magnitude, self.z / magnitude)
+    }
+
+    /// The opposite rotation, which for a unit quaternion is also its inverse.
+    pub fn conjugate(&self) -> Self {
+        Self::new(self.w, -self.x, -self.y, -self.z)
+    }
+
+    /// Interpolates linearly and normalizes the result. Cheaper than `slerp` and close to it
+    /// for nearby rotations, but it does not turn at a constant speed.
+    pub fn nlerp(&self, other: Quaternion, t: f64) -> Self {
+        let other = self.nearest(other);
+
+        Self::new(
+            self.w + (other.w - self.w) * t,
+            self.x + (other.x - self.x) * t,
+            self.y + (other.y - self.y) * t,
+            self.z + (other.z - self.z) * t,
+        ).normalize()
+    }
+
+    /// Spherical linear interpolation: turns from `self` to `other` the short way round, at a
+    /// constant speed.
+    pub fn slerp(&self, other: Quaternion, t: f64) -> Self {
+        let other = self.nearest(other);
+        let cos = self.dot(other).min(1.0);
+
+        // Nearly the same rotation, where the sine below gets too small to divide by.
+        if cos > 0.9995 {
+            return self.nlerp(other, t);
+        }
+
+        let angle = cos.acos();
+        let a = ((1.0 - t) * angle).sin() / angle.sin();
+        let b = (t * angle).sin() / angle.sin();
+
+        Self::new(
+            a * self.w + b * other.w,
+            a * self.x + b * other.x,
+            a * self.y + b * other.y,
+            a * self.z + b * other.z,
+        )
+    }
+
+    /// `other` or `-other`, whichever is closer to `self`, so that interpolating between them
+    /// takes the shortest path.
+    fn nearest(&self, other: Quaternion) -> Quaternion {
+        match self.dot(other) < 0.0 {
+            true  => -other,
+            false => other,
+        }
+    }
+}
+
+impl Default for Quaternion {
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+impl PartialEq for Quaternion {
+    fn eq(&self, other: &Self) -> bool {
+        self.w.approx_eq_low_precision(other.w)
+            && self.x.approx_eq_low_precision(other.x)
+            && self.y.approx_eq_low_precision(other.y)
+            && self.z.approx_eq_low_precision(other.z)
+    }
+}
+
+impl std::ops::Neg for Quaternion {
+    type Output = Quaternion;
+
+    fn neg(self) -> Self::Output {
+        Self::new(-self.w, -self.x, -self.y, -self.z)
+    }
+}
+
+impl std::ops::Mul for Quaternion {
+    type Output = Quaternion;
+
+    fn mul(self, rhs: Self) -> Self::Output {
+        Self::new(
+            self.w * rhs.w - self.x * rhs.x - self.y * rhs.y - self.z * rhs.z,
+            self.w * rhs.x + self.x * rhs.w + self.y * rhs.z - self.z * rhs.y,
+            self.w * rhs.y - self.x * rhs.z + self.y * rhs.w + self.z * rhs.x,
+            self.w * rhs.z + self.x * rhs.y - self.y * rhs.x + self.z * rhs.w,
+        )
+    }
+}
+
+impl<T> std::ops::Mul<T> for Quaternion
+    where T: Tuple
+{
+    type Output = T;
+
+    fn mul(self, rhs: T) -> Self::Output {
+        // v + 2u × (u × v + wv), with u the vector part of the quaternion.
+        let q = self.normalize();
+        let u = Vector::new(q.x, q.y, q.z);
+        let v = Vector::new(rhs.x(), rhs.y(), rhs.z());
+        let rotated = v + u.cross(u.cross(v) + v * q.w) * 2.0;
+
+        Self::Output::new(rotated.x(), rotated.y(), rotated.z())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+    use crate::core::point::Point;
+
+    use super::*;
+
+    #[test]
+    fn rotating_a_point_about_an_axis() {
+        // Given
+        let p = Point::new(0.0, 1.0, 0.0);
+        let half_quarter = Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), PI / 4.0);
+        let full_quarter = Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), PI / 2.0);
+
+        // Then
+        assert_eq!(half_quarter * p, Point::new(0.0, FRAC_1_SQRT_2, FRAC_1_SQRT_2));
+        assert_eq!(full_quarter * p, Point::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn rotations_match_the_rotation_matrices() {
+        // Given
+        let v = Vector::new(1.0, -2.0, 3.0);
+        let cases = [
+            (Vector::new(1.0, 0.0, 0.0), Matrix4::rotation_x(0.7)),
+            (Vector::new(0.0, 1.0, 0.0), Matrix4::rotation_y(0.7)),
+            (Vector::new(0.0, 0.0, 2.0), Matrix4::rotation_z(0.7)),
+        ];
+
+        for (axis, matrix) in cases {
+            // When
+            let q = Quaternion::from_axis_angle(axis, 0.7);
+
+            // Then
+            assert_eq!(q * v, matrix * v);
+            assert_eq!(q.to_matrix(), matrix);
+        }
+    }
+
+    #[test]
+    fn composing_rotations() {
+        // Given
+        let x = Quaternion::from_axis_angle(Vector::new(1.0, 0.0, 0.0), PI / 2.0);
+        let y = Quaternion::from_axis_angle(Vector::new(0.0, 1.0, 0.0), PI / 2.0);
+        let p = Point::new(0.0, 0.0, 1.0);
+
+        // Then
+        assert_eq!((y * x) * p, y * (x * p));
+        assert_eq!((y * x).to_matrix(), Matrix4::rotation_y(PI / 2.0) * Matrix4::rotation_x(PI / 2.0));
+        assert_eq!(x * x.conjugate(), Quaternion::identity());
+    }
+
+    #[test]
+    fn rotations_from_euler_angles() {
+        // Given
+        let q = Quaternion::from_euler(0.3, -1.2, 2.5);
+
+        // Then
+        assert_eq!(q.to_matrix(), Matrix4::rotation_z(2.5) * Matrix4::rotation_y(-1.2) * Matrix4::rotation_x(0.3));
+    }
+
+    #[test]
+    fn converting_matrices_to_quaternions_and_back() {
+        // Given
+        let matrices = [
+            Matrix4::identity(),
+            Matrix4::rotation_x(PI),
+            Matrix4::rotation_y(PI),
+            Matrix4::rotation_z(PI),
+            Matrix4::rotation_y(-2.0) * Matrix4::rotation_x(3.0),
+            Matrix4::rotation_z(1.0) * Matrix4::rotation_y(0.5) * Matrix4::rotation_x(-0.25),
+        ];
+
+        for m in matrices {
+            // When
+            let q = Quaternion::from_matrix(&m);
+
+            // Then
+            assert!(q.magnitude().approx_eq(1.0));
+            assert_eq!(q.to_matrix(), m);
+        }
+    }
+
+    #[test]
+    fn slerp_turns_at_a_constant_speed_the_short_way_round() {
+        // Given
+        let axis = Vector::new(0.0, 1.0, 0.0);
+        let a = Quaternion::from_axis_angle(axis, 0.0);
+        let b = Quaternion::from_axis_angle(axis, 3.0 * PI / 2.0);
+
+        // Then
+        assert_eq!(a.slerp(b, 0.0), a);
+        assert_eq!(a.slerp(b, 1.0), -b);
+        assert_eq!(a.slerp(b, 0.5), Quaternion::from_axis_angle(axis, -PI / 4.0));
+        assert_eq!(a.slerp(b, 0.25), Quaternion::from_axis_angle(axis, -PI / 8.0));
+        assert_eq!(a.nlerp(b, 0.5), Quaternion::from_axis_angle(axis, -PI / 4.0));
+        assert_eq!(a.slerp(a, 0.5), a);
+    }
+}
@@ -7,6 +7,7 @@ pub trait Tuple {
     fn w(&self) -> f64;
 }
 
+#[cfg(test)]
 mod tests {
     use crate::core::point::Point;
     use crate::core::vector::Vector;
@@ -2,6 +2,7 @@ use crate::core::float::ApproxEq;
 use crate::core::tuple::Tuple;
 
 #[derive(Clone, Copy, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Vector {
     x: f64,
     y: f64,
@@ -28,6 +29,10 @@ impl Vector {
             self.x * other.y - self.y * other.x
         )
     }
+
+    pub fn reflect(&self, normal: Vector) -> Self {
+        *self - normal * 2.0 * self.dot(normal)
+    }
 }
 
 impl Tuple for Vector {
@@ -112,6 +117,7 @@ impl std::ops::Div<f64> for Vector {
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
 
@@ -145,7 +151,7 @@ mod tests {
         let v = Vector::new(1.0, -2.0, 3.0);
 
         // Then
-        assert_eq!(Vector::new(3.5, -7.0, 10.5), v.clone() * 3.5);
+        assert_eq!(Vector::new(3.5, -7.0, 10.5), v * 3.5);
         assert_eq!(Vector::new(3.5, -7.0, 10.5), 3.5 * v);
     }
 
@@ -251,4 +257,24 @@ mod tests {
         assert_eq!(v1.cross(v2), Vector::new(-1.0, 2.0, -1.0));
         assert_eq!(v2.cross(v1), Vector::new(1.0, -2.0, 1.0));
     }
+
+    #[test]
+    fn reflecting_a_vector_approaching_at_45_degrees() {
+        // Given
+        let v = Vector::new(1.0, -1.0, 0.0);
+        let n = Vector::new(0.0, 1.0, 0.0);
+
+        // Then
+        assert_eq!(v.reflect(n), Vector::new(1.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn reflecting_a_vector_off_a_slanted_surface() {
+        // Given
+        let v = Vector::new(0.0, -1.0, 0.0);
+        let n = Vector::new(2f64.sqrt() / 2.0, 2f64.sqrt() / 2.0, 0.0);
+
+        // Then
+        assert_eq!(v.reflect(n), Vector::new(1.0, 0.0, 0.0));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,22 @@
+use std::io;
+use std::path::Path;
+
+use exr::prelude::read_first_rgba_layer_from_file;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+/// Loads the first layer of an OpenEXR file with red, green and blue channels.
+pub fn load(path: impl AsRef<Path>) -> io::Result<Canvas> {
+    let image = read_first_rgba_layer_from_file(
+        path,
+        |resolution, _| Canvas::new(resolution.width(), resolution.height()),
+        |canvas: &mut Canvas, position, (r, g, b, _): (f32, f32, f32, f32)| {
+            canvas[(position.x(), position.y())] = Color::new(r as f64, g as f64, b as f64);
+        },
+    );
+
+    image
+        .map(|image| image.layer_data.channel_data.pixels)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))
+}
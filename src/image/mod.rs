@@ -0,0 +1,21 @@
+pub mod exr;
+pub mod png;
+pub mod radiance;
+
+use std::io;
+use std::path::Path;
+
+use crate::canvas::Canvas;
+
+/// Loads an image, either a high dynamic range Radiance `.hdr` or OpenEXR `.exr` file or a `.png`
+/// file depending on the extension of `path`.
+pub fn load(path: impl AsRef<Path>) -> io::Result<Canvas> {
+    let path = path.as_ref();
+
+    match path.extension().and_then(|e| e.to_str()).map(str::to_ascii_lowercase).as_deref() {
+        Some("hdr" | "pic") => radiance::load(path),
+        Some("exr")         => exr::load(path),
+        Some("png")         => png::load(path),
+        _                   => Err(io::Error::new(io::ErrorKind::InvalidInput, "expected an .hdr, .exr or .png image")),
+    }
+}
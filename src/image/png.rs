@@ -0,0 +1,57 @@
+use std::fs::File;
+use std::io::{self, BufReader, Read};
+use std::path::Path;
+
+use png::{ColorType, Decoder, Transformations};
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+/// Loads a PNG image with the values of its pixels as they are stored, from 0 to 1, the way
+/// `Canvas::write_png` writes them. Gray images have the same value in every channel.
+pub fn load(path: impl AsRef<Path>) -> io::Result<Canvas> {
+    decode(BufReader::new(File::open(path)?))
+}
+
+pub fn decode(input: impl Read) -> io::Result<Canvas> {
+    let mut decoder = Decoder::new(input);
+    decoder.set_transformations(Transformations::normalize_to_color8());
+    let mut reader = decoder.read_info()?;
+    let mut buffer = vec![0; reader.output_buffer_size()];
+    let info = reader.next_frame(&mut buffer)?;
+
+    let channels = info.color_type.samples();
+    let (width, height) = (info.width as usize, info.height as usize);
+    let mut canvas = Canvas::new(width, height);
+    for (i, pixel) in buffer[..info.buffer_size()].chunks_exact(channels).enumerate() {
+        let value = |c: usize| pixel[c] as f64 / 255.0;
+        canvas[(i % width, i / width)] = match info.color_type {
+            ColorType::Grayscale | ColorType::GrayscaleAlpha => Color::new(value(0), value(0), value(0)),
+            _                                                => Color::new(value(0), value(1), value(2)),
+        };
+    }
+
+    Ok(canvas)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn images_come_back_as_they_were_written() {
+        // Given
+        let mut canvas = Canvas::new(3, 2);
+        canvas[(0, 0)] = Color::new(1.0, 0.0, 0.0);
+        canvas[(2, 1)] = Color::new(0.2, 0.4, 0.6);
+        let mut data = vec![];
+        canvas.write_png(&mut data).unwrap();
+
+        // When
+        let image = decode(&data[..]).unwrap();
+
+        // Then
+        assert_eq!((image.width(), image.height()), (3, 2));
+        assert_eq!(image.to_rgb(), canvas.to_rgb());
+    }
+}
@@ -0,0 +1,164 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+
+pub fn load(path: impl AsRef<Path>) -> io::Result<Canvas> {
+    parse(&fs::read(path)?)
+}
+
+/// Decodes an image in the Radiance RGBE format, whose pixels share an exponent between their
+/// three channels. Scanlines can be stored flat or run-length encoded channel by channel.
+pub fn parse(data: &[u8]) -> io::Result<Canvas> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let mut lines = data.split(|&b| b == b'\n');
+    let mut offset = 0;
+    let mut next_line = || {
+        let line = lines.next()?;
+        offset += line.len() + 1;
+        Some(String::from_utf8_lossy(line).into_owned())
+    };
+
+    if !next_line().is_some_and(|magic| magic.starts_with("#?")) {
+        return Err(invalid("not a Radiance image"));
+    }
+    loop {
+        match next_line() {
+            Some(line) if line.is_empty() => break,
+            Some(line) if line.starts_with("FORMAT=") && line != "FORMAT=32-bit_rle_rgbe" => {
+                return Err(invalid("only RGBE images are supported"));
+            }
+            Some(_) => (),
+            None    => return Err(invalid("the header is not terminated")),
+        }
+    }
+
+    let resolution = next_line().ok_or_else(|| invalid("missing resolution"))?;
+    let (height, width) = match resolution.split_whitespace().collect::<Vec<_>>()[..] {
+        ["-Y", height, "+X", width] => (height.parse::<usize>().ok(), width.parse::<usize>().ok()),
+        _ => return Err(invalid("only images stored from the top left row by row are supported")),
+    };
+    let (Some(height), Some(width)) = (height, width) else { return Err(invalid("invalid resolution")) };
+
+    let mut canvas = Canvas::new(width, height);
+    let mut reader = Scanlines { data, offset, width };
+    for y in 0..height {
+        let scanline = reader.next().ok_or_else(|| invalid("the image is truncated"))?;
+        for (x, rgbe) in scanline.iter().enumerate() {
+            canvas[(x, y)] = decode(*rgbe);
+        }
+    }
+
+    Ok(canvas)
+}
+
+/// The color of a pixel, from its mantissas and shared exponent.
+fn decode([r, g, b, e]: [u8; 4]) -> Color {
+    if e == 0 {
+        return Color::default();
+    }
+    let scale = 2f64.powi(e as i32 - (128 + 8));
+
+    Color::new(r as f64 * scale, g as f64 * scale, b as f64 * scale)
+}
+
+struct Scanlines<'a> {
+    data: &'a [u8],
+    offset: usize,
+    width: usize,
+}
+
+impl Iterator for Scanlines<'_> {
+    type Item = Vec<[u8; 4]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let start = self.data.get(self.offset..self.offset + 4)?;
+        let encoded = (8..0x8000).contains(&self.width) && start[0] == 2 && start[1] == 2 && start[2] & 0x80 == 0;
+        if !encoded {
+            let pixels = self.data.get(self.offset..self.offset + 4 * self.width)?;
+            self.offset += 4 * self.width;
+            return Some(pixels.chunks(4).map(|p| [p[0], p[1], p[2], p[3]]).collect());
+        }
+        if ((start[2] as usize) << 8 | start[3] as usize) != self.width {
+            return None;
+        }
+        self.offset += 4;
+
+        // Each channel is stored in turn, as runs of a repeated byte or of literal bytes.
+        let mut channels = vec![vec![0; self.width]; 4];
+        for channel in &mut channels {
+            let mut x = 0;
+            while x < self.width {
+                let count = *self.data.get(self.offset)? as usize;
+                let (run, literal) = match count > 128 {
+                    true  => (count - 128, false),
+                    false => (count, true),
+                };
+                if run == 0 || x + run > self.width {
+                    return None;
+                }
+                for i in 0..run {
+                    let at = match literal {
+                        true  => self.offset + 1 + i,
+                        false => self.offset + 1,
+                    };
+                    channel[x + i] = *self.data.get(at)?;
+                }
+                self.offset += match literal {
+                    true  => 1 + run,
+                    false => 2,
+                };
+                x += run;
+            }
+        }
+
+        Some((0..self.width).map(|x| [channels[0][x], channels[1][x], channels[2][x], channels[3][x]]).collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn header(width: usize, height: usize) -> Vec<u8> {
+        format!("#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y {} +X {}\n", height, width).into_bytes()
+    }
+
+    #[test]
+    fn reading_flat_scanlines() {
+        // Given
+        let mut data = header(2, 1);
+        data.extend([128, 64, 0, 129, 0, 0, 0, 0]);
+
+        // When
+        let canvas = parse(&data).unwrap();
+
+        // Then
+        assert_eq!((canvas.width(), canvas.height()), (2, 1));
+        assert_eq!(canvas[(0, 0)], Color::new(1.0, 0.5, 0.0));
+        assert_eq!(canvas[(1, 0)], Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn reading_run_length_encoded_scanlines() {
+        // Given
+        let mut data = header(8, 1);
+        data.extend([2, 2, 0, 8]);
+        data.extend([128 + 8, 255]);
+        data.extend([4, 1, 2, 3, 4, 128 + 4, 0]);
+        data.extend([128 + 8, 0]);
+        data.extend([128 + 8, 128]);
+
+        // When
+        let canvas = parse(&data).unwrap();
+
+        // Then
+        assert_eq!(canvas[(0, 0)].r, 255.0 / 256.0);
+        assert_eq!(canvas[(2, 0)].g, 3.0 / 256.0);
+        assert_eq!(canvas[(7, 0)].g, 0.0);
+        assert!(parse(&data[..data.len() - 1]).is_err());
+        assert!(parse(b"P6\n").is_err());
+    }
+}
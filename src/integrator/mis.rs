@@ -0,0 +1,80 @@
+/// How the path tracer finds the light arriving straight from lights with an area, which can be
+/// reached both by sampling points on the lights and by sampling the BSDF of the surface being
+/// lit. Light sampling does best for small lights and rough surfaces, BSDF sampling for large
+/// lights and shiny surfaces. Multiple importance sampling does both and weighs each sample by
+/// how likely the other strategy was to find it, which keeps the best of both.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Strategy {
+    /// Only sample the lights.
+    Light,
+    /// Only sample the BSDF.
+    Bsdf,
+    /// Both, weighed by Veach's balance heuristic.
+    Balance,
+    /// Both, weighed by Veach's power heuristic with an exponent of 2, which favors the strategy
+    /// that is most likely to find a sample more strongly than the balance heuristic.
+    #[default]
+    Power,
+}
+
+impl Strategy {
+    /// The weight of a sample found by sampling a light with the density `light_pdf`, which the
+    /// BSDF would have picked with the density `bsdf_pdf`.
+    pub fn light_weight(&self, light_pdf: f64, bsdf_pdf: f64) -> f64 {
+        match self {
+            Strategy::Light   => 1.0,
+            Strategy::Bsdf    => 0.0,
+            Strategy::Balance => balance(light_pdf, bsdf_pdf),
+            Strategy::Power   => power(light_pdf, bsdf_pdf),
+        }
+    }
+
+    /// The weight of a sample found by sampling the BSDF with the density `bsdf_pdf`, which the
+    /// light would have picked with the density `light_pdf`.
+    pub fn bsdf_weight(&self, bsdf_pdf: f64, light_pdf: f64) -> f64 {
+        match self {
+            Strategy::Light   => 0.0,
+            Strategy::Bsdf    => 1.0,
+            Strategy::Balance => balance(bsdf_pdf, light_pdf),
+            Strategy::Power   => power(bsdf_pdf, light_pdf),
+        }
+    }
+}
+
+/// The balance heuristic for one sample taken with the density `pdf`, and one with another
+/// strategy that would have picked it with the density `other`.
+pub fn balance(pdf: f64, other: f64) -> f64 {
+    match pdf + other {
+        0.0 => 0.0,
+        sum => pdf / sum,
+    }
+}
+
+/// The power heuristic with an exponent of 2, for one sample taken with the density `pdf`, and
+/// one with another strategy that would have picked it with the density `other`.
+pub fn power(pdf: f64, other: f64) -> f64 {
+    match pdf * pdf + other * other {
+        0.0 => 0.0,
+        sum => pdf * pdf / sum,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::float::ApproxEq;
+
+    use super::*;
+
+    #[test]
+    fn the_weights_of_both_strategies_add_up_to_one() {
+        for (a, b) in [(1.0, 1.0), (0.2, 5.0), (3.0, 0.0), (100.0, 0.01)] {
+            for strategy in [Strategy::Light, Strategy::Bsdf, Strategy::Balance, Strategy::Power] {
+                // Then
+                assert!((strategy.light_weight(a, b) + strategy.bsdf_weight(b, a)).approx_eq(1.0));
+            }
+        }
+        assert!(balance(1.0, 3.0).approx_eq(0.25));
+        assert!(power(1.0, 3.0).approx_eq(0.1));
+        assert_eq!(power(0.0, 0.0), 0.0);
+    }
+}
@@ -0,0 +1,30 @@
+pub mod mis;
+pub mod path;
+pub mod photon;
+
+use crate::color::Color;
+use crate::integrator::path::PathTracer;
+use crate::ray::Ray;
+use crate::world::World;
+
+/// How the light arriving at the camera along a ray is worked out.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Integrator {
+    /// Phong shading of the first surface a ray hits, with shadows (see `World::color_at`). Only
+    /// light coming straight from the lights is followed, with an ambient term standing in for
+    /// the rest.
+    #[default]
+    Whitted,
+    /// Monte Carlo path tracing, which follows light as it bounces around the world.
+    Path(PathTracer),
+}
+
+impl Integrator {
+    /// The light arriving along `ray`.
+    pub fn radiance(&self, world: &World, ray: &Ray) -> Color {
+        match self {
+            Integrator::Whitted      => world.color_at(ray),
+            Integrator::Path(tracer) => tracer.radiance(world, ray),
+        }
+    }
+}
@@ -0,0 +1,507 @@
+use crate::bsdf::Bsdf;
+use crate::color::Color;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::integrator::mis::Strategy;
+use crate::intersection::{hit, Computations};
+use crate::light::LightSample;
+use crate::medium::{Interaction, Medium};
+use crate::random::random;
+use crate::ray::Ray;
+use crate::sampling::Frame;
+use crate::spectrum::Wavelengths;
+use crate::world::World;
+
+/// A unidirectional path tracer.
+///
+/// At every surface a path hits, the light the surface emits is added, the lights of the world are
+/// sampled directly (next-event estimation), and the path carries on in a direction sampled from
+/// the BSDF of the surface to pick up light bouncing off other surfaces. Light from lights with an
+/// area is also picked up when a path runs into them, which `strategy` combines with sampling
+/// them. The intensity of a light is taken as the irradiance it gives a surface facing it, so
+/// images come out darker than with Phong shading, which adds `diffuse` and `specular` terms on top
+/// of an ambient one.
+///
+/// When the world has `caustics`, the light they bring to surfaces is taken from its photons.
+///
+/// In `spectral` mode every path carries light of a few wavelengths instead of red, green and
+/// blue, which the colors of the world are turned into spectra for (see `spectrum::Wavelengths`).
+/// The light is converted back to a color through CIE XYZ at the end. Only then do materials whose
+/// index of refraction depends on the wavelength split light into its colors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PathTracer {
+    /// The most bounces a path makes before it's cut off.
+    pub max_depth: usize,
+    /// The bounces after which a path is ended at random, more likely the less light it still
+    /// carries. Surviving paths are weighted up so that the image stays unbiased.
+    pub roulette_depth: usize,
+    /// How light straight from lights with an area is found.
+    pub strategy: Strategy,
+    pub spectral: bool,
+}
+
+impl PathTracer {
+    pub fn new(max_depth: usize) -> Self {
+        Self { max_depth, ..Self::default() }
+    }
+
+    /// An estimate of the light arriving along `ray`.
+    pub fn radiance(&self, world: &World, ray: &Ray) -> Color {
+        let mut radiance = Color::default();
+        let mut throughput = Color::new(1.0, 1.0, 1.0);
+        let mut ray = *ray;
+        // Where the ray was sampled from a BSDF or a phase function and with what density, unless
+        // it comes from the camera or a specular surface, which light sampling can't account for.
+        let mut scattered: Option<(Point, f64)> = None;
+        // Whether the last surface that isn't specular gathered the caustics of the photons, and
+        // whether the path has gone through specular surfaces since: the photons already carry
+        // the light it could run into then.
+        let (mut gathered, mut specular_since) = (false, false);
+        let mut wavelengths = self.spectral.then(|| Wavelengths::sample(random()));
+
+        for depth in 0..=self.max_depth {
+            let xs = world.intersect(&ray);
+            let hit = hit(&xs);
+            let (interaction, weight) = world.track(&ray, hit.map_or(f64::INFINITY, |h| h.t));
+            throughput = throughput * carried(weight, wavelengths.as_ref());
+            let end = match interaction {
+                Interaction::Passed                                     => hit.map(|h| h.t),
+                Interaction::Absorbed(t) | Interaction::Scattered(t, _) => Some(t),
+            };
+            let lights = self.lights_hit(world, &ray, end, scattered, gathered && specular_since);
+            radiance = radiance + throughput * carried(lights, wavelengths.as_ref());
+
+            let comps;
+            let vertex = match interaction {
+                Interaction::Absorbed(_)          => break,
+                Interaction::Scattered(t, medium) => Vertex::Medium {
+                    point: ray.position(t),
+                    direction: ray.direction.normalize(),
+                    medium,
+                    time: ray.time,
+                },
+                Interaction::Passed => {
+                    let Some(hit) = hit else { break };
+                    comps = hit.prepare_computations(&ray);
+                    let material = comps.object.material();
+                    radiance = radiance + throughput * carried(material.emission, wavelengths.as_ref());
+                    let bsdf = match &mut wavelengths {
+                        Some(wavelengths) => {
+                            if material.scattering().is_dispersive() {
+                                wavelengths.terminate_secondary();
+                            }
+                            material.scattering().at(wavelengths)
+                        }
+                        None => material.scattering(),
+                    };
+                    let frame = Frame::new(match comps.inside {
+                        true  => -comps.normalv,
+                        false => comps.normalv,
+                    });
+                    Vertex::Surface {
+                        comps: &comps,
+                        bsdf,
+                        wo: frame.to_local(comps.eyev),
+                        frame,
+                    }
+                }
+            };
+
+            if let (Some(caustics), Vertex::Surface { comps, bsdf, frame, wo }) = (&world.caustics, &vertex) {
+                if !bsdf.is_specular() {
+                    // The photons carry colors, so they're gathered with the BSDF for colors too.
+                    let bsdf = comps.object.material().scattering();
+                    let light = caustics.radiance(comps.point, |direction| bsdf.evaluate(*wo, frame.to_local(direction)));
+                    radiance = radiance + throughput * carried(light, wavelengths.as_ref());
+                }
+            }
+
+            // The BSDF isn't sampled after the last bounce, which leaves it all to the lights.
+            let strategy = match depth == self.max_depth {
+                true  => Strategy::Light,
+                false => self.strategy,
+            };
+            radiance = radiance + throughput * direct_light(world, &vertex, strategy, wavelengths.as_ref());
+            if depth == self.max_depth {
+                break;
+            }
+
+            let Some((direction, weight, pdf, specular)) = vertex.sample() else { break };
+            throughput = throughput * weight;
+            if depth >= self.roulette_depth {
+                let survival = throughput.r.max(throughput.g).max(throughput.b).min(1.0);
+                if random::<f64>() >= survival {
+                    break;
+                }
+                throughput = throughput * (1.0 / survival);
+            }
+
+            ray = Ray::new(vertex.origin(direction), direction).with_time(ray.time);
+            scattered = (!specular).then_some((vertex.point(), pdf));
+            (gathered, specular_since) = match &vertex {
+                Vertex::Surface { bsdf, .. } if bsdf.is_specular() => (gathered, true),
+                Vertex::Surface { .. }                            => (world.caustics.is_some(), false),
+                Vertex::Medium { .. }                             => (false, false),
+            };
+        }
+
+        match wavelengths {
+            Some(wavelengths) => wavelengths.to_rgb(radiance),
+            None              => radiance,
+        }
+    }
+
+    /// The light seen along `ray` from lights with an area it hits before `end`, if the ray ends,
+    /// weighed against sampling the lights if the ray was `scattered` from a point with a
+    /// density. Lights whose photons were already `gathered` for the path are left out.
+    fn lights_hit(&self, world: &World, ray: &Ray, end: Option<f64>, scattered: Option<(Point, f64)>, gathered: bool) -> Color {
+        world.lights.iter()
+            .enumerate()
+            .filter(|&(index, light)| light.has_area() && !(gathered && world.caustics.as_ref().is_some_and(|c| c.covers(index))))
+            .filter_map(|(_, light)| {
+                let (_, radiance) = light.hit(ray).filter(|&(distance, _)| end.is_none_or(|end| distance < end))?;
+                let weight = match scattered {
+                    Some((point, pdf)) => self.strategy.bsdf_weight(pdf, light.pdf(point, ray.direction)),
+                    None               => 1.0,
+                };
+                Some(radiance * weight)
+            })
+            .sum::<Color>()
+    }
+}
+
+impl Default for PathTracer {
+    fn default() -> Self {
+        Self { max_depth: 16, roulette_depth: 3, strategy: Strategy::default(), spectral: false, }
+    }
+}
+
+/// Where a path scatters light: off a surface with a BSDF, or inside a medium with its phase
+/// function.
+enum Vertex<'a> {
+    /// `frame` is the shading frame of the surface and `wo` the direction towards the eye in it.
+    Surface { comps: &'a Computations<'a>, bsdf: Bsdf, frame: Frame, wo: Vector },
+    /// `direction` is the unit vector along which the path arrived, away from the eye.
+    Medium { point: Point, direction: Vector, medium: &'a Medium, time: f64 },
+}
+
+impl Vertex<'_> {
+    fn point(&self) -> Point {
+        match self {
+            Vertex::Surface { comps, .. } => comps.point,
+            Vertex::Medium { point, .. }  => *point,
+        }
+    }
+
+    fn time(&self) -> f64 {
+        match self {
+            Vertex::Surface { comps, .. } => comps.time,
+            Vertex::Medium { time, .. }   => *time,
+        }
+    }
+
+    fn is_specular(&self) -> bool {
+        match self {
+            Vertex::Surface { bsdf, .. } => bsdf.is_specular(),
+            Vertex::Medium { .. }        => false,
+        }
+    }
+
+    /// The fraction of the light arriving from `direction` that is scattered towards the eye,
+    /// including the cosine with the normal of a surface.
+    fn scattering(&self, direction: Vector) -> Color {
+        match self {
+            Vertex::Surface { bsdf, frame, wo, .. } => {
+                let wi = frame.to_local(direction);
+                bsdf.evaluate(*wo, wi) * wi.z().abs()
+            }
+            Vertex::Medium { direction: arrival, medium, .. } => {
+                let phase = medium.phase.evaluate(direction.dot(*arrival));
+                Color::new(phase, phase, phase)
+            }
+        }
+    }
+
+    /// The density with which `sample` picks `direction`.
+    fn pdf(&self, direction: Vector) -> f64 {
+        match self {
+            Vertex::Surface { bsdf, frame, wo, .. }          => bsdf.pdf(*wo, frame.to_local(direction)),
+            Vertex::Medium { direction: arrival, medium, .. } => medium.phase.evaluate(direction.dot(*arrival)),
+        }
+    }
+
+    /// A direction to continue the path in, with the weight of the path along it, its density
+    /// and whether it was picked from a specular BSDF.
+    fn sample(&self) -> Option<(Vector, Color, f64, bool)> {
+        match self {
+            Vertex::Surface { bsdf, frame, wo, .. } => {
+                let sample = bsdf.sample(*wo, random(), random(), random())?;
+                Some((frame.to_world(sample.direction), sample.weight(), sample.pdf, sample.specular))
+            }
+            Vertex::Medium { direction: arrival, medium, .. } => {
+                // Sampling the phase function exactly leaves the weight at 1.
+                let direction = medium.phase.sample(*arrival, random(), random());
+                Some((direction, Color::new(1.0, 1.0, 1.0), medium.phase.evaluate(direction.dot(*arrival)), false))
+            }
+        }
+    }
+
+    /// Where a ray leaving the vertex in `direction` starts, on the side of a surface it goes to.
+    fn origin(&self, direction: Vector) -> Point {
+        match self {
+            Vertex::Surface { comps, .. } => match direction.dot(comps.geometric_normalv) > 0.0 {
+                true  => comps.over_point,
+                false => comps.under_point,
+            },
+            Vertex::Medium { point, .. } => *point,
+        }
+    }
+}
+
+/// The light scattered towards the eye at `vertex` straight from the lights of the world. Lights
+/// that are a point or a direction are averaged over their samples, those with an area are
+/// sampled once and weighed by `strategy`.
+fn direct_light(world: &World, vertex: &Vertex, strategy: Strategy, wavelengths: Option<&Wavelengths>) -> Color {
+    if vertex.is_specular() {
+        return Color::default();
+    }
+    // The light scattered from a sample with the given weight, if any.
+    let scattered = |sample: &LightSample, weight: f64| {
+        let f = vertex.scattering(sample.direction);
+        if f.r + f.g + f.b <= 0.0 || weight <= 0.0 {
+            return None;
+        }
+        let transmittance = world.transmittance(vertex.origin(sample.direction), sample.direction, sample.distance, vertex.time());
+        Some(f * carried(sample.intensity * transmittance, wavelengths) * weight)
+    };
+
+    world.lights.iter()
+        .map(|light| match light.has_area() {
+            true => {
+                let Some((sample, pdf)) = light.sample_radiance(vertex.point(), random(), random()) else { return Color::default() };
+                let weight = strategy.light_weight(pdf, vertex.pdf(sample.direction));
+                scattered(&sample, weight / pdf).unwrap_or_default()
+            }
+            false => {
+                let samples = light.samples(vertex.point());
+                let total = samples.iter()
+                    .filter_map(|sample| scattered(sample, 1.0))
+                    .sum::<Color>();
+                total * (1.0 / samples.len().max(1) as f64)
+            }
+        })
+        .sum::<Color>()
+}
+
+/// The light a path carries for a color of the world, which is the spectrum of the color at the
+/// `wavelengths` of the path in spectral mode.
+fn carried(color: Color, wavelengths: Option<&Wavelengths>) -> Color {
+    wavelengths.map_or(color, |wavelengths| wavelengths.spectrum(color))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_PI;
+
+    use crate::core::matrix::Matrix4;
+    use crate::light::point::PointLight;
+    use crate::bsdf::conductor::Conductor;
+    use crate::bsdf::glossy::Glossy;
+    use crate::bump::{Bump, ImageMap};
+    use crate::canvas::Canvas;
+    use crate::integrator::photon::PhotonMap;
+    use crate::intersection::Intersection;
+    use crate::light::area::AreaLight;
+    use crate::light::environment::{EnvironmentLight, Sky};
+    use crate::material::Material;
+    use crate::medium::Volume;
+    use crate::random;
+    use crate::shape::plane::Plane;
+    use crate::shape::sphere::Sphere;
+    use crate::shape::Shape;
+
+    use super::*;
+
+    #[test]
+    fn a_point_light_lights_a_diffuse_plane() {
+        // Given
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.set_material(Material { color: Color::new(0.5, 0.5, 0.5), diffuse: 1.0, ..Material::default() });
+        w.add_object(Box::new(floor));
+        w.lights.push(Box::new(PointLight::new(Point::new(0.0, 1.0, 0.0), Color::new(1.0, 1.0, 1.0))));
+        let r = Ray::new(Point::new(0.0, 1.0, -1.0), Vector::new(0.0, -1.0, 1.0).normalize());
+
+        // When
+        let c = PathTracer::new(0).radiance(&w, &r);
+
+        // Then
+        assert_eq!(c, Color::new(0.5, 0.5, 0.5) * FRAC_1_PI);
+    }
+
+    #[test]
+    fn rays_leave_from_the_side_of_the_surface_and_not_of_its_bumps() {
+        // Given: a floor with a normal map that leans its normal most of the way over to +x.
+        let mut floor = Plane::new();
+        let mut leaning = Canvas::new(1, 1);
+        leaning[(0, 0)] = Color::new(1.0, 0.5, 0.6);
+        floor.set_material(Material { bump: Some(Bump::Normal(ImageMap::new(leaning))), ..Material::default() });
+        let r = Ray::new(Point::new(0.0, 1.0, -1.0), Vector::new(0.0, -1.0, 1.0).normalize());
+        let comps = Intersection::new(2f64.sqrt(), &floor).prepare_computations(&r);
+        let frame = Frame::new(comps.normalv);
+        let vertex = Vertex::Surface { comps: &comps, bsdf: floor.material().scattering(), wo: frame.to_local(comps.eyev), frame };
+
+        // When: leaving above the bumps, but into the floor.
+        let direction = Vector::new(1.0, -0.1, 0.0).normalize();
+        let origin = vertex.origin(direction);
+
+        // Then
+        assert!(direction.dot(comps.normalv) > 0.5);
+        assert_eq!(origin, comps.under_point);
+        assert!(origin.y() < 0.0);
+    }
+
+    #[test]
+    fn spectral_mode_gives_about_the_same_colors() {
+        // Given
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.set_material(Material { color: Color::new(0.5, 0.5, 0.5), diffuse: 1.0, ..Material::default() });
+        w.add_object(Box::new(floor));
+        w.lights.push(Box::new(PointLight::new(Point::new(0.0, 1.0, 0.0), Color::new(1.0, 0.6, 0.3))));
+        let r = Ray::new(Point::new(0.0, 1.0, -1.0), Vector::new(0.0, -1.0, 1.0).normalize());
+        let tracer = PathTracer { spectral: true, ..PathTracer::new(0) };
+        random::seed(23);
+
+        // When
+        let n = 4000;
+        let mean = (0..n).map(|_| tracer.radiance(&w, &r)).sum::<Color>() * (1.0 / n as f64);
+
+        // Then
+        let expected = PathTracer::new(0).radiance(&w, &r);
+        for (a, b) in [(mean.r, expected.r), (mean.g, expected.g), (mean.b, expected.b)] {
+            assert!((a / b - 1.0).abs() < 0.1, "{:?} against {:?}", mean, expected);
+        }
+    }
+
+    #[test]
+    fn a_white_furnace_gives_back_the_light_it_emits() {
+        // Given
+        let mut w = World::new();
+        let mut furnace = Sphere::new();
+        furnace.set_transform(Matrix4::scaling(10.0, 10.0, 10.0));
+        furnace.set_material(Material {
+            color: Color::new(0.5, 0.5, 0.5),
+            diffuse: 1.0,
+            emission: Color::new(0.5, 0.5, 0.5),
+            ..Material::default()
+        });
+        w.add_object(Box::new(furnace));
+        let r = Ray::new(Point::zero(), Vector::new(0.0, 0.0, 1.0));
+        let tracer = PathTracer { max_depth: 64, roulette_depth: 0, ..PathTracer::default() };
+        random::seed(7);
+
+        // When
+        let n = 4000;
+        let mean = (0..n).map(|_| tracer.radiance(&w, &r)).sum::<Color>() * (1.0 / n as f64);
+
+        // Then: 0.5 + 0.5 × 0.5 + 0.5 × 0.5² + … = 1, whether the paths are ended by Russian
+        // roulette or not.
+        assert!((mean.r - 1.0).abs() < 0.05, "{:?}", mean);
+    }
+
+    #[test]
+    fn caustics_from_photons_stand_in_for_paths_through_a_mirror() {
+        // Given: a light between a matte floor and a mirror facing it.
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.set_material(Material { color: Color::new(0.5, 0.5, 0.5), diffuse: 1.0, ..Material::default() });
+        let mut mirror = Plane::new();
+        mirror.set_transform(Matrix4::translation(0.0, 2.0, 0.0));
+        mirror.set_material(Material { bsdf: Some(Bsdf::Conductor(Conductor::aluminium(0.0))), ..Material::default() });
+        w.add_object(Box::new(floor));
+        w.add_object(Box::new(mirror));
+        w.lights.push(Box::new(AreaLight::new(Point::new(-0.5, 1.0, -0.5), Vector::new(1.0, 0.0, 0.0), 1, Vector::new(0.0, 0.0, 1.0), 1, Color::new(1.0, 1.0, 1.0))));
+        let r = Ray::new(Point::new(0.0, 1.0, -1.0), Vector::new(0.0, -1.0, 1.0).normalize());
+        let tracer = PathTracer::new(8);
+        random::seed(17);
+        let n = 10000;
+        let mean = |w: &World| (0..n).map(|_| tracer.radiance(w, &r).g).sum::<f64>() / n as f64;
+
+        // When
+        let traced = mean(&w);
+        w.caustics = Some(PhotonMap::trace(&w, 100_000, 0.5));
+        let gathered = mean(&w);
+
+        // Then
+        let caustic = w.caustics.as_ref().unwrap().radiance(Point::zero(), |_| Color::new(0.5, 0.5, 0.5) * FRAC_1_PI);
+        assert!(caustic.g > 0.1 * traced);
+        assert!((gathered / traced - 1.0).abs() < 0.02, "{} against {}", gathered, traced);
+    }
+
+    #[test]
+    fn a_volume_that_only_scatters_keeps_the_light_around_it() {
+        // Given
+        let mut w = World::new();
+        let mut boundary = Sphere::new();
+        boundary.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        w.add_volume(Volume::new(Box::new(boundary), Medium::new(Color::default(), Color::new(1.5, 1.5, 1.5), 0.6)));
+        w.lights.push(Box::new(EnvironmentLight::new(Sky::Constant { color: Color::new(0.5, 0.5, 0.5) })));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let tracer = PathTracer { max_depth: 64, roulette_depth: 0, ..PathTracer::default() };
+        random::seed(13);
+
+        // When
+        let n = 4000;
+        let mean = (0..n).map(|_| tracer.radiance(&w, &r)).sum::<Color>() * (1.0 / n as f64);
+
+        // Then
+        assert!((mean.g - 0.5).abs() < 0.02, "{:?}", mean);
+    }
+
+    /// The mean and variance of the estimates of the light reflected by a glossy floor towards a
+    /// ray, lit only by `light`.
+    fn estimate(light: AreaLight, roughness: f64, strategy: Strategy) -> (f64, f64) {
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.set_material(Material {
+            bsdf: Some(Bsdf::Glossy(Glossy::new(Color::new(1.0, 1.0, 1.0), roughness, 1.0))),
+            ..Material::default()
+        });
+        w.add_object(Box::new(floor));
+        w.lights.push(Box::new(light));
+        let r = Ray::new(Point::new(0.0, 1.0, -1.0), Vector::new(0.0, -1.0, 1.0).normalize());
+        let tracer = PathTracer { max_depth: 1, strategy, ..PathTracer::default() };
+        random::seed(3);
+
+        let n = 4000;
+        let estimates = (0..n).map(|_| tracer.radiance(&w, &r).g).collect::<Vec<_>>();
+        let mean = estimates.iter().sum::<f64>() / n as f64;
+        let variance = estimates.iter().map(|e| (e - mean).powi(2)).sum::<f64>() / (n - 1) as f64;
+
+        (mean, variance)
+    }
+
+    #[test]
+    fn multiple_importance_sampling_keeps_the_best_of_both_strategies() {
+        // Given: a small light over a rough floor, and a large one over a shiny floor.
+        let small = AreaLight::new(Point::new(-0.1, 2.0, 1.9), Vector::new(0.2, 0.0, 0.0), 1, Vector::new(0.0, 0.0, 0.2), 1, Color::new(1.0, 1.0, 1.0));
+        let large = AreaLight::new(Point::new(-5.0, 2.0, -3.0), Vector::new(10.0, 0.0, 0.0), 1, Vector::new(0.0, 0.0, 10.0), 1, Color::new(100.0, 100.0, 100.0));
+
+        for (light, roughness, best) in [(small, 0.6, Strategy::Light), (large, 0.05, Strategy::Bsdf)] {
+            // When
+            let (expected, least) = estimate(light, roughness, best);
+            let most = [Strategy::Light, Strategy::Bsdf]
+                .map(|strategy| estimate(light, roughness, strategy).1)
+                .into_iter()
+                .fold(0.0, f64::max);
+            let (balance_mean, balance) = estimate(light, roughness, Strategy::Balance);
+            let (power_mean, power) = estimate(light, roughness, Strategy::Power);
+
+            // Then
+            assert!((balance_mean / expected - 1.0).abs() < 0.01 && (power_mean / expected - 1.0).abs() < 0.01);
+            assert!(power <= balance && balance < most / 1000.0, "{} and {} against {} to {}", power, balance, least, most);
+        }
+    }
+}
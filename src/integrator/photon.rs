@@ -0,0 +1,268 @@
+use std::f64::consts::PI;
+
+use crate::bounds::BoundingBox;
+use crate::bvh::axis_of;
+use crate::color::Color;
+use crate::core::point::Point;
+use crate::core::vector::Vector;
+use crate::intersection::hit;
+use crate::medium;
+use crate::random::random;
+use crate::ray::Ray;
+use crate::sampling::{Distribution1D, Frame};
+use crate::world::World;
+
+/// The most specular surfaces a photon bounces off before it's given up on.
+const MAX_BOUNCES: usize = 16;
+
+/// Light that bounced off mirrors or went through glass before landing on a surface that isn't
+/// specular.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Photon {
+    pub position: Point,
+    /// Unit vector back the way the photon came.
+    pub direction: Vector,
+    /// The share of the power of its light the photon carries.
+    pub power: Color,
+}
+
+/// The caustics of a world, as described by Jensen in "Global Illumination using Photon Maps":
+/// photons are sent out from the lights, followed through specular surfaces, and kept where they
+/// land after at least one of them. The light a surface reflects from the caustics is then worked
+/// out from the photons within `radius` of a point, weighted more the closer they are.
+///
+/// Path tracing only finds such light when a path bounces off the same specular surfaces and
+/// happens to hit a light, which it never does for lights that are a point, so the photons do it
+/// instead: paths that run into a light the photons came from after a surface that gathered them
+/// through nothing but specular surfaces leave its light out.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PhotonMap {
+    /// The photons as a balanced kd-tree: the photon in the middle of any range of the tree
+    /// splits the ones before it from the ones after it along its axis.
+    photons: Vec<Photon>,
+    axes: Vec<u8>,
+    radius: f64,
+    /// The indices of the lights that sent out photons.
+    lights: Vec<usize>,
+}
+
+impl PhotonMap {
+    /// Sends `count` photons out from the lights of `world`, shared out between the lights in
+    /// proportion to their power, and keeps those that make caustics.
+    pub fn trace(world: &World, count: usize, radius: f64) -> Self {
+        let bounds = world.extent();
+        let powers = world.lights.iter().map(|light| light.power(&bounds).luminance()).collect::<Vec<_>>();
+        let lights = (0..powers.len()).filter(|&i| powers[i] > 0.0).collect::<Vec<_>>();
+        if lights.is_empty() || count == 0 {
+            return Self { radius, lights, ..Self::default() };
+        }
+
+        let choice = Distribution1D::new(powers);
+        let mut photons = vec![];
+        for _ in 0..count {
+            let (_, pdf, index) = choice.sample(random());
+            let probability = pdf / world.lights.len() as f64;
+            let Some((ray, power)) = world.lights[index].emit(&bounds, [random(), random(), random(), random()]) else { continue };
+            photons.extend(follow(world, ray, power * (1.0 / (count as f64 * probability))));
+        }
+
+        let mut axes = vec![0; photons.len()];
+        build(&mut photons, &mut axes);
+
+        Self { photons, axes, radius, lights }
+    }
+
+    pub fn len(&self) -> usize {
+        self.photons.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.photons.is_empty()
+    }
+
+    pub fn radius(&self) -> f64 {
+        self.radius
+    }
+
+    /// Whether the photons carry the light of the light at `index` among the lights of the world.
+    pub fn covers(&self, index: usize) -> bool {
+        self.lights.contains(&index)
+    }
+
+    /// The photons within the radius of `point`.
+    pub fn photons_near(&self, point: Point) -> Vec<&Photon> {
+        let mut found = vec![];
+        gather(&self.photons, &self.axes, point, self.radius * self.radius, &mut found);
+
+        found
+    }
+
+    /// The light of the caustics around `point` scattered towards the eye, where `f` is the
+    /// fraction of the light arriving from a direction that is. Photons are weighted by a cone
+    /// falling to zero at the radius, which keeps the edges of caustics sharper.
+    pub fn radiance(&self, point: Point, f: impl Fn(Vector) -> Color) -> Color {
+        if self.radius <= 0.0 {
+            return Color::default();
+        }
+        let total = self.photons_near(point).into_iter()
+            .map(|photon| f(photon.direction) * photon.power * (1.0 - (photon.position - point).magnitude() / self.radius))
+            .sum::<Color>();
+
+        // The cone integrates to a third of the area of the disk.
+        total * (3.0 / (PI * self.radius * self.radius))
+    }
+}
+
+/// Follows the photon leaving along `ray` with `power` through specular surfaces to where it
+/// makes a caustic, if it does.
+fn follow(world: &World, mut ray: Ray, mut power: Color) -> Option<Photon> {
+    for bounce in 0..MAX_BOUNCES {
+        let xs = world.intersect(&ray);
+        let hit = hit(&xs)?;
+        power = power * medium::transmittance(&world.media_along(&ray, hit.t), &ray, hit.t);
+        let comps = hit.prepare_computations(&ray);
+        let bsdf = comps.object.material().scattering();
+        if !bsdf.is_specular() {
+            return (bounce > 0).then_some(Photon { position: comps.point, direction: comps.eyev, power });
+        }
+
+        let normal = match comps.inside {
+            true  => -comps.normalv,
+            false => comps.normalv,
+        };
+        let frame = Frame::new(normal);
+        let sample = bsdf.sample(frame.to_local(comps.eyev), random(), random(), random())?;
+        power = power * sample.weight();
+        let direction = frame.to_world(sample.direction);
+        let origin = match direction.dot(comps.geometric_normalv) > 0.0 {
+            true  => comps.over_point,
+            false => comps.under_point,
+        };
+        ray = Ray::new(origin, direction).with_time(ray.time);
+    }
+
+    None
+}
+
+/// Arranges `photons` into a kd-tree, split along the axis they spread out the most along.
+fn build(photons: &mut [Photon], axes: &mut [u8]) {
+    if photons.is_empty() {
+        return;
+    }
+    let mut bounds = BoundingBox::empty();
+    for photon in photons.iter() {
+        bounds.add_point(photon.position);
+    }
+    let axis = bounds.longest_axis();
+    let middle = photons.len() / 2;
+    photons.select_nth_unstable_by(middle, |a, b| axis_of(a.position, axis).total_cmp(&axis_of(b.position, axis)));
+    axes[middle] = axis as u8;
+
+    let (before, after) = photons.split_at_mut(middle);
+    let (axes_before, axes_after) = axes.split_at_mut(middle);
+    build(before, axes_before);
+    build(&mut after[1..], &mut axes_after[1..]);
+}
+
+/// Adds the photons of the tree within the square root of `distance2` of `point` to `found`,
+/// only going down the side of a split beyond it if that side is close enough.
+fn gather<'a>(photons: &'a [Photon], axes: &[u8], point: Point, distance2: f64, found: &mut Vec<&'a Photon>) {
+    if photons.is_empty() {
+        return;
+    }
+    let middle = photons.len() / 2;
+    let photon = &photons[middle];
+    let offset = point - photon.position;
+    if offset.dot(offset) <= distance2 {
+        found.push(photon);
+    }
+
+    let axis = axes[middle] as usize;
+    let d = axis_of(point, axis) - axis_of(photon.position, axis);
+    let (before, after) = (0..middle, middle + 1..photons.len());
+    let (near, far) = match d < 0.0 {
+        true  => (before, after),
+        false => (after, before),
+    };
+    gather(&photons[near.clone()], &axes[near], point, distance2, found);
+    if d * d <= distance2 {
+        gather(&photons[far.clone()], &axes[far], point, distance2, found);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_PI;
+
+    use crate::bsdf::conductor::Conductor;
+    use crate::bsdf::fresnel;
+    use crate::bsdf::Bsdf;
+    use crate::core::matrix::Matrix4;
+    use crate::core::tuple::Tuple;
+    use crate::light::point::PointLight;
+    use crate::material::Material;
+    use crate::random;
+    use crate::shape::plane::Plane;
+    use crate::shape::Shape;
+
+    use super::*;
+
+    #[test]
+    fn the_tree_finds_the_same_photons_as_a_search_through_all_of_them() {
+        // Given
+        random::seed(11);
+        let mut photons = (0..2000)
+            .map(|_| Photon {
+                position: Point::new(random::<f64>() * 4.0, random::<f64>(), random::<f64>() * 2.0),
+                direction: Vector::new(0.0, 1.0, 0.0),
+                power: Color::new(1.0, 1.0, 1.0),
+            })
+            .collect::<Vec<_>>();
+        let everything = photons.clone();
+        let mut axes = vec![0; photons.len()];
+        build(&mut photons, &mut axes);
+        let map = PhotonMap { photons, axes, radius: 0.3, lights: vec![0] };
+
+        for point in [Point::new(1.0, 0.5, 1.0), Point::new(3.9, 0.0, 0.1), Point::new(-1.0, 0.5, 1.0)] {
+            // When
+            let mut near = map.photons_near(point).into_iter().map(|p| p.position).collect::<Vec<_>>();
+            let mut expected = everything.iter()
+                .map(|p| p.position)
+                .filter(|&p| (p - point).magnitude() <= 0.3)
+                .collect::<Vec<_>>();
+
+            // Then
+            let order = |a: &Point, b: &Point| a.x().total_cmp(&b.x());
+            near.sort_by(order);
+            expected.sort_by(order);
+            assert_eq!(near, expected);
+        }
+    }
+
+    #[test]
+    fn a_mirror_makes_a_caustic_of_a_point_light() {
+        // Given: a light halfway between a matte floor and a mirror facing it.
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.set_material(Material { color: Color::new(0.5, 0.5, 0.5), diffuse: 1.0, ..Material::default() });
+        let mut mirror = Plane::new();
+        mirror.set_transform(Matrix4::translation(0.0, 2.0, 0.0));
+        let aluminium = Conductor::aluminium(0.0);
+        mirror.set_material(Material { bsdf: Some(Bsdf::Conductor(aluminium)), ..Material::default() });
+        w.add_object(Box::new(floor));
+        w.add_object(Box::new(mirror));
+        w.lights.push(Box::new(PointLight::new(Point::new(0.0, 1.0, 0.0), Color::new(1.0, 1.0, 1.0))));
+        random::seed(5);
+
+        // When
+        let map = PhotonMap::trace(&w, 200_000, 0.6);
+        let radiance = map.radiance(Point::zero(), |_| Color::new(0.5, 0.5, 0.5) * FRAC_1_PI);
+
+        // Then: the floor sees the reflection of the light 3 units away.
+        let reflectance = fresnel::conductor(1.0, aluminium.eta, aluminium.k);
+        let expected = reflectance * Color::new(0.5, 0.5, 0.5) * (FRAC_1_PI / 9.0);
+        assert!((radiance.g / expected.g - 1.0).abs() < 0.08, "{:?} against {:?}", radiance, expected);
+        assert!(map.covers(0) && !map.covers(1));
+        assert!(map.photons_near(Point::new(0.0, 2.0, 0.0)).is_empty());
+    }
+}
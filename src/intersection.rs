@@ -0,0 +1,209 @@
+use crate::core::float::EPSILON_OFFSET;
+use crate::core::point::Point;
+use crate::core::vector::Vector;
+use crate::ray::Ray;
+use crate::shape::Shape;
+
+#[derive(Clone, Copy, Debug)]
+pub struct Intersection<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+}
+
+impl<'a> Intersection<'a> {
+    pub fn new(t: f64, object: &'a dyn Shape) -> Self {
+        Self { t, object, }
+    }
+
+    pub fn prepare_computations(&self, ray: &Ray) -> Computations<'a> {
+        let point = ray.position(self.t);
+        let eyev = -ray.direction;
+        let normal = self.object.normal_at_time(point, ray.time);
+        let shading = match &self.object.material().bump {
+            Some(bump) => bump.normal_at(self.object, point, ray.time, normal),
+            None       => normal,
+        };
+
+        let (inside, geometric_normalv, normalv) = match normal.dot(eyev) {
+            d if d < 0.0 => (true, -normal, -shading),
+            _            => (false, normal, shading),
+        };
+
+        // Points are nudged off the surface itself, which bumps don't move.
+        Computations {
+            t: self.t,
+            object: self.object,
+            point,
+            over_point: point + geometric_normalv * EPSILON_OFFSET,
+            under_point: point - geometric_normalv * EPSILON_OFFSET,
+            eyev,
+            geometric_normalv,
+            normalv,
+            inside,
+            time: ray.time,
+        }
+    }
+}
+
+impl PartialEq for Intersection<'_> {
+    fn eq(&self, other: &Self) -> bool {
+        self.t == other.t && self.object == other.object
+    }
+}
+
+/// Precomputed state of a ray hitting a surface, shared by all shading code.
+#[derive(Clone, Copy, Debug)]
+pub struct Computations<'a> {
+    pub t: f64,
+    pub object: &'a dyn Shape,
+    pub point: Point,
+    pub over_point: Point,
+    /// The point nudged just below the surface, where rays going through it start.
+    pub under_point: Point,
+    pub eyev: Vector,
+    /// The normal of the surface itself, which decides what side of it rays leaving the point
+    /// start from.
+    pub geometric_normalv: Vector,
+    /// The normal shading is done with, which is bent by the bumps of the material if it has any.
+    pub normalv: Vector,
+    pub inside: bool,
+    /// The time of the ray, at which everything lighting the point is looked at as well.
+    pub time: f64,
+}
+
+/// Returns the visible intersection, which is the one with the lowest non-negative `t`.
+pub fn hit<'a, 'b>(xs: &'b [Intersection<'a>]) -> Option<&'b Intersection<'a>> {
+    xs.iter()
+        .filter(|i| i.t >= 0.0)
+        .min_by(|a, b| a.t.total_cmp(&b.t))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::bump::{Bump, ImageMap};
+    use crate::canvas::Canvas;
+    use crate::color::Color;
+    use crate::core::matrix::Matrix4;
+    use crate::core::tuple::Tuple;
+    use crate::material::Material;
+    use crate::shape::plane::Plane;
+    use crate::shape::sphere::Sphere;
+
+    use super::*;
+
+    #[test]
+    fn hit_when_all_intersections_have_positive_t() {
+        // Given
+        let s = Sphere::new();
+        let xs = [Intersection::new(1.0, &s), Intersection::new(2.0, &s)];
+
+        // Then
+        assert_eq!(hit(&xs), Some(&xs[0]));
+    }
+
+    #[test]
+    fn hit_when_some_intersections_have_negative_t() {
+        // Given
+        let s = Sphere::new();
+        let xs = [Intersection::new(-1.0, &s), Intersection::new(1.0, &s)];
+
+        // Then
+        assert_eq!(hit(&xs), Some(&xs[1]));
+    }
+
+    #[test]
+    fn hit_when_all_intersections_have_negative_t() {
+        // Given
+        let s = Sphere::new();
+        let xs = [Intersection::new(-2.0, &s), Intersection::new(-1.0, &s)];
+
+        // Then
+        assert_eq!(hit(&xs), None);
+    }
+
+    #[test]
+    fn hit_is_always_the_lowest_nonnegative_intersection() {
+        // Given
+        let s = Sphere::new();
+        let xs = [
+            Intersection::new(5.0, &s),
+            Intersection::new(7.0, &s),
+            Intersection::new(-3.0, &s),
+            Intersection::new(2.0, &s),
+        ];
+
+        // Then
+        assert_eq!(hit(&xs), Some(&xs[3]));
+    }
+
+    #[test]
+    fn precomputing_the_state_of_an_intersection() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let s = Sphere::new();
+        let i = Intersection::new(4.0, &s);
+
+        // When
+        let comps = i.prepare_computations(&r);
+
+        // Then
+        assert_eq!(comps.t, i.t);
+        assert_eq!(comps.point, Point::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.eyev, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.normalv, Vector::new(0.0, 0.0, -1.0));
+        assert!(!comps.inside);
+    }
+
+    #[test]
+    fn hit_when_an_intersection_occurs_on_the_inside() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let s = Sphere::new();
+        let i = Intersection::new(1.0, &s);
+
+        // When
+        let comps = i.prepare_computations(&r);
+
+        // Then
+        assert_eq!(comps.point, Point::new(0.0, 0.0, 1.0));
+        assert_eq!(comps.eyev, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(comps.normalv, Vector::new(0.0, 0.0, -1.0));
+        assert!(comps.inside);
+    }
+
+    #[test]
+    fn hit_should_offset_the_point() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::translation(0.0, 0.0, 1.0));
+        let i = Intersection::new(5.0, &s);
+
+        // When
+        let comps = i.prepare_computations(&r);
+
+        // Then
+        assert!(comps.over_point.z() < -EPSILON_OFFSET / 2.0);
+        assert!(comps.point.z() > comps.over_point.z());
+    }
+
+    #[test]
+    fn bumps_bend_the_normal_but_not_where_points_are_nudged_to() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 1.0, -1.0), Vector::new(0.0, -1.0, 1.0).normalize());
+        let mut p = Plane::new();
+        let mut leaning = Canvas::new(1, 1);
+        leaning[(0, 0)] = Color::new(1.0, 0.5, 1.0);
+        p.set_material(Material { bump: Some(Bump::Normal(ImageMap::new(leaning))), ..Material::default() });
+        let i = Intersection::new(2f64.sqrt(), &p);
+
+        // When
+        let comps = i.prepare_computations(&r);
+
+        // Then
+        assert_eq!(comps.normalv, Vector::new(1.0, 1.0, 0.0).normalize());
+        assert_eq!(comps.geometric_normalv, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(comps.over_point, Point::new(0.0, EPSILON_OFFSET, 0.0));
+        assert!(!comps.inside);
+    }
+}
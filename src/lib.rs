@@ -1,4 +1,26 @@
 pub mod core;
 pub mod color;
+pub mod spectrum;
 pub mod canvas;
-
+pub mod image;
+pub mod ray;
+pub mod random;
+pub mod sampling;
+pub mod noise;
+pub mod bounds;
+pub mod bvh;
+pub mod intersection;
+pub mod bsdf;
+pub mod bump;
+pub mod material;
+pub mod medium;
+pub mod shape;
+pub mod light;
+pub mod world;
+pub mod camera;
+pub mod integrator;
+pub mod render;
+pub mod animation;
+pub mod sequence;
+pub mod obj;
+pub mod scene;
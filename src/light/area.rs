@@ -0,0 +1,419 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::bounds::BoundingBox;
+use crate::color::Color;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::light::{Falloff, Light, LightSample};
+use crate::random::random;
+use crate::ray::Ray;
+use crate::sampling::{cosine_hemisphere, uniform_sphere, Frame};
+
+/// A rectangular light spanned by two edges from `corner`, split into a grid of `usteps` by
+/// `vsteps` cells. Every cell contributes one sample, taken at a random position inside the cell
+/// when `jitter` is set or at its center otherwise.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AreaLight {
+    pub corner: Point,
+    pub uvec: Vector,
+    pub usteps: usize,
+    pub vvec: Vector,
+    pub vsteps: usize,
+    pub intensity: Color,
+    pub falloff: Falloff,
+    pub jitter: bool,
+}
+
+impl AreaLight {
+    pub fn new(corner: Point, full_uvec: Vector, usteps: usize, full_vvec: Vector, vsteps: usize, intensity: Color) -> Self {
+        Self {
+            corner,
+            uvec: full_uvec / usteps as f64,
+            usteps,
+            vvec: full_vvec / vsteps as f64,
+            vsteps,
+            intensity,
+            falloff: Falloff::None,
+            jitter: true,
+        }
+    }
+
+    /// The center of the light.
+    pub fn position(&self) -> Point {
+        self.corner + self.uvec * (self.usteps as f64 / 2.0) + self.vvec * (self.vsteps as f64 / 2.0)
+    }
+
+    pub fn point_on_light(&self, u: usize, v: usize) -> Point {
+        let (ju, jv) = match self.jitter {
+            true  => (random(), random()),
+            false => (0.5, 0.5),
+        };
+
+        self.corner + self.uvec * (u as f64 + ju) + self.vvec * (v as f64 + jv)
+    }
+
+    /// The whole edges of the light, and the normal of its surface scaled by its area.
+    fn edges(&self) -> (Vector, Vector, Vector) {
+        let (u, v) = (self.uvec * self.usteps as f64, self.vvec * self.vsteps as f64);
+
+        (u, v, u.cross(v))
+    }
+
+    /// The radiance of both sides of the surface.
+    fn radiance(&self) -> Color {
+        self.intensity * (1.0 / self.edges().2.magnitude())
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "area-light"))]
+impl Light for AreaLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        (0..self.vsteps)
+            .flat_map(|v| (0..self.usteps).map(move |u| (u, v)))
+            .map(|(u, v)| LightSample::from_position(point, self.point_on_light(u, v), self.intensity, self.falloff))
+            .collect()
+    }
+
+    fn has_area(&self) -> bool {
+        true
+    }
+
+    fn sample_radiance(&self, point: Point, u: f64, v: f64) -> Option<(LightSample, f64)> {
+        let (uvec, vvec, normal) = self.edges();
+        let sample = LightSample::from_position(point, self.corner + uvec * u + vvec * v, self.radiance(), Falloff::None);
+        let cos = sample.direction.dot(normal).abs();
+        if cos == 0.0 || sample.distance == 0.0 {
+            return None;
+        }
+
+        Some((sample, sample.distance * sample.distance / cos))
+    }
+
+    fn pdf(&self, point: Point, direction: Vector) -> f64 {
+        let normal = self.edges().2;
+
+        match self.hit(&Ray::new(point, direction)) {
+            Some((t, _)) => (direction * t).magnitude().powi(2) / direction.normalize().dot(normal).abs(),
+            None         => 0.0,
+        }
+    }
+
+    fn hit(&self, ray: &Ray) -> Option<(f64, Color)> {
+        let (uvec, vvec, normal) = self.edges();
+        let denominator = ray.direction.dot(normal);
+        if denominator == 0.0 {
+            return None;
+        }
+        let t = (self.corner - ray.origin).dot(normal) / denominator;
+        if t <= 0.0 {
+            return None;
+        }
+
+        // The coordinates of the hit along the edges, which also works for parallelograms.
+        let w = ray.position(t) - self.corner;
+        let area2 = normal.dot(normal);
+        let u = w.cross(vvec).dot(normal) / area2;
+        let v = uvec.cross(w).dot(normal) / area2;
+
+        ((0.0..=1.0).contains(&u) && (0.0..=1.0).contains(&v)).then(|| (t, self.radiance()))
+    }
+
+    /// Photons leave either side of the light from anywhere on it, more of them straight out.
+    fn emit(&self, bounds: &BoundingBox, [u, v, s, t]: [f64; 4]) -> Option<(Ray, Color)> {
+        let (uvec, vvec, normal) = self.edges();
+        // The first number picks the side as well as the position along the first edge.
+        let (side, u) = match u < 0.5 {
+            true  => (1.0, u * 2.0),
+            false => (-1.0, u * 2.0 - 1.0),
+        };
+        let direction = Frame::new(normal.normalize() * side).to_world(cosine_hemisphere(s, t));
+
+        Some((Ray::new(self.corner + uvec * u + vvec * v, direction), self.power(bounds)))
+    }
+
+    fn power(&self, _bounds: &BoundingBox) -> Color {
+        self.intensity * TAU
+    }
+}
+
+/// A spherical light, sampled at `samples` points spread evenly over its surface along a
+/// Fibonacci spiral. With `jitter` set, every sample is moved randomly within its own band of the
+/// spiral and the spiral as a whole is randomly rotated around the vertical axis.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SphereLight {
+    pub center: Point,
+    pub radius: f64,
+    pub samples: usize,
+    pub intensity: Color,
+    pub falloff: Falloff,
+    pub jitter: bool,
+}
+
+impl SphereLight {
+    pub fn new(center: Point, radius: f64, samples: usize, intensity: Color) -> Self {
+        Self { center, radius, samples, intensity, falloff: Falloff::None, jitter: true, }
+    }
+
+    /// Positions of the samples on the surface of the light.
+    pub fn points_on_light(&self) -> Vec<Point> {
+        let golden_angle = PI * (3.0 - 5f64.sqrt());
+        let rotation = match self.jitter {
+            true  => random::<f64>() * 2.0 * PI,
+            false => 0.0,
+        };
+
+        (0..self.samples)
+            .map(|i| {
+                let offset = match self.jitter {
+                    true  => random(),
+                    false => 0.5,
+                };
+                let z = 1.0 - 2.0 * (i as f64 + offset) / self.samples as f64;
+                let r = (1.0 - z * z).sqrt();
+                let phi = rotation + golden_angle * i as f64;
+
+                self.center + Vector::new(r * phi.cos(), r * phi.sin(), z) * self.radius
+            })
+            .collect()
+    }
+
+    /// The radiance of the surface.
+    fn radiance(&self) -> Color {
+        self.intensity * (1.0 / (PI * self.radius * self.radius))
+    }
+
+    /// The cosine of the half-angle of the cone the light fills as seen from `point`, or None
+    /// from inside the light.
+    fn cone(&self, point: Point) -> Option<f64> {
+        let sin2 = self.radius * self.radius / (self.center - point).dot(self.center - point);
+
+        (sin2 < 1.0).then(|| (1.0 - sin2).sqrt())
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "sphere-light"))]
+impl Light for SphereLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        self.points_on_light().into_iter()
+            .map(|position| LightSample::from_position(point, position, self.intensity, self.falloff))
+            .collect()
+    }
+
+    fn has_area(&self) -> bool {
+        true
+    }
+
+    /// Only the part of the light facing `point` is sampled, by picking directions evenly
+    /// within the cone it fills.
+    fn sample_radiance(&self, point: Point, u: f64, v: f64) -> Option<(LightSample, f64)> {
+        let cos_max = self.cone(point)?;
+        let cos = 1.0 - u * (1.0 - cos_max);
+        let sin = (1.0 - cos * cos).max(0.0).sqrt();
+        let (sin_phi, cos_phi) = (v * TAU).sin_cos();
+        let direction = Frame::new((self.center - point).normalize()).to_world(Vector::new(sin * cos_phi, sin * sin_phi, cos));
+        let (distance, radiance) = self.hit(&Ray::new(point, direction))?;
+
+        Some((LightSample::new(direction, distance, radiance), 1.0 / (TAU * (1.0 - cos_max))))
+    }
+
+    fn pdf(&self, point: Point, direction: Vector) -> f64 {
+        match (self.cone(point), self.hit(&Ray::new(point, direction))) {
+            (Some(cos_max), Some(_)) => 1.0 / (TAU * (1.0 - cos_max)),
+            _                        => 0.0,
+        }
+    }
+
+    fn hit(&self, ray: &Ray) -> Option<(f64, Color)> {
+        let to_origin = ray.origin - self.center;
+        let a = ray.direction.dot(ray.direction);
+        let b = ray.direction.dot(to_origin);
+        let c = to_origin.dot(to_origin) - self.radius * self.radius;
+        let discriminant = b * b - a * c;
+        if discriminant < 0.0 {
+            return None;
+        }
+        let t = (-b - discriminant.sqrt()) / a;
+
+        (t > 0.0).then(|| (t, self.radiance()))
+    }
+
+    fn emit(&self, bounds: &BoundingBox, [u, v, s, t]: [f64; 4]) -> Option<(Ray, Color)> {
+        let normal = uniform_sphere(u, v);
+        let direction = Frame::new(normal).to_world(cosine_hemisphere(s, t));
+
+        Some((Ray::new(self.center + normal * self.radius, direction), self.power(bounds)))
+    }
+
+    fn power(&self, _bounds: &BoundingBox) -> Color {
+        self.intensity * (4.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_1_SQRT_2;
+
+    use crate::core::float::ApproxEq;
+    use crate::light::lighting;
+    use crate::material::Material;
+    use crate::world::tests::default_world;
+
+    use super::*;
+
+    #[test]
+    fn creating_an_area_light() {
+        // Given
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+
+        // When
+        let light = AreaLight::new(corner, v1, 4, v2, 2, Color::new(1.0, 1.0, 1.0));
+
+        // Then
+        assert_eq!(light.corner, corner);
+        assert_eq!(light.uvec, Vector::new(0.5, 0.0, 0.0));
+        assert_eq!(light.usteps, 4);
+        assert_eq!(light.vvec, Vector::new(0.0, 0.0, 0.5));
+        assert_eq!(light.vsteps, 2);
+        assert_eq!(light.samples(Point::zero()).len(), 8);
+        assert_eq!(light.position(), Point::new(1.0, 0.0, 0.5));
+    }
+
+    #[test]
+    fn finding_a_single_point_on_an_area_light() {
+        // Given
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let mut light = AreaLight::new(corner, v1, 4, v2, 2, Color::new(1.0, 1.0, 1.0));
+        light.jitter = false;
+
+        // Then
+        assert_eq!(light.point_on_light(0, 0), Point::new(0.25, 0.0, 0.25));
+        assert_eq!(light.point_on_light(1, 0), Point::new(0.75, 0.0, 0.25));
+        assert_eq!(light.point_on_light(0, 1), Point::new(0.25, 0.0, 0.75));
+        assert_eq!(light.point_on_light(2, 0), Point::new(1.25, 0.0, 0.25));
+        assert_eq!(light.point_on_light(3, 1), Point::new(1.75, 0.0, 0.75));
+    }
+
+    #[test]
+    fn jittered_points_stay_inside_their_cell() {
+        // Given
+        let corner = Point::new(0.0, 0.0, 0.0);
+        let v1 = Vector::new(2.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 0.0, 1.0);
+        let light = AreaLight::new(corner, v1, 4, v2, 2, Color::new(1.0, 1.0, 1.0));
+
+        // When
+        let p = light.point_on_light(3, 1);
+
+        // Then
+        assert!((1.5..=2.0).contains(&p.x()));
+        assert!((0.5..=1.0).contains(&p.z()));
+    }
+
+    #[test]
+    fn area_light_intensity_function() {
+        // Given
+        let w = default_world();
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        let mut light = AreaLight::new(corner, v1, 2, v2, 2, Color::new(1.0, 1.0, 1.0));
+        light.jitter = false;
+
+        // Then
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, 2.0), 0.0, &w), 0.0);
+        assert_eq!(light.intensity_at(Point::new(1.0, -1.0, 2.0), 0.0, &w), 0.25);
+        assert_eq!(light.intensity_at(Point::new(1.5, 0.0, 2.0), 0.0, &w), 0.5);
+        assert_eq!(light.intensity_at(Point::new(1.25, 1.25, 3.0), 0.0, &w), 0.75);
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, -2.0), 0.0, &w), 1.0);
+    }
+
+    #[test]
+    fn lighting_samples_the_area_light() {
+        // Given
+        let corner = Point::new(-0.5, -0.5, -5.0);
+        let v1 = Vector::new(1.0, 0.0, 0.0);
+        let v2 = Vector::new(0.0, 1.0, 0.0);
+        let mut light = AreaLight::new(corner, v1, 2, v2, 2, Color::new(1.0, 1.0, 1.0));
+        light.jitter = false;
+        let m = Material { ambient: 0.1, diffuse: 0.9, specular: 0.0, ..Material::default() };
+        let eye = Point::new(0.0, 0.0, -5.0);
+
+        // Then
+        let pt = Point::new(0.0, 0.0, -1.0);
+        let (eyev, normalv) = ((eye - pt).normalize(), pt - Point::zero());
+        assert_eq!(lighting(&m, &light, pt, eyev, normalv, 1.0), Color::new(0.9965, 0.9965, 0.9965));
+
+        let pt = Point::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2);
+        let (eyev, normalv) = ((eye - pt).normalize(), pt - Point::zero());
+        assert_eq!(lighting(&m, &light, pt, eyev, normalv, 1.0), Color::new(0.6232, 0.6232, 0.6232));
+    }
+
+    #[test]
+    fn sphere_light_samples_lie_on_its_surface() {
+        // Given
+        let light = SphereLight::new(Point::new(1.0, 2.0, 3.0), 0.5, 16, Color::new(1.0, 1.0, 1.0));
+
+        // When
+        let samples = light.points_on_light();
+
+        // Then
+        assert_eq!(samples.len(), 16);
+        assert!(samples.iter().all(|&s| ((s - light.center).magnitude() - 0.5).abs() < 1.0e-9));
+    }
+
+    #[test]
+    fn sphere_light_intensity_function() {
+        // Given
+        let w = default_world();
+        let mut light = SphereLight::new(Point::new(0.0, 0.0, -5.0), 1.0, 32, Color::new(1.0, 1.0, 1.0));
+        light.jitter = false;
+
+        // When
+        let penumbra = light.intensity_at(Point::new(1.5, 0.0, 2.0), 0.0, &w);
+
+        // Then
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, 2.0), 0.0, &w), 0.0);
+        assert!(penumbra > 0.0 && penumbra < 1.0);
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, -2.0), 0.0, &w), 1.0);
+    }
+
+    #[test]
+    fn lights_with_an_area_can_be_hit_and_sampled() {
+        // Given
+        let rectangle = AreaLight::new(Point::new(-1.0, 2.0, -1.0), Vector::new(2.0, 0.0, 0.0), 2, Vector::new(0.0, 0.0, 2.0), 2, Color::new(4.0, 4.0, 4.0));
+        let sphere = SphereLight::new(Point::new(0.0, 3.0, 0.0), 1.0, 8, Color::new(PI, PI, PI));
+        let lights: [&dyn Light; 2] = [&rectangle, &sphere];
+
+        for light in lights {
+            for (u, v) in [(0.5, 0.5), (0.1, 0.9), (0.8, 0.3)] {
+                // When
+                let (sample, pdf) = light.sample_radiance(Point::zero(), u, v).unwrap();
+                let (t, radiance) = light.hit(&Ray::new(Point::zero(), sample.direction)).unwrap();
+
+                // Then
+                assert!(t.approx_eq(sample.distance));
+                assert_eq!(radiance, Color::new(1.0, 1.0, 1.0));
+                assert!(pdf.approx_eq(light.pdf(Point::zero(), sample.direction)));
+            }
+            assert!(light.has_area());
+            assert!(light.hit(&Ray::new(Point::zero(), Vector::new(0.0, -1.0, 0.0))).is_none());
+        }
+        assert!(rectangle.pdf(Point::zero(), Vector::new(0.0, 1.0, 0.0)).approx_eq(1.0));
+        assert!(rectangle.hit(&Ray::new(Point::zero(), Vector::new(1.0, 1.0, 0.0))).is_none());
+    }
+}
@@ -0,0 +1,111 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::bounds::BoundingBox;
+use crate::color::Color;
+#[cfg(feature = "serde")]
+use crate::core::float::ApproxEq;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::light::{Light, LightSample};
+use crate::ray::Ray;
+use crate::sampling::Frame;
+
+/// A light infinitely far away, like the sun, whose rays all travel in the same `direction`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "DirectionalLightRepr"))]
+pub struct DirectionalLight {
+    pub direction: Vector,
+    pub intensity: Color,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: Vector, intensity: Color) -> Self {
+        Self { direction: direction.normalize(), intensity, }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct DirectionalLightRepr {
+    direction: Vector,
+    intensity: Color,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<DirectionalLightRepr> for DirectionalLight {
+    type Error = &'static str;
+
+    fn try_from(repr: DirectionalLightRepr) -> Result<Self, Self::Error> {
+        match repr.direction.magnitude().approx_eq(0.0) {
+            true  => Err("a direction can't be zero"),
+            false => Ok(DirectionalLight::new(repr.direction, repr.intensity)),
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "directional-light"))]
+impl Light for DirectionalLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, _point: Point) -> Vec<LightSample> {
+        vec![LightSample::new(-self.direction, f64::INFINITY, self.intensity)]
+    }
+
+    /// Photons are sent from a disk as wide as the sphere around `bounds`, facing the light from
+    /// outside of it.
+    fn emit(&self, bounds: &BoundingBox, [u, v, _, _]: [f64; 4]) -> Option<(Ray, Color)> {
+        let (center, radius) = sphere_around(bounds)?;
+        let (sin, cos) = (v * TAU).sin_cos();
+        let offset = Frame::new(self.direction).to_world(Vector::new(cos, sin, 0.0) * (u.sqrt() * radius));
+
+        Some((Ray::new(center + offset - self.direction * radius, self.direction), self.power(bounds)))
+    }
+
+    fn power(&self, bounds: &BoundingBox) -> Color {
+        sphere_around(bounds).map_or(Color::default(), |(_, radius)| self.intensity * (PI * radius * radius))
+    }
+}
+
+/// The center and radius of the sphere around a finite box that isn't empty.
+fn sphere_around(bounds: &BoundingBox) -> Option<(Point, f64)> {
+    (bounds.is_finite() && !bounds.is_empty()).then(|| (bounds.centroid(), (bounds.max - bounds.min).magnitude() / 2.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::world::tests::default_world;
+
+    use super::*;
+
+    #[test]
+    fn directional_light_shines_the_same_way_everywhere() {
+        // Given
+        let light = DirectionalLight::new(Vector::new(0.0, -2.0, 0.0), Color::new(1.0, 1.0, 1.0));
+
+        // When
+        let near = light.samples(Point::new(0.0, 0.0, 0.0));
+        let far = light.samples(Point::new(100.0, -50.0, 20.0));
+
+        // Then
+        assert_eq!(near, far);
+        assert_eq!(near[0].direction, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(near[0].distance, f64::INFINITY);
+        assert_eq!(near[0].endpoint(Point::zero()), None);
+        assert_eq!(near[0].intensity, Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn directional_light_casts_shadows_from_any_distance() {
+        // Given
+        let w = default_world();
+        let light = DirectionalLight::new(Vector::new(0.0, 0.0, 1.0), Color::new(1.0, 1.0, 1.0));
+
+        // Then
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, -1.0001), 0.0, &w), 1.0);
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, 1000.0), 0.0, &w), 0.0);
+        assert_eq!(light.intensity_at(Point::new(2.0, 0.0, 1000.0), 0.0, &w), 1.0);
+    }
+}
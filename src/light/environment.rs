@@ -0,0 +1,369 @@
+use std::f64::consts::{PI, TAU};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::core::matrix::Matrix4;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::image;
+use crate::light::preetham::PreethamSky;
+use crate::light::{Light, LightSample};
+use crate::random::random;
+use crate::ray::Ray;
+use crate::sampling::{uniform_sphere, Distribution2D, UNIFORM_SPHERE_PDF};
+
+/// A panorama of the light arriving from every direction, laid out like the canvas of an
+/// `EquirectangularCamera`: longitude across and latitude down, with `-z` in the middle.
+pub struct EnvironmentMap {
+    /// The file the map was loaded from, if any.
+    path: Option<PathBuf>,
+    image: Canvas,
+    /// Picks pixels in proportion to the light they send, weighed by the solid angle they cover.
+    distribution: Distribution2D,
+    /// The radiance averaged over all directions.
+    mean: Color,
+}
+
+impl EnvironmentMap {
+    pub fn new(image: Canvas) -> Self {
+        let (width, height) = (image.width() as usize, image.height() as usize);
+        let mut weights = Vec::with_capacity(width * height);
+        let mut mean = Color::default();
+
+        for y in 0..height {
+            // Rows near the poles are squeezed onto a smaller part of the sphere.
+            let sin_theta = (PI * (y as f64 + 0.5) / height as f64).sin();
+            for x in 0..width {
+                let c = image[(x, y)];
+                weights.push(c.luminance() * sin_theta);
+                mean = mean + c * sin_theta;
+            }
+        }
+        let solid_angle = (0..height).map(|y| (PI * (y as f64 + 0.5) / height as f64).sin()).sum::<f64>() * width as f64;
+
+        Self {
+            path: None,
+            distribution: Distribution2D::new(&weights, width),
+            mean: mean * (1.0 / solid_angle),
+            image,
+        }
+    }
+
+    /// Loads a Radiance `.hdr` or OpenEXR `.exr` panorama.
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        let map = Self::new(image::load(&path)?);
+
+        Ok(Self { path: Some(path.as_ref().to_path_buf()), ..map })
+    }
+
+    /// The radiance arriving from the unit vector `direction`.
+    pub fn radiance(&self, direction: Vector) -> Color {
+        let (u, v) = coordinates(direction);
+        let (width, height) = (self.image.width() as usize, self.image.height() as usize);
+
+        self.image[(((u * width as f64) as usize).min(width - 1), ((v * height as f64) as usize).min(height - 1))]
+    }
+
+    /// A direction picked by two numbers in `0..1`, more likely the brighter it is, together with
+    /// its density with respect to solid angle.
+    fn sample(&self, u: f64, v: f64) -> Option<(Vector, f64)> {
+        let ((x, y), pdf) = self.distribution.sample(u, v);
+        let sin_theta = (PI * y).sin();
+        if pdf == 0.0 || sin_theta == 0.0 {
+            return None;
+        }
+
+        Some((direction(x, y), pdf / (2.0 * PI * PI * sin_theta)))
+    }
+
+    fn pdf(&self, direction: Vector) -> f64 {
+        let (u, v) = coordinates(direction);
+        let sin_theta = (PI * v).sin();
+
+        match sin_theta {
+            0.0 => 0.0,
+            _   => self.distribution.pdf(u, v) / (2.0 * PI * PI * sin_theta),
+        }
+    }
+}
+
+impl fmt::Debug for EnvironmentMap {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EnvironmentMap")
+            .field("path", &self.path)
+            .field("width", &self.image.width())
+            .field("height", &self.image.height())
+            .finish()
+    }
+}
+
+/// What is stored of an environment map: the file it's loaded from again.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct EnvironmentMapRepr {
+    file: PathBuf,
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for EnvironmentMap {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let file = self.path.clone()
+            .ok_or_else(|| serde::ser::Error::custom("only environment maps loaded from a file can be stored"))?;
+
+        EnvironmentMapRepr { file }.serialize(serializer)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for EnvironmentMap {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let repr = EnvironmentMapRepr::deserialize(deserializer)?;
+
+        Self::load(&repr.file).map_err(|e| serde::de::Error::custom(format!("could not read {}: {}", repr.file.display(), e)))
+    }
+}
+
+/// What the sky looks like in every direction.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(tag = "type", rename_all = "kebab-case"))]
+pub enum Sky {
+    /// The same color everywhere.
+    Constant { color: Color },
+    /// Blends from `bottom` straight down to `top` straight up.
+    Gradient { bottom: Color, top: Color },
+    /// A panorama, usually a photograph.
+    Map(EnvironmentMap),
+    /// A clear daylight sky.
+    Preetham(PreethamSky),
+}
+
+impl Sky {
+    /// The radiance arriving from the unit vector `direction`.
+    pub fn radiance(&self, direction: Vector) -> Color {
+        match self {
+            Sky::Constant { color }       => *color,
+            Sky::Gradient { bottom, top } => {
+                let t = (direction.y() + 1.0) / 2.0;
+                *bottom * (1.0 - t) + *top * t
+            }
+            Sky::Map(map)                 => map.radiance(direction),
+            Sky::Preetham(sky)            => sky.radiance(direction),
+        }
+    }
+}
+
+/// Light arriving from infinitely far away in every direction, which is also what rays that
+/// miss everything see. `transform` turns the sky around, taking its directions to the world.
+///
+/// Maps are sampled in proportion to their brightness, so that small bright spots like the sun
+/// are found with few samples, and plain skies evenly over the sphere. Phong shading takes
+/// `samples` directions for every point it lights.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "EnvironmentLightRepr"))]
+pub struct EnvironmentLight {
+    pub sky: Sky,
+    /// Scales the radiance of the sky.
+    pub intensity: Color,
+    pub samples: usize,
+    transform: Matrix4,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    inverse: Matrix4,
+}
+
+impl EnvironmentLight {
+    pub fn new(sky: Sky) -> Self {
+        Self {
+            sky,
+            intensity: Color::new(1.0, 1.0, 1.0),
+            samples: 16,
+            transform: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+        }
+    }
+
+    pub fn transform(&self) -> &Matrix4 {
+        &self.transform
+    }
+
+    pub fn set_transform(&mut self, transform: Matrix4) {
+        self.transform = transform;
+        self.inverse = transform.invert();
+    }
+
+    /// The radiance arriving from the unit vector `direction` in world space.
+    pub fn radiance(&self, direction: Vector) -> Color {
+        self.sky.radiance((self.inverse * direction).normalize()) * self.intensity
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct EnvironmentLightRepr {
+    sky: Sky,
+    intensity: Color,
+    samples: usize,
+    #[serde(default = "Matrix4::identity")]
+    transform: Matrix4,
+}
+
+#[cfg(feature = "serde")]
+impl From<EnvironmentLightRepr> for EnvironmentLight {
+    fn from(repr: EnvironmentLightRepr) -> Self {
+        let mut light = EnvironmentLight { intensity: repr.intensity, samples: repr.samples, ..EnvironmentLight::new(repr.sky) };
+        light.set_transform(repr.transform);
+
+        light
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "environment-light"))]
+impl Light for EnvironmentLight {
+    fn intensity(&self) -> Color {
+        let mean = match &self.sky {
+            Sky::Constant { color }       => *color,
+            Sky::Gradient { bottom, top } => (*bottom + *top) * 0.5,
+            Sky::Map(map)                 => map.mean,
+            Sky::Preetham(sky)            => sky.mean(),
+        };
+
+        mean * self.intensity
+    }
+
+    /// Directions picked as for `sample_radiance`, each with the light it brings divided by its
+    /// density so that their average estimates the light from the whole sky.
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        (0..self.samples)
+            .filter_map(|_| self.sample_radiance(point, random(), random()))
+            .map(|(sample, pdf)| LightSample { intensity: sample.intensity * (1.0 / pdf), ..sample })
+            .collect()
+    }
+
+    fn has_area(&self) -> bool {
+        true
+    }
+
+    fn sample_radiance(&self, _point: Point, u: f64, v: f64) -> Option<(LightSample, f64)> {
+        let (local, pdf) = match &self.sky {
+            Sky::Map(map) => map.sample(u, v)?,
+            _             => (uniform_sphere(u, v), UNIFORM_SPHERE_PDF),
+        };
+        // Rotations keep the density, which is all the transform is meant for.
+        let direction = (self.transform * local).normalize();
+
+        Some((LightSample::new(direction, f64::INFINITY, self.radiance(direction)), pdf))
+    }
+
+    fn pdf(&self, _point: Point, direction: Vector) -> f64 {
+        match &self.sky {
+            Sky::Map(map) => map.pdf((self.inverse * direction).normalize()),
+            _             => UNIFORM_SPHERE_PDF,
+        }
+    }
+
+    fn hit(&self, ray: &Ray) -> Option<(f64, Color)> {
+        Some((f64::INFINITY, self.radiance(ray.direction.normalize())))
+    }
+}
+
+/// Where the unit vector `direction` is on a map, as fractions of its width and height.
+fn coordinates(direction: Vector) -> (f64, f64) {
+    let longitude = (-direction.x()).atan2(-direction.z());
+    let latitude = direction.y().clamp(-1.0, 1.0).asin();
+
+    (longitude / TAU + 0.5, 0.5 - latitude / PI)
+}
+
+/// The direction at the coordinates `(u, v)` of a map.
+fn direction(u: f64, v: f64) -> Vector {
+    let (sin_longitude, cos_longitude) = ((u - 0.5) * TAU).sin_cos();
+    let (sin_latitude, cos_latitude) = ((0.5 - v) * PI).sin_cos();
+
+    Vector::new(-sin_longitude * cos_latitude, sin_latitude, -cos_longitude * cos_latitude)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_2;
+
+    use crate::core::float::ApproxEq;
+
+    use super::*;
+
+    /// A dark map with a bright spot at `(x, y)`.
+    fn spot_map(x: usize, y: usize) -> EnvironmentMap {
+        let mut image = Canvas::new(16, 8);
+        for py in 0..8 {
+            for px in 0..16 {
+                image[(px, py)] = Color::new(0.1, 0.1, 0.1);
+            }
+        }
+        image[(x, y)] = Color::new(100.0, 100.0, 100.0);
+
+        EnvironmentMap::new(image)
+    }
+
+    #[test]
+    fn maps_are_laid_out_like_panoramas() {
+        // Given
+        let map = spot_map(12, 4);
+
+        // Then
+        assert_eq!(map.radiance(Vector::new(-1.0, 0.0, 0.2).normalize()), Color::new(100.0, 100.0, 100.0));
+        assert_eq!(map.radiance(Vector::new(1.0, 0.0, 0.0)), Color::new(0.1, 0.1, 0.1));
+        for (u, v) in [(0.3, 0.2), (0.75, 0.5), (0.9, 0.95)] {
+            let (cu, cv) = coordinates(direction(u, v));
+            assert!(cu.approx_eq(u) && cv.approx_eq(v));
+        }
+    }
+
+    #[test]
+    fn maps_are_sampled_where_they_are_bright() {
+        // Given
+        let map = spot_map(3, 2);
+
+        // When
+        let samples = (0..1000).filter_map(|i| map.sample((i as f64 + 0.5) / 1000.0, (i as f64 * 0.618_034).fract())).collect::<Vec<_>>();
+
+        // Then
+        let bright = samples.iter().filter(|(d, _)| map.radiance(*d).r > 1.0).count();
+        assert!(bright > 900, "{}", bright);
+        for (direction, pdf) in &samples {
+            assert!((map.pdf(*direction) / pdf - 1.0).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn importance_sampling_estimates_the_light_of_the_whole_sky() {
+        // Given
+        let light = EnvironmentLight::new(Sky::Map(spot_map(5, 6)));
+        crate::random::seed(11);
+
+        // When
+        let n = 20000;
+        let estimate = (0..n)
+            .filter_map(|_| light.sample_radiance(Point::zero(), random(), random()))
+            .map(|(sample, pdf)| sample.intensity.g / pdf)
+            .sum::<f64>() / n as f64;
+
+        // Then
+        assert!((estimate / (4.0 * PI * light.intensity().g) - 1.0).abs() < 0.01, "{}", estimate);
+    }
+
+    #[test]
+    fn rotating_the_sky() {
+        // Given
+        let mut light = EnvironmentLight::new(Sky::Gradient { bottom: Color::new(0.0, 0.0, 0.0), top: Color::new(1.0, 0.0, 0.0) });
+
+        // When
+        light.set_transform(Matrix4::rotation_z(FRAC_PI_2));
+
+        // Then: the top of the sky is turned to -x.
+        assert_eq!(light.radiance(Vector::new(-1.0, 0.0, 0.0)), Color::new(1.0, 0.0, 0.0));
+        assert_eq!(light.hit(&Ray::new(Point::zero(), Vector::new(0.0, 1.0, 0.0))), Some((f64::INFINITY, Color::new(0.5, 0.0, 0.0))));
+        assert_eq!(light.intensity(), Color::new(0.5, 0.0, 0.0));
+    }
+}
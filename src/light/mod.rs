@@ -0,0 +1,325 @@
+pub mod area;
+pub mod directional;
+pub mod environment;
+pub mod point;
+pub mod preetham;
+pub mod spot;
+
+use std::fmt::Debug;
+
+use crate::bounds::BoundingBox;
+use crate::color::Color;
+use crate::core::point::Point;
+use crate::core::vector::Vector;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::world::World;
+
+/// How the light of a positional light diminishes with the distance it travels.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Falloff {
+    /// The light reaches every point at full intensity.
+    #[default]
+    None,
+    /// Physically based attenuation by `1 / distance²`.
+    InverseSquare,
+}
+
+impl Falloff {
+    pub fn attenuate(&self, intensity: Color, distance: f64) -> Color {
+        match self {
+            Falloff::None          => intensity,
+            Falloff::InverseSquare => intensity * (1.0 / (distance * distance)),
+        }
+    }
+}
+
+/// A single sample of a light, as seen from the point being lit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LightSample {
+    /// Unit vector from the lit point towards the light.
+    pub direction: Vector,
+    /// Distance from the lit point to the end of the shadow ray, infinite for lights that have
+    /// no position.
+    pub distance: f64,
+    /// The light arriving at the lit point from this sample.
+    pub intensity: Color,
+}
+
+impl LightSample {
+    pub fn new(direction: Vector, distance: f64, intensity: Color) -> Self {
+        Self { direction, distance, intensity, }
+    }
+
+    /// A sample of light emitted from `position` towards `point`.
+    pub fn from_position(point: Point, position: Point, intensity: Color, falloff: Falloff) -> Self {
+        let v = position - point;
+        let distance = v.magnitude();
+
+        Self::new(v / distance, distance, falloff.attenuate(intensity, distance))
+    }
+
+    /// The point at the far end of the shadow ray, if the light has a position.
+    pub fn endpoint(&self, point: Point) -> Option<Point> {
+        match self.distance.is_finite() {
+            true  => Some(point + self.direction * self.distance),
+            false => None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(tag = "type"))]
+pub trait Light: Debug + Send + Sync {
+    /// The nominal color and strength of the light, which also drives the ambient term.
+    fn intensity(&self) -> Color;
+
+    /// Samples of the light as seen from `point`, used to estimate both its visibility and its
+    /// contribution to the shading of `point`. Lights with an area return a new (possibly
+    /// jittered) set of samples on every call, so the same set should be used for both.
+    fn samples(&self, point: Point) -> Vec<LightSample>;
+
+    /// The fraction of the light that reaches `point` at `time`, from 0.0 (fully in shadow) to
+    /// 1.0.
+    fn intensity_at(&self, point: Point, time: f64, world: &World) -> f64 {
+        visibility(&self.samples(point), point, time, world)
+    }
+
+    /// Whether the light has a surface that glows, which rays can hit. Lights that are a point or
+    /// a direction can only be sampled. Environments are hit infinitely far away by any ray that
+    /// misses everything else.
+    ///
+    /// For the path tracer, the surface of such a light glows with the radiance that makes it as
+    /// bright far away as its intensity with inverse-square falloff, whatever its `falloff`.
+    fn has_area(&self) -> bool {
+        false
+    }
+
+    /// A point on the surface of the light picked by two numbers in `0..1` among those seen from
+    /// `point`, as a sample with the radiance it sends towards `point`, together with the density
+    /// of its direction with respect to solid angle.
+    fn sample_radiance(&self, _point: Point, _u: f64, _v: f64) -> Option<(LightSample, f64)> {
+        None
+    }
+
+    /// The density with which `sample_radiance` picks `direction` from `point`.
+    fn pdf(&self, _point: Point, _direction: Vector) -> f64 {
+        0.0
+    }
+
+    /// Where `ray` first hits the surface of the light, and the radiance it sees there.
+    fn hit(&self, _ray: &Ray) -> Option<(f64, Color)> {
+        None
+    }
+
+    /// A ray of light leaving the light picked by four numbers in `0..1`, with the power it
+    /// carries divided by the density with which it was picked, so that it averages out to the
+    /// `power` of the light. `bounds` is the box around the world, which lights without a
+    /// position shine on. None for lights that don't send out photons, such as environments.
+    ///
+    /// Like the surfaces of lights with an area, lights are taken to fall off with the square of
+    /// the distance for this, whatever their `falloff`.
+    fn emit(&self, _bounds: &BoundingBox, _u: [f64; 4]) -> Option<(Ray, Color)> {
+        None
+    }
+
+    /// About how much light the light sends out into `bounds` in total, which photons are shared
+    /// out between lights by.
+    fn power(&self, _bounds: &BoundingBox) -> Color {
+        Color::default()
+    }
+}
+
+/// The fraction of `samples` of a light that reach `point` at `time`, unless anything blocks them.
+/// None of the light reaches `point` when there are no samples.
+pub fn visibility(samples: &[LightSample], point: Point, time: f64, world: &World) -> f64 {
+    if samples.is_empty() {
+        return 0.0;
+    }
+    let visible = samples.iter()
+        .filter(|sample| !world.is_occluded(point, sample.direction, sample.distance, time))
+        .count();
+
+    visible as f64 / samples.len() as f64
+}
+
+/// Phong reflection model, with the diffuse and specular terms averaged over the samples of the
+/// light and scaled by the fraction of the light that is visible from `point`.
+pub fn lighting(
+    material: &Material,
+    light: &dyn Light,
+    point: Point,
+    eyev: Vector,
+    normalv: Vector,
+    intensity: f64,
+) -> Color {
+    lighting_samples(material, light, &light.samples(point), eyev, normalv, intensity)
+}
+
+/// Phong reflection model like `lighting`, averaged over given `samples` of the light, of which
+/// only the ambient term is left without any samples.
+pub fn lighting_samples(
+    material: &Material,
+    light: &dyn Light,
+    samples: &[LightSample],
+    eyev: Vector,
+    normalv: Vector,
+    intensity: f64,
+) -> Color {
+    let ambient = material.color * light.intensity() * material.ambient;
+    if samples.is_empty() {
+        return ambient;
+    }
+    let mut sum = Color::default();
+
+    for sample in samples {
+        let lightv = sample.direction;
+        let light_dot_normal = lightv.dot(normalv);
+
+        if light_dot_normal < 0.0 {
+            continue;
+        }
+
+        let diffuse = material.color * sample.intensity * material.diffuse * light_dot_normal;
+        let reflect_dot_eye = (-lightv).reflect(normalv).dot(eyev);
+        let specular = match reflect_dot_eye {
+            r if r <= 0.0 => Color::default(),
+            r             => sample.intensity * material.specular * r.powf(material.shininess),
+        };
+
+        sum = sum + diffuse + specular;
+    }
+
+    ambient + sum * (intensity / samples.len() as f64)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::tuple::Tuple;
+    use crate::light::point::PointLight;
+    use crate::world::tests::default_world;
+
+    use super::*;
+
+    fn setup() -> (Material, Point) {
+        (Material::default(), Point::zero())
+    }
+
+    #[test]
+    fn lighting_with_the_eye_between_the_light_and_the_surface() {
+        // Given
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        // Then
+        assert_eq!(lighting(&m, &light, position, eyev, normalv, 1.0), Color::new(1.9, 1.9, 1.9));
+    }
+
+    #[test]
+    fn lighting_with_the_eye_offset_45_degrees() {
+        // Given
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 2f64.sqrt() / 2.0, -(2f64.sqrt()) / 2.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        // Then
+        assert_eq!(lighting(&m, &light, position, eyev, normalv, 1.0), Color::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn lighting_with_the_light_offset_45_degrees() {
+        // Given
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        // Then
+        assert_eq!(lighting(&m, &light, position, eyev, normalv, 1.0), Color::new(0.7364, 0.7364, 0.7364));
+    }
+
+    #[test]
+    fn lighting_with_the_eye_in_the_path_of_the_reflection() {
+        // Given
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, -(2f64.sqrt()) / 2.0, -(2f64.sqrt()) / 2.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        // Then
+        assert_eq!(lighting(&m, &light, position, eyev, normalv, 1.0), Color::new(1.6364, 1.6364, 1.6364));
+    }
+
+    #[test]
+    fn lighting_with_the_light_behind_the_surface() {
+        // Given
+        let (m, position) = setup();
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+        let light = PointLight::new(Point::new(0.0, 0.0, 10.0), Color::new(1.0, 1.0, 1.0));
+
+        // Then
+        assert_eq!(lighting(&m, &light, position, eyev, normalv, 1.0), Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn lighting_uses_light_intensity_to_attenuate_color() {
+        // Given
+        let w = default_world();
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+        let mut m = w.objects()[0].material().clone();
+        m.ambient = 0.1;
+        m.diffuse = 0.9;
+        m.specular = 0.0;
+        m.color = Color::new(1.0, 1.0, 1.0);
+        let pt = Point::new(0.0, 0.0, -1.0);
+        let eyev = Vector::new(0.0, 0.0, -1.0);
+        let normalv = Vector::new(0.0, 0.0, -1.0);
+
+        // Then
+        assert_eq!(lighting(&m, &light, pt, eyev, normalv, 1.0), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(lighting(&m, &light, pt, eyev, normalv, 0.5), Color::new(0.55, 0.55, 0.55));
+        assert_eq!(lighting(&m, &light, pt, eyev, normalv, 0.0), Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn lighting_without_samples_is_only_ambient() {
+        // Given
+        let w = default_world();
+        let (m, position) = setup();
+        let light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        // Then
+        assert_eq!(visibility(&[], position, 0.0, &w), 0.0);
+        assert_eq!(lighting_samples(&m, &light, &[], Vector::new(0.0, 0.0, -1.0), Vector::new(0.0, 0.0, -1.0), 1.0), Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn inverse_square_falloff_attenuates_with_distance() {
+        // Given
+        let intensity = Color::new(8.0, 8.0, 8.0);
+
+        // Then
+        assert_eq!(Falloff::None.attenuate(intensity, 2.0), intensity);
+        assert_eq!(Falloff::InverseSquare.attenuate(intensity, 2.0), Color::new(2.0, 2.0, 2.0));
+        assert_eq!(Falloff::InverseSquare.attenuate(intensity, 4.0), Color::new(0.5, 0.5, 0.5));
+    }
+
+    #[test]
+    fn light_sample_from_a_position() {
+        // Given
+        let point = Point::new(0.0, 0.0, 0.0);
+        let position = Point::new(0.0, 0.0, -10.0);
+
+        // When
+        let sample = LightSample::from_position(point, position, Color::new(1.0, 1.0, 1.0), Falloff::None);
+
+        // Then
+        assert_eq!(sample.direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(sample.distance, 10.0);
+        assert_eq!(sample.endpoint(point), Some(position));
+    }
+}
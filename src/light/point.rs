@@ -0,0 +1,97 @@
+use std::f64::consts::PI;
+
+use crate::bounds::BoundingBox;
+use crate::color::Color;
+use crate::core::point::Point;
+use crate::light::{Falloff, Light, LightSample};
+use crate::ray::Ray;
+use crate::sampling::uniform_sphere;
+
+/// An infinitely small light shining in all directions, which casts hard shadows.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointLight {
+    pub position: Point,
+    pub intensity: Color,
+    pub falloff: Falloff,
+}
+
+impl PointLight {
+    pub fn new(position: Point, intensity: Color) -> Self {
+        Self { position, intensity, falloff: Falloff::None, }
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "point-light"))]
+impl Light for PointLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        vec![LightSample::from_position(point, self.position, self.intensity, self.falloff)]
+    }
+
+    fn emit(&self, bounds: &BoundingBox, [u, v, _, _]: [f64; 4]) -> Option<(Ray, Color)> {
+        Some((Ray::new(self.position, uniform_sphere(u, v)), self.power(bounds)))
+    }
+
+    fn power(&self, _bounds: &BoundingBox) -> Color {
+        self.intensity * (4.0 * PI)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::tuple::Tuple;
+    use crate::core::vector::Vector;
+    use crate::world::tests::default_world;
+
+    use super::*;
+
+    #[test]
+    fn point_light_has_a_position_and_intensity() {
+        // Given
+        let intensity = Color::new(1.0, 1.0, 1.0);
+        let position = Point::new(0.0, 0.0, 0.0);
+
+        // When
+        let light = PointLight::new(position, intensity);
+
+        // Then
+        assert_eq!(light.position, position);
+        assert_eq!(light.intensity, intensity);
+    }
+
+    #[test]
+    fn point_lights_evaluate_the_light_intensity_at_a_given_point() {
+        // Given
+        let w = default_world();
+        let light = &w.lights[0];
+
+        // Then
+        assert_eq!(light.intensity_at(Point::new(0.0, 1.0001, 0.0), 0.0, &w), 1.0);
+        assert_eq!(light.intensity_at(Point::new(-1.0001, 0.0, 0.0), 0.0, &w), 1.0);
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, -1.0001), 0.0, &w), 1.0);
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, 1.0001), 0.0, &w), 0.0);
+        assert_eq!(light.intensity_at(Point::new(1.0001, 0.0, 0.0), 0.0, &w), 0.0);
+        assert_eq!(light.intensity_at(Point::new(0.0, -1.0001, 0.0), 0.0, &w), 0.0);
+        assert_eq!(light.intensity_at(Point::new(0.0, 0.0, 0.0), 0.0, &w), 0.0);
+    }
+
+    #[test]
+    fn point_light_with_inverse_square_falloff() {
+        // Given
+        let mut light = PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(100.0, 100.0, 100.0));
+        light.falloff = Falloff::InverseSquare;
+
+        // When
+        let samples = light.samples(Point::new(0.0, 0.0, -5.0));
+
+        // Then
+        assert_eq!(samples.len(), 1);
+        assert_eq!(samples[0].direction, Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(samples[0].distance, 5.0);
+        assert_eq!(samples[0].intensity, Color::new(4.0, 4.0, 4.0));
+    }
+}
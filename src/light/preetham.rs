@@ -0,0 +1,237 @@
+use std::f64::consts::{FRAC_PI_2, PI, TAU};
+
+use crate::color::Color;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::light::directional::DirectionalLight;
+
+/// Luminance in thousands of cd/m² for a radiance of 1, so that the sun high in a clear sky gives
+/// an irradiance of about 1, like other lights.
+const LUMINANCE_SCALE: f64 = 100.0;
+
+/// The illuminance of the sun above the atmosphere, in the same units.
+const SOLAR_ILLUMINANCE: f64 = 1.28;
+
+/// The wavelengths in µm at which the sunlight of each channel is attenuated.
+const WAVELENGTHS: [f64; 3] = [0.68, 0.55, 0.44];
+
+/// The analytic model of a clear daylight sky by Preetham et al. in "A Practical Analytic Model
+/// for Daylight". `sun` points towards the sun and `turbidity` is how hazy the air is, from 2 for
+/// a very clear sky to 10 for a hazy one. Below the horizon is the ground, which reflects the
+/// light of the sky and the sun with its `ground_albedo`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "PreethamSkyRepr"))]
+pub struct PreethamSky {
+    sun: Vector,
+    turbidity: f64,
+    ground_albedo: Color,
+    /// The coefficients of the Perez distribution for the luminance and the two chromaticities.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    perez: [[f64; 5]; 3],
+    /// The luminance and chromaticities at the zenith, divided by the Perez distribution there.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    zenith: [f64; 3],
+    #[cfg_attr(feature = "serde", serde(skip))]
+    ground: Color,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    mean: Color,
+}
+
+impl PreethamSky {
+    pub fn new(sun: Vector, turbidity: f64, ground_albedo: Color) -> Self {
+        let sun = sun.normalize();
+        let t = turbidity;
+        // The model only holds for the sun above the horizon.
+        let theta_sun = sun.y().clamp(0.0, 1.0).acos();
+        let perez = [
+            [0.1787 * t - 1.4630, -0.3554 * t + 0.4275, -0.0227 * t + 5.3251, 0.1206 * t - 2.5771, -0.0670 * t + 0.3703],
+            [-0.0193 * t - 0.2592, -0.0665 * t + 0.0008, -0.0004 * t + 0.2125, -0.0641 * t - 0.8989, -0.0033 * t + 0.0452],
+            [-0.0167 * t - 0.2608, -0.0950 * t + 0.0092, -0.0079 * t + 0.2102, -0.0441 * t - 1.6537, -0.0109 * t + 0.0529],
+        ];
+
+        let chi = (4.0 / 9.0 - t / 120.0) * (PI - 2.0 * theta_sun);
+        let luminance = ((4.0453 * t - 4.9710) * chi.tan() - 0.2155 * t + 2.4192) / LUMINANCE_SCALE;
+        let chromaticity = |coefficients: [[f64; 4]; 3]| {
+            let [a, b, c] = coefficients.map(|[c3, c2, c1, c0]| ((c3 * theta_sun + c2) * theta_sun + c1) * theta_sun + c0);
+            a * t * t + b * t + c
+        };
+        let x = chromaticity([[0.00166, -0.00375, 0.00209, 0.0], [-0.02903, 0.06377, -0.03202, 0.00394], [0.11693, -0.21196, 0.06052, 0.25886]]);
+        let y = chromaticity([[0.00275, -0.00610, 0.00317, 0.0], [-0.04214, 0.08970, -0.04153, 0.00516], [0.15346, -0.26756, 0.06670, 0.26688]]);
+
+        let mut zenith = [luminance, x, y];
+        for (value, coefficients) in zenith.iter_mut().zip(&perez) {
+            *value /= perez_distribution(coefficients, 0.0, theta_sun);
+        }
+
+        let mut sky = Self { sun, turbidity, ground_albedo, perez, zenith, ground: Color::default(), mean: Color::default() };
+        sky.integrate();
+
+        sky
+    }
+
+    pub fn sun(&self) -> Vector {
+        self.sun
+    }
+
+    pub fn turbidity(&self) -> f64 {
+        self.turbidity
+    }
+
+    pub fn ground_albedo(&self) -> Color {
+        self.ground_albedo
+    }
+
+    /// The radiance arriving from the unit vector `direction`, leaving out the sun itself.
+    pub fn radiance(&self, direction: Vector) -> Color {
+        if direction.y() < 0.0 {
+            return self.ground;
+        }
+        let theta = direction.y().min(1.0).acos();
+        let gamma = direction.dot(self.sun).clamp(-1.0, 1.0).acos();
+        let [luminance, x, y] = [0, 1, 2].map(|i| self.zenith[i] * perez_distribution(&self.perez[i], theta, gamma));
+
+        xyy_to_rgb(x, y, luminance)
+    }
+
+    /// The radiance averaged over all directions.
+    pub fn mean(&self) -> Color {
+        self.mean
+    }
+
+    /// The irradiance of the sunlight reaching the ground through the atmosphere, on a surface
+    /// facing the sun.
+    pub fn sun_irradiance(&self) -> Color {
+        let elevation = FRAC_PI_2 - self.sun.y().clamp(-1.0, 1.0).acos();
+        if elevation <= 0.0 {
+            return Color::default();
+        }
+
+        // The relative optical mass of the air the light goes through, as given by Kasten.
+        let zenith_degrees = 90.0 - elevation.to_degrees();
+        let mass = 1.0 / (elevation.sin() + 0.15 * (93.885 - zenith_degrees).powf(-1.253));
+        let beta = 0.04608 * self.turbidity - 0.04586;
+        let [r, g, b] = WAVELENGTHS.map(|lambda: f64| {
+            let rayleigh = 0.008735 * lambda.powf(-4.08);
+            let aerosols = beta * lambda.powf(-1.3);
+            SOLAR_ILLUMINANCE * (-mass * (rayleigh + aerosols)).exp()
+        });
+
+        Color::new(r, g, b)
+    }
+
+    /// The sun that goes with the sky, as a light shining from its direction.
+    pub fn sun_light(&self) -> DirectionalLight {
+        DirectionalLight::new(-self.sun, self.sun_irradiance())
+    }
+
+    /// Works out the light of the ground and the mean radiance by summing up the sky over a grid
+    /// of directions.
+    fn integrate(&mut self) {
+        let (rows, columns) = (32, 64);
+        let (d_theta, d_phi) = (FRAC_PI_2 / rows as f64, TAU / columns as f64);
+        let mut irradiance = self.sun_irradiance() * self.sun.y().max(0.0);
+        let mut total = Color::default();
+
+        for row in 0..rows {
+            let theta = (row as f64 + 0.5) * d_theta;
+            let (sin_theta, cos_theta) = theta.sin_cos();
+            for column in 0..columns {
+                let (sin_phi, cos_phi) = ((column as f64 + 0.5) * d_phi).sin_cos();
+                let radiance = self.radiance(Vector::new(sin_theta * cos_phi, cos_theta, sin_theta * sin_phi));
+                let solid_angle = sin_theta * d_theta * d_phi;
+                irradiance = irradiance + radiance * (cos_theta * solid_angle);
+                total = total + radiance * solid_angle;
+            }
+        }
+
+        self.ground = self.ground_albedo * irradiance * (1.0 / PI);
+        self.mean = (total + self.ground * TAU) * (1.0 / (2.0 * TAU));
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct PreethamSkyRepr {
+    sun: Vector,
+    turbidity: f64,
+    ground_albedo: Color,
+}
+
+#[cfg(feature = "serde")]
+impl From<PreethamSkyRepr> for PreethamSky {
+    fn from(repr: PreethamSkyRepr) -> Self {
+        PreethamSky::new(repr.sun, repr.turbidity, repr.ground_albedo)
+    }
+}
+
+/// How the sky brightens towards the horizon and around the sun, at an angle `theta` from the
+/// zenith and `gamma` from the sun.
+fn perez_distribution([a, b, c, d, e]: &[f64; 5], theta: f64, gamma: f64) -> f64 {
+    let cos_gamma = gamma.cos();
+
+    (1.0 + a * (b / theta.cos().max(0.01)).exp()) * (1.0 + c * (d * gamma).exp() + e * cos_gamma * cos_gamma)
+}
+
+/// The linear sRGB color with the chromaticity `(x, y)` and the luminance `luminance`.
+fn xyy_to_rgb(x: f64, y: f64, luminance: f64) -> Color {
+    if y <= 0.0 {
+        return Color::default();
+    }
+    let (cx, cy, cz) = (x * luminance / y, luminance, (1.0 - x - y) * luminance / y);
+
+    Color::from_xyz(cx, cy, cz).max(Color::default())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::light::Light;
+
+    use super::*;
+
+    #[test]
+    fn the_sky_is_brightest_around_the_sun() {
+        // Given
+        let sky = PreethamSky::new(Vector::new(0.0, 1.0, 1.0), 3.0, Color::new(0.3, 0.3, 0.3));
+
+        // When
+        let near = sky.radiance(Vector::new(0.0, 0.8, 1.0).normalize());
+        let away = sky.radiance(Vector::new(0.0, 0.8, -1.0).normalize());
+        let zenith = sky.radiance(Vector::new(0.0, 1.0, 0.0));
+
+        // Then
+        assert!(near.g > 2.0 * away.g);
+        assert!(zenith.b > zenith.r, "{:?}", zenith);
+        assert!((zenith.luminance() * LUMINANCE_SCALE / 7.331 - 1.0).abs() < 0.01, "{:?}", zenith);
+    }
+
+    #[test]
+    fn the_ground_reflects_the_light_of_the_sky_and_the_sun() {
+        // Given
+        let bright = PreethamSky::new(Vector::new(0.0, 1.0, 0.0), 2.0, Color::new(0.5, 0.5, 0.5));
+        let dark = PreethamSky::new(Vector::new(0.0, 1.0, 0.0), 2.0, Color::default());
+
+        // Then
+        let ground = bright.radiance(Vector::new(0.0, -1.0, 0.0));
+        assert!(ground.g > 0.5 * bright.sun_irradiance().g / PI);
+        assert_eq!(dark.radiance(Vector::new(1.0, -0.5, 0.0)), Color::default());
+    }
+
+    #[test]
+    fn the_sun_reddens_as_it_sets() {
+        // Given
+        let noon = PreethamSky::new(Vector::new(0.0, 1.0, 0.2), 3.0, Color::default());
+        let evening = PreethamSky::new(Vector::new(0.0, 0.05, 1.0), 3.0, Color::default());
+        let night = PreethamSky::new(Vector::new(0.0, -0.2, 1.0), 3.0, Color::default());
+
+        // When
+        let light = noon.sun_light();
+
+        // Then
+        let (high, low) = (noon.sun_irradiance(), evening.sun_irradiance());
+        assert!(high.g > 0.8 && high.g < SOLAR_ILLUMINANCE);
+        assert!(low.r / low.b > 2.0 * high.r / high.b);
+        assert_eq!(night.sun_irradiance(), Color::default());
+        assert_eq!(light.direction, -noon.sun());
+        assert_eq!(light.intensity(), high);
+    }
+}
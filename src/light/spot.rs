@@ -0,0 +1,182 @@
+use std::f64::consts::TAU;
+
+use crate::bounds::BoundingBox;
+use crate::color::Color;
+#[cfg(feature = "serde")]
+use crate::core::float::ApproxEq;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::light::{Falloff, Light, LightSample};
+use crate::ray::Ray;
+use crate::sampling::Frame;
+
+/// A light at `position` shining a cone of light along `direction`. Points within `inner_angle`
+/// of the axis of the cone receive the full intensity, which smoothly fades out towards
+/// `outer_angle`. Both angles are measured from the axis, in radians.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(try_from = "SpotLightRepr"))]
+pub struct SpotLight {
+    pub position: Point,
+    pub direction: Vector,
+    pub inner_angle: f64,
+    pub outer_angle: f64,
+    pub intensity: Color,
+    pub falloff: Falloff,
+}
+
+impl SpotLight {
+    pub fn new(position: Point, direction: Vector, inner_angle: f64, outer_angle: f64, intensity: Color) -> Self {
+        Self {
+            position,
+            direction: direction.normalize(),
+            inner_angle,
+            outer_angle,
+            intensity,
+            falloff: Falloff::None,
+        }
+    }
+
+    /// The fraction of the intensity that is emitted towards `point`, based on its angle to the
+    /// axis of the cone.
+    pub fn cone_factor(&self, point: Point) -> f64 {
+        let cos_angle = (point - self.position).normalize().dot(self.direction);
+        let (cos_inner, cos_outer) = (self.inner_angle.cos(), self.outer_angle.cos());
+
+        match cos_angle {
+            c if c >= cos_inner => 1.0,
+            c if c <= cos_outer => 0.0,
+            c => {
+                let t = (c - cos_outer) / (cos_inner - cos_outer);
+                t * t * (3.0 - 2.0 * t)
+            }
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct SpotLightRepr {
+    position: Point,
+    direction: Vector,
+    inner_angle: f64,
+    outer_angle: f64,
+    intensity: Color,
+    falloff: Falloff,
+}
+
+#[cfg(feature = "serde")]
+impl TryFrom<SpotLightRepr> for SpotLight {
+    type Error = &'static str;
+
+    fn try_from(repr: SpotLightRepr) -> Result<Self, Self::Error> {
+        if repr.direction.magnitude().approx_eq(0.0) {
+            return Err("a direction can't be zero");
+        }
+        let light = SpotLight::new(repr.position, repr.direction, repr.inner_angle, repr.outer_angle, repr.intensity);
+
+        Ok(SpotLight { falloff: repr.falloff, ..light })
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "spot-light"))]
+impl Light for SpotLight {
+    fn intensity(&self) -> Color {
+        self.intensity
+    }
+
+    fn samples(&self, point: Point) -> Vec<LightSample> {
+        let intensity = self.intensity * self.cone_factor(point);
+
+        vec![LightSample::from_position(point, self.position, intensity, self.falloff)]
+    }
+
+    /// Photons are sent out evenly within the outer cone.
+    fn emit(&self, _bounds: &BoundingBox, [u, v, _, _]: [f64; 4]) -> Option<(Ray, Color)> {
+        let cos_outer = self.outer_angle.cos();
+        let cos = 1.0 - u * (1.0 - cos_outer);
+        let sin = (1.0 - cos * cos).max(0.0).sqrt();
+        let (sin_phi, cos_phi) = (v * TAU).sin_cos();
+        let direction = Frame::new(self.direction).to_world(Vector::new(sin * cos_phi, sin * sin_phi, cos));
+        let factor = self.cone_factor(self.position + direction);
+
+        (factor > 0.0).then(|| (Ray::new(self.position, direction), self.intensity * (factor * TAU * (1.0 - cos_outer))))
+    }
+
+    /// The light within the cone halfway between the inner and the outer one.
+    fn power(&self, _bounds: &BoundingBox) -> Color {
+        self.intensity * (TAU * (1.0 - ((self.inner_angle + self.outer_angle) / 2.0).cos()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use super::*;
+
+    fn spot() -> SpotLight {
+        SpotLight::new(Point::new(0.0, 10.0, 0.0), Vector::new(0.0, -1.0, 0.0), PI / 8.0, PI / 4.0, Color::new(1.0, 1.0, 1.0))
+    }
+
+    #[test]
+    fn spot_light_is_full_intensity_inside_the_inner_cone() {
+        // Given
+        let light = spot();
+
+        // When
+        let samples = light.samples(Point::new(1.0, 0.0, 0.0));
+
+        // Then
+        assert_eq!(samples[0].intensity, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(samples[0].distance, 101f64.sqrt());
+    }
+
+    #[test]
+    fn spot_light_is_dark_outside_the_outer_cone() {
+        // Given
+        let light = spot();
+
+        // Then
+        assert_eq!(light.samples(Point::new(20.0, 0.0, 0.0))[0].intensity, Color::new(0.0, 0.0, 0.0));
+        assert_eq!(light.samples(Point::new(0.0, 20.0, 0.0))[0].intensity, Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn spot_light_fades_between_the_cones() {
+        // Given
+        let light = spot();
+        let halfway = (PI / 8.0 + PI / 4.0) / 2.0;
+
+        // When
+        let factor = light.cone_factor(Point::new(10.0 * halfway.tan(), 0.0, 0.0));
+        let inner = light.cone_factor(Point::new(10.0 * 0.3f64.tan(), 0.0, 0.0));
+        let outer = light.cone_factor(Point::new(10.0 * 0.7f64.tan(), 0.0, 0.0));
+
+        // Then
+        assert!(factor > 0.0 && factor < 1.0);
+        assert!(inner > factor && factor > outer);
+    }
+
+    #[test]
+    fn spot_light_with_inverse_square_falloff() {
+        // Given
+        let mut light = spot();
+        light.intensity = Color::new(100.0, 100.0, 100.0);
+        light.falloff = Falloff::InverseSquare;
+
+        // Then
+        assert_eq!(light.samples(Point::new(0.0, 0.0, 0.0))[0].intensity, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(light.samples(Point::new(0.0, 5.0, 0.0))[0].intensity, Color::new(4.0, 4.0, 4.0));
+    }
+
+    #[test]
+    fn spot_light_keeps_the_ambient_term() {
+        // Given
+        let light = spot();
+
+        // Then
+        assert_eq!(light.intensity(), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(light.samples(Point::zero())[0].direction, Vector::new(0.0, 1.0, 0.0));
+    }
+}
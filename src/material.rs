@@ -0,0 +1,64 @@
+use crate::bsdf::lambert::Lambert;
+use crate::bsdf::Bsdf;
+use crate::bump::Bump;
+use crate::color::Color;
+
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Material {
+    pub color: Color,
+    pub ambient: f64,
+    pub diffuse: f64,
+    pub specular: f64,
+    pub shininess: f64,
+    /// The light given off by the surface itself, as radiance. Glowing shapes light up their
+    /// surroundings when path tracing.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub emission: Color,
+    /// How the surface scatters light when path tracing, which ignores the Phong parameters. A
+    /// Lambertian surface of `color` times `diffuse` if there is none.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bsdf: Option<Bsdf>,
+    /// Detail bent into the normal the surface is shaded with.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub bump: Option<Bump>,
+}
+
+impl Material {
+    pub fn new(color: Color, ambient: f64, diffuse: f64, specular: f64, shininess: f64) -> Self {
+        Self { color, ambient, diffuse, specular, shininess, emission: Color::default(), bsdf: None, bump: None, }
+    }
+
+    /// How the surface scatters light when path tracing: its `bsdf`, or a Lambertian surface of
+    /// `color` times `diffuse`.
+    pub fn scattering(&self) -> Bsdf {
+        self.bsdf.unwrap_or(Bsdf::Lambert(Lambert::new(self.color * self.diffuse)))
+    }
+}
+
+impl Default for Material {
+    fn default() -> Self {
+        Self::new(Color::new(1.0, 1.0, 1.0), 0.1, 0.9, 0.9, 200.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn default_material() {
+        // Given
+        let m = Material::default();
+
+        // Then
+        assert_eq!(m.color, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(m.ambient, 0.1);
+        assert_eq!(m.diffuse, 0.9);
+        assert_eq!(m.specular, 0.9);
+        assert_eq!(m.shininess, 200.0);
+        assert_eq!(m.emission, Color::new(0.0, 0.0, 0.0));
+        assert_eq!(m.bsdf, None);
+        assert_eq!(m.bump, None);
+    }
+}
@@ -0,0 +1,145 @@
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::noise::fbm;
+
+/// How thick a medium is from place to place, as a factor from 0 to its `max` on its
+/// coefficients. Points are in the space of the volume the medium fills.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum Density {
+    /// The same everywhere, which lets light through the medium be worked out exactly.
+    Constant(f64),
+    Noise(NoiseDensity),
+    Grid(DensityGrid),
+}
+
+impl Density {
+    pub fn at(&self, point: Point) -> f64 {
+        match self {
+            Density::Constant(density) => *density,
+            Density::Noise(noise)      => noise.at(point),
+            Density::Grid(grid)        => grid.at(point),
+        }
+    }
+
+    /// The largest density anywhere.
+    pub fn max(&self) -> f64 {
+        match self {
+            Density::Constant(density) => *density,
+            Density::Noise(_)          => 1.0,
+            Density::Grid(grid)        => grid.values.iter().copied().fold(0.0, f64::max),
+        }
+    }
+
+    pub fn is_constant(&self) -> bool {
+        matches!(self, Density::Constant(_))
+    }
+}
+
+impl Default for Density {
+    fn default() -> Self {
+        Density::Constant(1.0)
+    }
+}
+
+/// Patchy density from fractal noise with `octaves` layers and features about `1 / frequency`
+/// apart. Where the noise, from 0 to 1, is below `cutoff` the medium is empty.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct NoiseDensity {
+    pub frequency: f64,
+    pub octaves: usize,
+    pub cutoff: f64,
+}
+
+impl NoiseDensity {
+    pub fn new(frequency: f64, octaves: usize, cutoff: f64) -> Self {
+        Self { frequency, octaves, cutoff, }
+    }
+
+    pub fn at(&self, point: Point) -> f64 {
+        let f = self.frequency;
+        let noise = (fbm(Point::new(point.x() * f, point.y() * f, point.z() * f), self.octaves) + 1.0) / 2.0;
+
+        match self.cutoff < 1.0 {
+            true  => ((noise - self.cutoff) / (1.0 - self.cutoff)).clamp(0.0, 1.0),
+            false => 0.0,
+        }
+    }
+}
+
+/// Density given on a grid of `width` by `height` by `depth` points spread evenly over the cube
+/// from -1 to 1 on every axis, in order of x, then y, then z, and interpolated in between.
+/// Outside of the cube the medium is empty.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DensityGrid {
+    pub width: usize,
+    pub height: usize,
+    pub depth: usize,
+    pub values: Vec<f64>,
+}
+
+impl DensityGrid {
+    /// A grid of the given size, or None if there isn't a value for every point.
+    pub fn new(width: usize, height: usize, depth: usize, values: Vec<f64>) -> Option<Self> {
+        (width > 0 && height > 0 && depth > 0 && values.len() == width * height * depth)
+            .then_some(Self { width, height, depth, values, })
+    }
+
+    pub fn at(&self, point: Point) -> f64 {
+        if [point.x(), point.y(), point.z()].iter().any(|c| c.abs() > 1.0) {
+            return 0.0;
+        }
+        // The position between the points of the grid along each axis.
+        let position = |c: f64, n: usize| {
+            let x = (c + 1.0) / 2.0 * (n - 1) as f64;
+            let i = (x.floor() as usize).min(n.saturating_sub(2));
+            (i, (i + 1).min(n - 1), x - i as f64)
+        };
+        let (x0, x1, tx) = position(point.x(), self.width);
+        let (y0, y1, ty) = position(point.y(), self.height);
+        let (z0, z1, tz) = position(point.z(), self.depth);
+        let value = |x: usize, y: usize, z: usize| self.values[(z * self.height + y) * self.width + x];
+        let lerp = |t: f64, a: f64, b: f64| a + t * (b - a);
+
+        lerp(tz,
+            lerp(ty, lerp(tx, value(x0, y0, z0), value(x1, y0, z0)), lerp(tx, value(x0, y1, z0), value(x1, y1, z0))),
+            lerp(ty, lerp(tx, value(x0, y0, z1), value(x1, y0, z1)), lerp(tx, value(x0, y1, z1), value(x1, y1, z1))))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn grids_are_interpolated_between_their_points() {
+        // Given
+        let grid = DensityGrid::new(2, 2, 2, vec![0.0, 1.0, 0.0, 1.0, 2.0, 3.0, 2.0, 3.0]).unwrap();
+        let density = Density::Grid(grid.clone());
+
+        // Then
+        assert_eq!(grid.at(Point::new(-1.0, -1.0, -1.0)), 0.0);
+        assert_eq!(grid.at(Point::new(1.0, 0.0, 1.0)), 3.0);
+        assert_eq!(grid.at(Point::new(0.0, 0.0, 0.0)), 1.5);
+        assert_eq!(grid.at(Point::new(0.0, 0.0, 1.5)), 0.0);
+        assert_eq!(density.max(), 3.0);
+        assert_eq!(DensityGrid::new(2, 2, 2, vec![1.0]), None);
+    }
+
+    #[test]
+    fn noise_is_cut_off_below_a_threshold() {
+        // Given
+        let full = NoiseDensity::new(2.0, 3, 0.0);
+        let patchy = NoiseDensity::new(2.0, 3, 0.5);
+
+        // When
+        let points = (0..500).map(|i| Point::new(i as f64 * 0.37, i as f64 * 0.11, 0.3)).collect::<Vec<_>>();
+
+        // Then
+        assert!(points.iter().all(|&p| (0.0..=1.0).contains(&full.at(p))));
+        assert!(points.iter().any(|&p| patchy.at(p) == 0.0));
+        assert!(points.iter().any(|&p| patchy.at(p) > 0.0));
+    }
+}
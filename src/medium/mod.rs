@@ -0,0 +1,318 @@
+pub mod density;
+pub mod phase;
+
+use crate::color::Color;
+use crate::core::point::Point;
+use crate::medium::density::Density;
+use crate::medium::phase::HenyeyGreenstein;
+use crate::random::random;
+use crate::ray::Ray;
+use crate::shape::Shape;
+
+/// A participating medium such as fog, smoke or murky water, which absorbs and scatters the light
+/// going through it. `absorption` and `scattering` are the fractions of light absorbed and
+/// scattered per unit of distance where the `density` is 1, and `phase` is the directions the
+/// scattered light goes.
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Medium {
+    pub absorption: Color,
+    pub scattering: Color,
+    pub phase: HenyeyGreenstein,
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub density: Density,
+}
+
+impl Medium {
+    /// A medium of constant density 1.
+    pub fn new(absorption: Color, scattering: Color, g: f64) -> Self {
+        Self { absorption, scattering, phase: HenyeyGreenstein::new(g), density: Density::default(), }
+    }
+
+    pub fn with_density(self, density: Density) -> Self {
+        Self { density, ..self }
+    }
+
+    /// The absorption and scattering coefficients at a point in the space of the medium.
+    pub fn coefficients(&self, point: Point) -> (Color, Color) {
+        let density = self.density.at(point);
+
+        (self.absorption * density, self.scattering * density)
+    }
+
+    /// The largest fraction of light taken out of any channel per unit of distance anywhere.
+    fn majorant(&self) -> f64 {
+        max_channel(self.absorption + self.scattering) * self.density.max()
+    }
+}
+
+/// A medium filling the inside of a closed shape, which is otherwise invisible. The density of
+/// the medium is looked up in the space of the shape.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Volume {
+    pub boundary: Box<dyn Shape>,
+    pub medium: Medium,
+}
+
+impl Volume {
+    pub fn new(boundary: Box<dyn Shape>, medium: Medium) -> Self {
+        Self { boundary, medium, }
+    }
+
+    /// The stretches of `ray` inside the volume, up to `end`.
+    pub fn spans(&self, ray: &Ray, end: f64) -> Vec<Span<'_>> {
+        let mut ts = self.boundary.intersect(ray).iter().map(|i| i.t).collect::<Vec<_>>();
+        ts.sort_by(f64::total_cmp);
+
+        let mut inside = ts.iter().filter(|&&t| t <= 0.0).count() % 2 == 1;
+        let mut start = 0.0;
+        let mut spans = vec![];
+        for t in ts.into_iter().filter(|&t| t > 0.0) {
+            if inside {
+                spans.push(Span::new(&self.medium, Some(self.boundary.as_ref()), start, t.min(end)));
+            }
+            if t >= end {
+                return spans;
+            }
+            inside = !inside;
+            start = t;
+        }
+        if inside {
+            spans.push(Span::new(&self.medium, Some(self.boundary.as_ref()), start, end));
+        }
+
+        spans
+    }
+}
+
+/// A stretch of a ray through a medium, from `start` to `end` along it.
+#[derive(Clone, Copy, Debug)]
+pub struct Span<'a> {
+    pub medium: &'a Medium,
+    /// The volume the medium fills, if it isn't everywhere.
+    pub volume: Option<&'a dyn Shape>,
+    pub start: f64,
+    pub end: f64,
+}
+
+impl<'a> Span<'a> {
+    pub fn new(medium: &'a Medium, volume: Option<&'a dyn Shape>, start: f64, end: f64) -> Self {
+        Self { medium, volume, start, end, }
+    }
+
+    /// The absorption and scattering coefficients at a point of the world.
+    pub fn coefficients(&self, point: Point) -> (Color, Color) {
+        match self.volume {
+            Some(volume) => self.medium.coefficients(volume.world_to_object(point)),
+            None         => self.medium.coefficients(point),
+        }
+    }
+
+    fn contains(&self, t: f64) -> bool {
+        (self.start..self.end).contains(&t)
+    }
+}
+
+/// What happens to light going along a ray through media.
+#[derive(Clone, Copy, Debug)]
+pub enum Interaction<'a> {
+    /// It makes it through to the end of the ray.
+    Passed,
+    /// It's absorbed at a distance along the ray.
+    Absorbed(f64),
+    /// It's scattered by a medium at a distance along the ray.
+    Scattered(f64, &'a Medium),
+}
+
+/// Finds where along `ray` up to `end` light going through `spans` is first absorbed or
+/// scattered, if at all, by delta tracking, together with the weight of what was sampled. Media
+/// that absorb and scatter all colors alike always have a weight of 1.
+pub fn track<'a>(spans: &[Span<'a>], ray: &Ray, end: f64) -> (Interaction<'a>, Color) {
+    let speed = ray.direction.magnitude();
+    let mut weight = Color::new(1.0, 1.0, 1.0);
+
+    for (start, stop, active) in pieces(spans, end) {
+        let majorant = active.iter().map(|span| span.medium.majorant()).sum::<f64>();
+        if majorant == 0.0 {
+            continue;
+        }
+
+        let mut t = start;
+        loop {
+            t -= (1.0 - random::<f64>()).ln() / (majorant * speed);
+            if t >= stop {
+                break;
+            }
+
+            let point = ray.position(t);
+            let mut absorption = Color::default();
+            let mut scattering = Color::default();
+            // One of the media scattering at the point, picked in proportion to how much.
+            let mut scatterer = None;
+            let mut picked = 0.0;
+            for span in &active {
+                let (a, s) = span.coefficients(point);
+                absorption = absorption + a;
+                scattering = scattering + s;
+                picked += average(s);
+                if picked > 0.0 && random::<f64>() * picked < average(s) {
+                    scatterer = Some(span.medium);
+                }
+            }
+
+            let p_absorbed = average(absorption) / majorant;
+            let p_scattered = average(scattering) / majorant;
+            let u = random::<f64>();
+            if u < p_absorbed {
+                return (Interaction::Absorbed(t), Color::default());
+            }
+            if let Some(medium) = scatterer.filter(|_| u < p_absorbed + p_scattered) {
+                return (Interaction::Scattered(t, medium), weight * scattering * (1.0 / (majorant * p_scattered)));
+            }
+            let p_null = 1.0 - p_absorbed - p_scattered;
+            if p_null <= 0.0 {
+                return (Interaction::Absorbed(t), Color::default());
+            }
+            weight = weight * null(majorant, absorption + scattering) * (1.0 / (majorant * p_null));
+        }
+    }
+
+    (Interaction::Passed, weight)
+}
+
+/// The fraction of light that makes it along `ray` up to `end` through `spans`. It's exact
+/// through media of constant density, and estimated by ratio tracking through others.
+pub fn transmittance(spans: &[Span], ray: &Ray, end: f64) -> Color {
+    let speed = ray.direction.magnitude();
+    let mut transmittance = Color::new(1.0, 1.0, 1.0);
+
+    for (start, stop, active) in pieces(spans, end) {
+        if active.iter().all(|span| span.medium.density.is_constant()) {
+            let extinction = active.iter()
+                .map(|span| {
+                    let (a, s) = span.coefficients(ray.position(start));
+                    a + s
+                })
+                .sum::<Color>();
+            transmittance = transmittance * exp(extinction * -((stop - start) * speed));
+            continue;
+        }
+
+        let majorant = active.iter().map(|span| span.medium.majorant()).sum::<f64>();
+        let mut t = start;
+        loop {
+            t -= (1.0 - random::<f64>()).ln() / (majorant * speed);
+            if t >= stop || max_channel(transmittance) == 0.0 {
+                break;
+            }
+            let point = ray.position(t);
+            let extinction = active.iter()
+                .map(|span| {
+                    let (a, s) = span.coefficients(point);
+                    a + s
+                })
+                .sum::<Color>();
+            transmittance = transmittance * null(majorant, extinction) * (1.0 / majorant);
+        }
+    }
+
+    transmittance
+}
+
+/// Splits the ray up to `end` where spans start and stop, into pieces with the spans covering
+/// each of them.
+pub(crate) fn pieces<'s, 'a>(spans: &'s [Span<'a>], end: f64) -> Vec<(f64, f64, Vec<&'s Span<'a>>)> {
+    let mut cuts = spans.iter()
+        .flat_map(|span| [span.start, span.end])
+        .filter(|&t| t > 0.0 && t < end)
+        .collect::<Vec<_>>();
+    cuts.push(0.0);
+    cuts.push(end);
+    cuts.sort_by(f64::total_cmp);
+    cuts.dedup();
+
+    cuts.windows(2)
+        .filter_map(|w| {
+            let (start, stop) = (w[0], w[1]);
+            let middle = match stop.is_finite() {
+                true  => (start + stop) / 2.0,
+                false => start + 1.0,
+            };
+            let active = spans.iter().filter(|span| span.contains(middle)).collect::<Vec<_>>();
+            (!active.is_empty()).then_some((start, stop, active))
+        })
+        .collect()
+}
+
+/// The part of `majorant` in every channel that isn't taken out by `extinction`.
+fn null(majorant: f64, extinction: Color) -> Color {
+    Color::new((majorant - extinction.r).max(0.0), (majorant - extinction.g).max(0.0), (majorant - extinction.b).max(0.0))
+}
+
+fn exp(c: Color) -> Color {
+    Color::new(c.r.exp(), c.g.exp(), c.b.exp())
+}
+
+fn average(c: Color) -> f64 {
+    (c.r + c.g + c.b) / 3.0
+}
+
+fn max_channel(c: Color) -> f64 {
+    c.r.max(c.g).max(c.b)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::matrix::Matrix4;
+    use crate::core::tuple::Tuple;
+    use crate::core::vector::Vector;
+    use crate::medium::density::NoiseDensity;
+    use crate::random;
+    use crate::shape::sphere::Sphere;
+
+    use super::*;
+
+    #[test]
+    fn volumes_are_crossed_where_the_ray_is_inside() {
+        // Given
+        let mut sphere = Sphere::new();
+        sphere.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        let volume = Volume::new(Box::new(sphere), Medium::new(Color::default(), Color::new(1.0, 1.0, 1.0), 0.0));
+        let outside = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let inside = Ray::new(Point::new(0.0, 0.0, 1.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let through = volume.spans(&outside, f64::INFINITY);
+        let blocked = volume.spans(&outside, 4.0);
+        let out = volume.spans(&inside, f64::INFINITY);
+
+        // Then
+        assert_eq!(through.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>(), vec![(3.0, 7.0)]);
+        assert_eq!(blocked.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>(), vec![(3.0, 4.0)]);
+        assert_eq!(out.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>(), vec![(0.0, 1.0)]);
+    }
+
+    #[test]
+    fn light_through_a_medium_falls_off_exponentially() {
+        // Given
+        let fog = Medium::new(Color::new(0.1, 0.2, 0.3), Color::new(0.4, 0.4, 0.4), 0.5);
+        let smoke = fog.clone().with_density(Density::Noise(NoiseDensity::new(1.0, 2, 0.0)));
+        let spans = [Span::new(&fog, None, 0.0, 2.0)];
+        let ray = Ray::new(Point::zero(), Vector::new(0.0, 0.0, 0.5));
+        random::seed(5);
+
+        // When
+        let exact = transmittance(&spans, &ray, 3.0);
+        let n = 20000;
+        let passed = (0..n).filter(|_| matches!(track(&spans, &ray, 3.0).0, Interaction::Passed)).count();
+        let smoky = [Span::new(&smoke, None, 0.0, 2.0)];
+        let estimate = (0..n).map(|_| transmittance(&smoky, &ray, 3.0).g).sum::<f64>() / n as f64;
+        let tracked = (0..n).filter(|_| matches!(track(&smoky, &ray, 3.0).0, Interaction::Passed)).count();
+
+        // Then: the ray travels 1 unit of distance through the fog.
+        assert_eq!(exact, Color::new((-0.5f64).exp(), (-0.6f64).exp(), (-0.7f64).exp()));
+        assert!((passed as f64 / n as f64 - (-0.6f64).exp()).abs() < 0.01);
+        assert!((tracked as f64 / n as f64 - estimate).abs() < 0.01, "{} against {}", tracked, estimate);
+    }
+}
@@ -0,0 +1,73 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::sampling::Frame;
+
+/// The phase function of Henyey and Greenstein, which says in which directions a medium scatters
+/// light. The asymmetry `g` goes from -1, where all light is scattered back, through 0, where it
+/// is scattered evenly in all directions, to 1, where it carries on straight ahead.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct HenyeyGreenstein {
+    pub g: f64,
+}
+
+impl HenyeyGreenstein {
+    pub fn new(g: f64) -> Self {
+        Self { g: g.clamp(-0.99, 0.99) }
+    }
+
+    /// The density of light travelling along a unit vector being scattered along another, given
+    /// the cosine of the angle between them.
+    pub fn evaluate(&self, cos_theta: f64) -> f64 {
+        let g = self.g;
+        let denominator = 1.0 + g * g - 2.0 * g * cos_theta;
+
+        (1.0 - g * g) / (4.0 * PI * denominator * denominator.sqrt())
+    }
+
+    /// A direction for light travelling along the unit vector `direction` to be scattered along,
+    /// picked by two numbers in `0..1` with the density `evaluate`.
+    pub fn sample(&self, direction: Vector, u: f64, v: f64) -> Vector {
+        let g = self.g;
+        let cos_theta = match g.abs() < 1e-3 {
+            true  => 1.0 - 2.0 * u,
+            false => {
+                let s = (1.0 - g * g) / (1.0 - g + 2.0 * g * u);
+                (1.0 + g * g - s * s) / (2.0 * g)
+            }
+        };
+        let sin_theta = (1.0 - cos_theta * cos_theta).max(0.0).sqrt();
+        let (sin_phi, cos_phi) = (v * TAU).sin_cos();
+
+        Frame::new(direction).to_world(Vector::new(sin_theta * cos_phi, sin_theta * sin_phi, cos_theta))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::float::ApproxEq;
+
+    use super::*;
+
+    #[test]
+    fn the_phase_function_is_a_density_over_the_sphere() {
+        for g in [-0.7, 0.0, 0.3, 0.9] {
+            // Given
+            let phase = HenyeyGreenstein::new(g);
+            let direction = Vector::new(0.0, 0.6, 0.8);
+
+            // When: the mean cosine of the sampled directions, and the integral of the density.
+            let n = 400;
+            let samples = (0..n * n).map(|i| phase.sample(direction, ((i / n) as f64 + 0.5) / n as f64, ((i % n) as f64 + 0.5) / n as f64));
+            let mean_cos = samples.map(|d| d.dot(direction)).sum::<f64>() / (n * n) as f64;
+            let steps = 100 * n;
+            let integral = (0..steps).map(|i| phase.evaluate(-1.0 + (2.0 * i as f64 + 1.0) / steps as f64) * 2.0 / steps as f64).sum::<f64>() * TAU;
+
+            // Then
+            assert!((mean_cos - g).abs() < 1e-3, "{} against {}", mean_cos, g);
+            assert!(integral.approx_eq_low_precision(1.0), "{}", integral);
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+
+/// Gradient noise as described by Perlin in "Improving Noise": a smooth function of space between
+/// about -1 and 1, with features about 1 apart, which is 0 at every whole-numbered point.
+pub fn perlin(p: Point) -> f64 {
+    let (xi, yi, zi) = (p.x().floor(), p.y().floor(), p.z().floor());
+    let (x, y, z) = (p.x() - xi, p.y() - yi, p.z() - zi);
+    let (u, v, w) = (fade(x), fade(y), fade(z));
+    let corner = |dx: f64, dy: f64, dz: f64| {
+        let h = hash(xi as i64 + dx as i64, yi as i64 + dy as i64, zi as i64 + dz as i64);
+        gradient(h, x - dx, y - dy, z - dz)
+    };
+
+    lerp(w,
+        lerp(v,
+            lerp(u, corner(0.0, 0.0, 0.0), corner(1.0, 0.0, 0.0)),
+            lerp(u, corner(0.0, 1.0, 0.0), corner(1.0, 1.0, 0.0))),
+        lerp(v,
+            lerp(u, corner(0.0, 0.0, 1.0), corner(1.0, 0.0, 1.0)),
+            lerp(u, corner(0.0, 1.0, 1.0), corner(1.0, 1.0, 1.0))))
+}
+
+/// Fractal noise made of `octaves` layers of Perlin noise, each with features half the size and
+/// half the strength of the one before, scaled back to between about -1 and 1.
+pub fn fbm(p: Point, octaves: usize) -> f64 {
+    let mut sum = 0.0;
+    let mut total = 0.0;
+    let (mut frequency, mut amplitude) = (1.0, 1.0);
+
+    for _ in 0..octaves.max(1) {
+        sum += amplitude * perlin(Point::new(p.x() * frequency, p.y() * frequency, p.z() * frequency));
+        total += amplitude;
+        frequency *= 2.0;
+        amplitude *= 0.5;
+    }
+
+    sum / total
+}
+
+fn fade(t: f64) -> f64 {
+    t * t * t * (t * (t * 6.0 - 15.0) + 10.0)
+}
+
+fn lerp(t: f64, a: f64, b: f64) -> f64 {
+    a + t * (b - a)
+}
+
+/// The dot product of the offset `(x, y, z)` with one of twelve gradients towards the edges of a
+/// cube, picked by `hash`.
+fn gradient(hash: u64, x: f64, y: f64, z: f64) -> f64 {
+    match hash % 12 {
+        0  => x + y,
+        1  => -x + y,
+        2  => x - y,
+        3  => -x - y,
+        4  => x + z,
+        5  => -x + z,
+        6  => x - z,
+        7  => -x - z,
+        8  => y + z,
+        9  => -y + z,
+        10 => y - z,
+        _  => -y - z,
+    }
+}
+
+/// Scrambles the coordinates of a corner of the lattice, in place of Perlin's permutation table.
+fn hash(x: i64, y: i64, z: i64) -> u64 {
+    let mut h = (x as u64).wrapping_mul(0x8da6_b343) ^ (y as u64).wrapping_mul(0xd816_3841) ^ (z as u64).wrapping_mul(0xcb1a_b31f);
+    h ^= h >> 29;
+    h = h.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+
+    h ^ (h >> 32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn noise_is_smooth_and_bounded() {
+        // Given
+        let points = (0..1000).map(|i| {
+            let i = i as f64;
+            Point::new(i * 0.173, i * 0.0517 - 3.0, i * 0.311 + 1.0)
+        });
+
+        for p in points {
+            // When
+            let n = perlin(p);
+            let nearby = perlin(Point::new(p.x() + 1e-4, p.y(), p.z()));
+
+            // Then
+            assert!(n.abs() <= 1.1, "{}", n);
+            assert!((n - nearby).abs() < 1e-3);
+            assert!(fbm(p, 4).abs() <= 1.1);
+        }
+        assert_eq!(perlin(Point::new(3.0, -2.0, 7.0)), 0.0);
+        assert_ne!(perlin(Point::new(0.5, 0.5, 0.5)), perlin(Point::new(1.5, 0.5, 0.5)));
+    }
+}
@@ -0,0 +1,253 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::shape::triangle::Triangle;
+use crate::shape::Shape;
+
+/// The geometry found in a Wavefront OBJ file. Polygons are split into triangles as a fan around
+/// their first vertex, with the texture coordinates of their corners if all of them have some.
+/// Lines that are not understood are skipped and counted in `ignored`.
+#[derive(Debug, Default)]
+pub struct ObjFile {
+    pub vertices: Vec<Point>,
+    pub uvs: Vec<(f64, f64)>,
+    pub triangles: Vec<Triangle>,
+    pub ignored: usize,
+}
+
+impl ObjFile {
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self::parse(&fs::read_to_string(path)?))
+    }
+
+    pub fn parse(input: &str) -> Self {
+        let mut obj = Self::default();
+
+        for line in input.lines() {
+            let mut parts = line.split_whitespace();
+            let parsed = match parts.next() {
+                Some("v")  => obj.parse_vertex(parts),
+                Some("vt") => obj.parse_uv(parts),
+                Some("f")  => obj.parse_face(parts),
+                _          => None,
+            };
+
+            if parsed.is_none() {
+                obj.ignored += 1;
+            }
+        }
+
+        obj
+    }
+
+    pub fn into_shapes(self) -> Vec<Box<dyn Shape>> {
+        self.triangles.into_iter()
+            .map(|t| Box::new(t) as Box<dyn Shape>)
+            .collect()
+    }
+
+    fn parse_vertex<'a>(&mut self, parts: impl Iterator<Item = &'a str>) -> Option<()> {
+        let coords = parts.map(|p| p.parse::<f64>().ok()).collect::<Option<Vec<_>>>()?;
+        match coords[..] {
+            [x, y, z] | [x, y, z, _] => self.vertices.push(Point::new(x, y, z)),
+            _                        => return None,
+        }
+
+        Some(())
+    }
+
+    /// Texture coordinates, of which a third one for 3D textures is skipped.
+    fn parse_uv<'a>(&mut self, parts: impl Iterator<Item = &'a str>) -> Option<()> {
+        let coords = parts.map(|p| p.parse::<f64>().ok()).collect::<Option<Vec<_>>>()?;
+        match coords[..] {
+            [u]                => self.uvs.push((u, 0.0)),
+            [u, v] | [u, v, _] => self.uvs.push((u, v)),
+            _                  => return None,
+        }
+
+        Some(())
+    }
+
+    fn parse_face<'a>(&mut self, parts: impl Iterator<Item = &'a str>) -> Option<()> {
+        let corners = parts
+            .map(|p| {
+                let mut indices = p.split('/');
+                let vertex = lookup(&self.vertices, indices.next()?)?;
+                Some((vertex, indices.next().and_then(|i| lookup(&self.uvs, i))))
+            })
+            .collect::<Option<Vec<_>>>()?;
+
+        if corners.len() < 3 {
+            return None;
+        }
+
+        for i in 1..corners.len() - 1 {
+            let [(p1, uv1), (p2, uv2), (p3, uv3)] = [corners[0], corners[i], corners[i + 1]];
+            let triangle = Triangle::new(p1, p2, p3);
+            self.triangles.push(match (uv1, uv2, uv3) {
+                (Some(uv1), Some(uv2), Some(uv3)) => triangle.with_uvs([uv1, uv2, uv3]),
+                _                                 => triangle,
+            });
+        }
+
+        Some(())
+    }
+}
+
+/// Looks up a vertex or texture coordinates by their 1-based index, where negative indices count
+/// back from the most recently defined ones.
+fn lookup<T: Copy>(items: &[T], index: &str) -> Option<T> {
+    let index = index.parse::<i64>().ok()?;
+    let resolved = match index {
+        i if i > 0 => i - 1,
+        i if i < 0 => items.len() as i64 + i,
+        _          => return None,
+    };
+
+    usize::try_from(resolved).ok().and_then(|i| items.get(i)).copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ignoring_unrecognized_lines() {
+        // Given
+        let gibberish = "There was a young lady named Bright\n\
+                         who traveled much faster than light.\n\
+                         She set out one day\n\
+                         in a relative way,\n\
+                         and came back the previous night.";
+
+        // When
+        let obj = ObjFile::parse(gibberish);
+
+        // Then
+        assert_eq!(obj.ignored, 5);
+    }
+
+    #[test]
+    fn parsing_vertex_records() {
+        // Given
+        let file = "v -1 1 0\n\
+                    v -1.0000 0.5000 0.0000\n\
+                    v 1 0 0\n\
+                    v 1 1 0";
+
+        // When
+        let obj = ObjFile::parse(file);
+
+        // Then
+        assert_eq!(obj.vertices, vec![
+            Point::new(-1.0, 1.0, 0.0),
+            Point::new(-1.0, 0.5, 0.0),
+            Point::new(1.0, 0.0, 0.0),
+            Point::new(1.0, 1.0, 0.0),
+        ]);
+    }
+
+    #[test]
+    fn parsing_triangle_faces() {
+        // Given
+        let file = "v -1 1 0\n\
+                    v -1 0 0\n\
+                    v 1 0 0\n\
+                    v 1 1 0\n\
+                    \n\
+                    f 1 2 3\n\
+                    f 1 3 4";
+
+        // When
+        let obj = ObjFile::parse(file);
+
+        // Then
+        assert_eq!(obj.triangles.len(), 2);
+        assert_eq!(obj.triangles[0].p1, obj.vertices[0]);
+        assert_eq!(obj.triangles[0].p2, obj.vertices[1]);
+        assert_eq!(obj.triangles[0].p3, obj.vertices[2]);
+        assert_eq!(obj.triangles[1].p1, obj.vertices[0]);
+        assert_eq!(obj.triangles[1].p2, obj.vertices[2]);
+        assert_eq!(obj.triangles[1].p3, obj.vertices[3]);
+    }
+
+    #[test]
+    fn triangulating_polygons() {
+        // Given
+        let file = "v -1 1 0\n\
+                    v -1 0 0\n\
+                    v 1 0 0\n\
+                    v 1 1 0\n\
+                    v 0 2 0\n\
+                    \n\
+                    f 1 2 3 4 5";
+
+        // When
+        let obj = ObjFile::parse(file);
+
+        // Then
+        assert_eq!(obj.triangles.len(), 3);
+        assert_eq!(obj.triangles[2].p1, obj.vertices[0]);
+        assert_eq!(obj.triangles[2].p2, obj.vertices[3]);
+        assert_eq!(obj.triangles[2].p3, obj.vertices[4]);
+    }
+
+    #[test]
+    fn faces_with_texture_and_normal_indices_and_relative_vertices() {
+        // Given
+        let file = "v 0 1 0\n\
+                    v -1 0 0\n\
+                    v 1 0 0\n\
+                    f 1/1/1 2/2/2 3/3/3\n\
+                    f -3//1 -2//1 -1//1";
+
+        // When
+        let obj = ObjFile::parse(file);
+
+        // Then
+        assert_eq!(obj.ignored, 0);
+        assert_eq!(obj.triangles.len(), 2);
+        assert_eq!(obj.triangles[1].p1, obj.vertices[0]);
+        assert_eq!(obj.triangles[1].p3, obj.vertices[2]);
+    }
+
+    #[test]
+    fn faces_referring_to_missing_vertices_are_ignored() {
+        // Given
+        let file = "v 0 1 0\n\
+                    v -1 0 0\n\
+                    f 1 2 3";
+
+        // When
+        let obj = ObjFile::parse(file);
+
+        // Then
+        assert!(obj.triangles.is_empty());
+        assert_eq!(obj.ignored, 1);
+    }
+
+    #[test]
+    fn faces_with_texture_coordinates() {
+        // Given
+        let file = "v 0 1 0\n\
+                    v -1 0 0\n\
+                    v 1 0 0\n\
+                    vt 0.5 1\n\
+                    vt 0 0 0\n\
+                    vt 1 0\n\
+                    f 1/1 2/2 3/3\n\
+                    f 1/1 2 3/3";
+
+        // When
+        let obj = ObjFile::parse(file);
+
+        // Then
+        assert_eq!(obj.ignored, 0);
+        assert_eq!(obj.uvs, vec![(0.5, 1.0), (0.0, 0.0), (1.0, 0.0)]);
+        assert_eq!(obj.triangles[0].uvs, Some([(0.5, 1.0), (0.0, 0.0), (1.0, 0.0)]));
+        assert_eq!(obj.triangles[1].uvs, None);
+    }
+}
@@ -0,0 +1,69 @@
+use std::cell::RefCell;
+
+use rand::distributions::{Distribution, Standard};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+thread_local! {
+    static RNG: RefCell<StdRng> = RefCell::new(StdRng::from_entropy());
+}
+
+/// A random value from the generator of the current thread, which is used for everything that is
+/// sampled while rendering: a float is in `0.0..1.0`.
+pub fn random<T>() -> T
+where
+    Standard: Distribution<T>,
+{
+    RNG.with(|rng| rng.borrow_mut().gen())
+}
+
+/// Restarts the generator of the current thread from `seed`, so that whatever is sampled next
+/// comes out the same every time.
+pub fn seed(seed: u64) {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::seed_from_u64(seed));
+}
+
+/// The seed of the `index`th of many streams of random numbers sharing one `seed`, which is the
+/// `index`th number SplitMix64 generates from `seed`. Unlike the hashers of the standard library,
+/// it is the same with every version of Rust.
+pub fn stream_seed(seed: u64, index: u64) -> u64 {
+    let mut z = seed.wrapping_add(index.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+
+    z ^ (z >> 31)
+}
+
+/// Restarts the generator of the current thread from the operating system's entropy.
+pub fn reseed() {
+    RNG.with(|rng| *rng.borrow_mut() = StdRng::from_entropy());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seeding_repeats_the_same_values() {
+        // Given
+        seed(42);
+        let first = (0..4).map(|_| random::<f64>()).collect::<Vec<_>>();
+
+        // When
+        seed(42);
+        let second = (0..4).map(|_| random::<f64>()).collect::<Vec<_>>();
+
+        // Then
+        assert_eq!(first, second);
+        assert!(first.iter().all(|x| (0.0..1.0).contains(x)));
+        assert_ne!(first[0], first[1]);
+    }
+
+    #[test]
+    fn stream_seeds_are_the_numbers_of_splitmix64() {
+        // Then
+        assert_eq!(stream_seed(1234567, 0), 6457827717110365317);
+        assert_eq!(stream_seed(1234567, 1), 3203168211198807973);
+        assert_eq!(stream_seed(1234567, 2), 9817491932198370423);
+    }
+}
@@ -0,0 +1,90 @@
+use crate::core::matrix::Matrix4;
+use crate::core::point::Point;
+use crate::core::vector::Vector;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ray {
+    pub origin: Point,
+    pub direction: Vector,
+    /// When the ray is cast, from 0.0 when the shutter opens to 1.0 when it closes. Moving shapes
+    /// are intersected where they are at that time.
+    pub time: f64,
+}
+
+impl Ray {
+    pub fn new(origin: Point, direction: Vector) -> Self {
+        Self { origin, direction, time: 0.0, }
+    }
+
+    pub fn with_time(self, time: f64) -> Self {
+        Self { time, ..self }
+    }
+
+    pub fn position(&self, t: f64) -> Point {
+        self.origin + self.direction * t
+    }
+
+    pub fn transform(&self, m: Matrix4) -> Self {
+        Self::new(m * self.origin, m * self.direction).with_time(self.time)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::tuple::Tuple;
+
+    use super::*;
+
+    #[test]
+    fn creating_and_querying_a_ray() {
+        // Given
+        let origin = Point::new(1.0, 2.0, 3.0);
+        let direction = Vector::new(4.0, 5.0, 6.0);
+
+        // When
+        let r = Ray::new(origin, direction);
+
+        // Then
+        assert_eq!(r.origin, origin);
+        assert_eq!(r.direction, direction);
+    }
+
+    #[test]
+    fn computing_a_point_from_a_distance() {
+        // Given
+        let r = Ray::new(Point::new(2.0, 3.0, 4.0), Vector::new(1.0, 0.0, 0.0));
+
+        // Then
+        assert_eq!(r.position(0.0), Point::new(2.0, 3.0, 4.0));
+        assert_eq!(r.position(1.0), Point::new(3.0, 3.0, 4.0));
+        assert_eq!(r.position(-1.0), Point::new(1.0, 3.0, 4.0));
+        assert_eq!(r.position(2.5), Point::new(4.5, 3.0, 4.0));
+    }
+
+    #[test]
+    fn translating_a_ray() {
+        // Given
+        let r = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0));
+
+        // When
+        let r2 = r.with_time(0.5).transform(Matrix4::translation(3.0, 4.0, 5.0));
+
+        // Then
+        assert_eq!(r2.origin, Point::new(4.0, 6.0, 8.0));
+        assert_eq!(r2.direction, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(r2.time, 0.5);
+    }
+
+    #[test]
+    fn scaling_a_ray() {
+        // Given
+        let r = Ray::new(Point::new(1.0, 2.0, 3.0), Vector::new(0.0, 1.0, 0.0));
+
+        // When
+        let r2 = r.transform(Matrix4::scaling(2.0, 3.0, 4.0));
+
+        // Then
+        assert_eq!(r2.origin, Point::new(2.0, 6.0, 12.0));
+        assert_eq!(r2.direction, Vector::new(0.0, 3.0, 0.0));
+    }
+}
@@ -0,0 +1,161 @@
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+
+use crate::camera::Camera;
+use crate::canvas::Canvas;
+use crate::color::Color;
+use crate::integrator::Integrator;
+use crate::random::{self, random};
+use crate::ray::Ray;
+use crate::world::World;
+
+/// Options that trade rendering time for image quality.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RenderSettings {
+    /// Rays traced per pixel. A single sample goes through the center of the pixel, more
+    /// samples are spread randomly over it and averaged.
+    pub samples: usize,
+    /// Threads rendering rows of the image in parallel.
+    pub threads: usize,
+    /// How the light along each ray is worked out.
+    pub integrator: Integrator,
+    /// Seeds the random numbers of every row, so that the same image comes out each time whatever
+    /// the number of threads. Without one the noise differs between renders.
+    pub seed: Option<u64>,
+}
+
+impl Default for RenderSettings {
+    fn default() -> Self {
+        Self { samples: 1, threads: 1, integrator: Integrator::default(), seed: None, }
+    }
+}
+
+/// Renders `world` as seen by `camera`.
+pub fn render(camera: &dyn Camera, world: &World, settings: &RenderSettings) -> Canvas {
+    let mut image = Canvas::new(camera.hsize(), camera.vsize());
+    let next_row = AtomicUsize::new(0);
+
+    let rows = thread::scope(|s| {
+        let workers = (0..settings.threads.clamp(1, camera.vsize().max(1)))
+            .map(|_| s.spawn(|| {
+                let mut rows = vec![];
+                loop {
+                    let y = next_row.fetch_add(1, Ordering::Relaxed);
+                    if y >= camera.vsize() {
+                        return rows;
+                    }
+                    rows.push((y, render_row(camera, world, settings, y)));
+                }
+            }))
+            .collect::<Vec<_>>();
+
+        workers.into_iter()
+            .flat_map(|w| w.join().unwrap())
+            .collect::<Vec<_>>()
+    });
+
+    for (y, row) in rows {
+        for (x, color) in row.into_iter().enumerate() {
+            image[(x, y)] = color;
+        }
+    }
+
+    image
+}
+
+fn render_row(camera: &dyn Camera, world: &World, settings: &RenderSettings, y: usize) -> Vec<Color> {
+    if let Some(seed) = settings.seed {
+        random::seed(random::stream_seed(seed, y as u64));
+    }
+    let color_of = |ray: Option<Ray>| ray.map_or(Color::default(), |ray| settings.integrator.radiance(world, &ray));
+
+    (0..camera.hsize())
+        .map(|x| match settings.samples {
+            0 | 1 => color_of(camera.ray_for_pixel(x, y)),
+            n     => (0..n)
+                .map(|_| color_of(camera.ray_through(x as f64 + random::<f64>(), y as f64 + random::<f64>())))
+                .sum::<Color>() * (1.0 / n as f64),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+
+    use crate::camera::perspective::PerspectiveCamera;
+    use crate::core::matrix::Matrix4;
+    use crate::core::point::Point;
+    use crate::core::tuple::Tuple;
+    use crate::core::vector::Vector;
+    use crate::integrator::path::PathTracer;
+    use crate::light::point::PointLight;
+    use crate::material::Material;
+    use crate::shape::sphere::Sphere;
+    use crate::shape::Shape;
+    use crate::world::tests::default_world;
+
+    use super::*;
+
+    #[test]
+    fn rendering_with_several_threads_gives_the_same_image() {
+        // Given
+        let w = default_world();
+        let mut c = PerspectiveCamera::new(21, 17, PI / 2.0);
+        c.set_transform(Matrix4::view_transform(Point::new(0.0, 0.0, -5.0), Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)));
+
+        // When
+        let single = render(&c, &w, &RenderSettings::default());
+        let threaded = render(&c, &w, &RenderSettings { threads: 4, ..RenderSettings::default() });
+
+        // Then
+        for y in 0..17 {
+            for x in 0..21 {
+                assert_eq!(single[(x, y)], threaded[(x, y)]);
+            }
+        }
+    }
+
+    #[test]
+    fn multiple_samples_of_a_uniformly_colored_pixel() {
+        // Given
+        let mut s = Sphere::new();
+        s.set_material(Material { ambient: 1.0, diffuse: 0.0, specular: 0.0, ..Material::default() });
+        let mut w = World::new();
+        w.add_object(Box::new(s));
+        w.lights.push(Box::new(PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0))));
+        let mut c = PerspectiveCamera::new(11, 11, PI / 2.0);
+        c.set_transform(Matrix4::view_transform(Point::new(0.0, 0.0, -5.0), Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)));
+
+        // When
+        let image = render(&c, &w, &RenderSettings { samples: 16, threads: 2, ..RenderSettings::default() });
+
+        // Then
+        assert_eq!(image[(5, 5)], Color::new(1.0, 1.0, 1.0));
+        assert_eq!(image[(0, 0)], Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn seeded_renders_repeat_whatever_the_threads() {
+        // Given
+        let w = default_world();
+        let mut c = PerspectiveCamera::new(16, 12, PI / 2.0);
+        c.set_transform(Matrix4::view_transform(Point::new(0.0, 0.0, -5.0), Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)));
+        let settings = RenderSettings {
+            samples: 4,
+            integrator: Integrator::Path(PathTracer::default()),
+            seed: Some(42),
+            ..RenderSettings::default()
+        };
+
+        // When
+        let first = render(&c, &w, &settings);
+        let second = render(&c, &w, &RenderSettings { threads: 3, ..settings });
+        let reseeded = render(&c, &w, &RenderSettings { seed: Some(43), ..settings });
+
+        // Then
+        assert!((0..12).all(|y| (0..16).all(|x| first[(x, y)] == second[(x, y)])));
+        assert!((0..12).any(|y| (0..16).any(|x| first[(x, y)] != reseeded[(x, y)])));
+        assert_ne!(first[(8, 6)], Color::default());
+    }
+}
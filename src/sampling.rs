@@ -0,0 +1,241 @@
+use std::f64::consts::{FRAC_1_PI, PI, TAU};
+
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+
+/// An orthonormal basis with the surface normal as its z axis, in which shading is done: there the
+/// cosine of the angle between a direction and the normal is just its z coordinate.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Frame {
+    pub tangent: Vector,
+    pub bitangent: Vector,
+    pub normal: Vector,
+}
+
+impl Frame {
+    /// Any frame around the unit vector `normal`, chosen without branches as described by Duff et
+    /// al. in "Building an Orthonormal Basis, Revisited".
+    pub fn new(normal: Vector) -> Self {
+        let sign = 1f64.copysign(normal.z());
+        let a = -1.0 / (sign + normal.z());
+        let b = normal.x() * normal.y() * a;
+
+        Self {
+            tangent: Vector::new(1.0 + sign * normal.x() * normal.x() * a, sign * b, -sign * normal.x()),
+            bitangent: Vector::new(b, sign + normal.y() * normal.y() * a, -normal.y()),
+            normal,
+        }
+    }
+
+    pub fn to_local(&self, v: Vector) -> Vector {
+        Vector::new(v.dot(self.tangent), v.dot(self.bitangent), v.dot(self.normal))
+    }
+
+    pub fn to_world(&self, v: Vector) -> Vector {
+        self.tangent * v.x() + self.bitangent * v.y() + self.normal * v.z()
+    }
+}
+
+/// Maps a pair of numbers in `0..1` to a direction in the hemisphere around the z axis, more
+/// likely the closer it is to the axis: the density is `cosine_hemisphere_pdf`.
+pub fn cosine_hemisphere(u: f64, v: f64) -> Vector {
+    let r = u.sqrt();
+    let (sin, cos) = (v * TAU).sin_cos();
+
+    Vector::new(r * cos, r * sin, (1.0 - u).max(0.0).sqrt())
+}
+
+/// The density of `cosine_hemisphere` for a direction at an angle with the given cosine to the
+/// z axis.
+pub fn cosine_hemisphere_pdf(cos_theta: f64) -> f64 {
+    cos_theta.max(0.0) * FRAC_1_PI
+}
+
+/// Maps a pair of numbers in `0..1` to a direction picked evenly among all directions, with the
+/// density `UNIFORM_SPHERE_PDF`.
+pub fn uniform_sphere(u: f64, v: f64) -> Vector {
+    let z = 1.0 - 2.0 * u;
+    let r = (1.0 - z * z).max(0.0).sqrt();
+    let (sin, cos) = (v * TAU).sin_cos();
+
+    Vector::new(r * cos, r * sin, z)
+}
+
+pub const UNIFORM_SPHERE_PDF: f64 = 1.0 / (4.0 * PI);
+
+/// A piecewise-constant density over `0..1` in proportion to a function given by its values on
+/// equal steps, for picking values where the function is large more often.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribution1D {
+    function: Vec<f64>,
+    /// The cumulative distribution at the start of each step, and 1 at the end.
+    cdf: Vec<f64>,
+    /// The integral of the function over `0..1`.
+    integral: f64,
+}
+
+impl Distribution1D {
+    /// The distribution for the values of a function, which must not be negative. A function that
+    /// is zero everywhere gives a uniform distribution.
+    pub fn new(function: Vec<f64>) -> Self {
+        let n = function.len() as f64;
+        let mut cdf = vec![0.0];
+        for f in &function {
+            cdf.push(cdf[cdf.len() - 1] + f / n);
+        }
+
+        let integral = cdf[cdf.len() - 1];
+        for (i, c) in cdf.iter_mut().enumerate() {
+            *c = match integral {
+                0.0 => i as f64 / n,
+                _   => *c / integral,
+            };
+        }
+
+        Self { function, cdf, integral }
+    }
+
+    pub fn integral(&self) -> f64 {
+        self.integral
+    }
+
+    /// Maps a number in `0..1` to a value in `0..1` picked from the distribution, together with
+    /// its density and the step it's in.
+    pub fn sample(&self, u: f64) -> (f64, f64, usize) {
+        let step = self.cdf.partition_point(|&c| c <= u).clamp(1, self.function.len()) - 1;
+        let width = self.cdf[step + 1] - self.cdf[step];
+        let offset = match width {
+            0.0 => 0.0,
+            _   => (u - self.cdf[step]) / width,
+        };
+
+        ((step as f64 + offset.clamp(0.0, 1.0)) / self.function.len() as f64, self.pdf_of(step), step)
+    }
+
+    /// The density of the value `x` in `0..1`.
+    pub fn pdf(&self, x: f64) -> f64 {
+        self.pdf_of(((x * self.function.len() as f64) as usize).min(self.function.len() - 1))
+    }
+
+    fn pdf_of(&self, step: usize) -> f64 {
+        match self.integral {
+            0.0 => 1.0,
+            _   => self.function[step] / self.integral,
+        }
+    }
+}
+
+/// A piecewise-constant density over the unit square in proportion to a function given by its
+/// values on a grid of rows, picking a row first and then a value along it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Distribution2D {
+    rows: Vec<Distribution1D>,
+    marginal: Distribution1D,
+}
+
+impl Distribution2D {
+    /// The distribution for a function with `width` values per row, given row by row.
+    pub fn new(function: &[f64], width: usize) -> Self {
+        let rows = function.chunks(width)
+            .map(|row| Distribution1D::new(row.to_vec()))
+            .collect::<Vec<_>>();
+        let marginal = Distribution1D::new(rows.iter().map(Distribution1D::integral).collect());
+
+        Self { rows, marginal }
+    }
+
+    /// Maps a pair of numbers in `0..1` to a point `(x, y)` in the unit square picked from the
+    /// distribution, together with its density.
+    pub fn sample(&self, u: f64, v: f64) -> ((f64, f64), f64) {
+        let (y, marginal_pdf, row) = self.marginal.sample(v);
+        let (x, row_pdf, _) = self.rows[row].sample(u);
+
+        ((x, y), marginal_pdf * row_pdf)
+    }
+
+    pub fn pdf(&self, x: f64, y: f64) -> f64 {
+        let row = ((y * self.rows.len() as f64) as usize).min(self.rows.len() - 1);
+
+        self.marginal.pdf(y) * self.rows[row].pdf(x)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::float::ApproxEq;
+
+    use super::*;
+
+    #[test]
+    fn frames_are_orthonormal() {
+        // Given
+        let normals = [
+            Vector::new(0.0, 0.0, 1.0),
+            Vector::new(0.0, 0.0, -1.0),
+            Vector::new(1.0, 0.0, 0.0),
+            Vector::new(0.48, -0.6, 0.64),
+        ];
+
+        for normal in normals {
+            // When
+            let f = Frame::new(normal);
+            let v = Vector::new(0.3, -0.2, 0.9);
+
+            // Then
+            assert!(f.tangent.magnitude().approx_eq(1.0) && f.bitangent.magnitude().approx_eq(1.0));
+            assert!(f.tangent.dot(f.bitangent).approx_eq(0.0) && f.tangent.dot(normal).approx_eq(0.0));
+            assert_eq!(f.tangent.cross(f.bitangent), normal);
+            assert_eq!(f.to_local(normal), Vector::new(0.0, 0.0, 1.0));
+            assert_eq!(f.to_world(f.to_local(v)), v);
+        }
+    }
+
+    #[test]
+    fn cosine_weighted_directions_lean_towards_the_axis() {
+        // Given
+        let n = 64;
+
+        // When
+        let directions = (0..n * n)
+            .map(|i| cosine_hemisphere((i / n) as f64 / n as f64 + 0.5 / n as f64, (i % n) as f64 / n as f64))
+            .collect::<Vec<_>>();
+        let mean_cos = directions.iter().map(|d| d.z()).sum::<f64>() / directions.len() as f64;
+
+        // Then
+        assert!(directions.iter().all(|d| d.magnitude().approx_eq(1.0) && d.z() >= 0.0));
+        assert!((mean_cos - 2.0 / 3.0).abs() < 1e-3);
+        assert!(cosine_hemisphere_pdf(1.0).approx_eq(FRAC_1_PI));
+        assert_eq!(cosine_hemisphere_pdf(-0.5), 0.0);
+    }
+
+    #[test]
+    fn distributions_pick_values_in_proportion_to_the_function() {
+        // Given
+        let d = Distribution1D::new(vec![1.0, 3.0, 0.0, 4.0]);
+        let flat = Distribution1D::new(vec![0.0, 0.0]);
+
+        // Then
+        assert!(d.integral().approx_eq(2.0));
+        assert_eq!(d.sample(0.0), (0.0, 0.5, 0));
+        assert_eq!(d.sample(0.125), (0.25, 1.5, 1));
+        assert_eq!(d.sample(0.5), (0.75, 2.0, 3));
+        assert!(d.sample(0.999).0 < 1.0);
+        assert_eq!(d.pdf(0.6), 0.0);
+        assert_eq!(flat.sample(0.75), (0.75, 1.0, 1));
+    }
+
+    #[test]
+    fn two_dimensional_distributions_pick_rows_first() {
+        // Given
+        let d = Distribution2D::new(&[1.0, 1.0, 0.0, 0.0, 2.0, 6.0], 2);
+
+        // When
+        let ((x, y), pdf) = d.sample(0.5, 0.9);
+
+        // Then
+        assert!(y > 2.0 / 3.0 && x > 0.5);
+        assert!(pdf.approx_eq(d.pdf(x, y)));
+        assert!(d.pdf(0.75, 0.9).approx_eq(6.0 / 10.0 * 6.0));
+        assert_eq!(d.pdf(0.2, 0.5), 0.0);
+    }
+}
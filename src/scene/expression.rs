@@ -0,0 +1,264 @@
+use std::f64::consts::{PI, TAU};
+
+/// Evaluates an arithmetic expression such as `radius * 2` or `pi / 4`. Supports `+`, `-`, `*`,
+/// `/`, `%`, `^` (power), parentheses, the constants `pi` and `tau`, variables looked up through
+/// `variable`, and the functions `sin`, `cos`, `tan`, `sqrt`, `abs`, `floor`, `ceil`, `min`,
+/// `max`, `radians` and `degrees`. Names may contain dashes like the rest of a scene file, so a
+/// minus between two names needs spaces around it. Expressions that come out infinite or not a
+/// number, such as `1 / 0`, are an error.
+pub fn evaluate(source: &str, variable: &dyn Fn(&str) -> Option<f64>) -> Result<f64, String> {
+    let tokens = tokenize(source)?;
+    let mut parser = Parser { tokens: &tokens, position: 0, variable };
+
+    let value = parser.expression()?;
+    match parser.peek() {
+        None if value.is_finite() => Ok(value),
+        None                      => Err(format!("`{}` is not a finite number", source)),
+        Some(token)               => Err(format!("unexpected `{}` in `{}`", token, source)),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Number(f64),
+    Name(String),
+    Symbol(char),
+}
+
+impl std::fmt::Display for Token {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Token::Number(n) => write!(f, "{}", n),
+            Token::Name(n)   => write!(f, "{}", n),
+            Token::Symbol(c) => write!(f, "{}", c),
+        }
+    }
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = vec![];
+    let mut chars = source.char_indices().peekable();
+
+    while let Some(&(start, c)) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            c if c.is_ascii_digit() || c == '.' => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    // Allow exponents such as 1e-3.
+                    let exponent_sign = (c == '-' || c == '+') && source[..i].ends_with(['e', 'E']);
+                    if !(c.is_ascii_alphanumeric() || c == '.' || exponent_sign) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let number = &source[start..end];
+                tokens.push(Token::Number(number.parse().map_err(|_| format!("invalid number `{}`", number))?));
+            }
+            c if c.is_alphabetic() || c == '_' => {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_alphanumeric() || c == '_' || c == '-' && source[i + 1..].starts_with(char::is_alphabetic)) {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                tokens.push(Token::Name(source[start..end].to_string()));
+            }
+            '+' | '-' | '*' | '/' | '%' | '^' | '(' | ')' | ',' => {
+                tokens.push(Token::Symbol(c));
+                chars.next();
+            }
+            c => return Err(format!("unexpected `{}` in `{}`", c, source)),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// A recursive descent parser that evaluates while it parses.
+struct Parser<'a> {
+    tokens: &'a [Token],
+    position: usize,
+    variable: &'a dyn Fn(&str) -> Option<f64>,
+}
+
+impl Parser<'_> {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.position)
+    }
+
+    fn next(&mut self) -> Option<Token> {
+        let token = self.tokens.get(self.position).cloned();
+        self.position += 1;
+
+        token
+    }
+
+    fn eat(&mut self, symbol: char) -> bool {
+        match self.peek() {
+            Some(Token::Symbol(c)) if *c == symbol => {
+                self.position += 1;
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn expect(&mut self, symbol: char) -> Result<(), String> {
+        match self.eat(symbol) {
+            true  => Ok(()),
+            false => Err(match self.peek() {
+                Some(token) => format!("expected `{}`, found `{}`", symbol, token),
+                None        => format!("expected `{}`", symbol),
+            }),
+        }
+    }
+
+    fn expression(&mut self) -> Result<f64, String> {
+        let mut value = self.term()?;
+        loop {
+            if self.eat('+') {
+                value += self.term()?;
+            } else if self.eat('-') {
+                value -= self.term()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn term(&mut self) -> Result<f64, String> {
+        let mut value = self.unary()?;
+        loop {
+            if self.eat('*') {
+                value *= self.unary()?;
+            } else if self.eat('/') {
+                value /= self.unary()?;
+            } else if self.eat('%') {
+                value %= self.unary()?;
+            } else {
+                return Ok(value);
+            }
+        }
+    }
+
+    fn unary(&mut self) -> Result<f64, String> {
+        if self.eat('-') {
+            Ok(-self.unary()?)
+        } else if self.eat('+') {
+            self.unary()
+        } else {
+            self.power()
+        }
+    }
+
+    fn power(&mut self) -> Result<f64, String> {
+        let base = self.primary()?;
+        match self.eat('^') {
+            true  => Ok(base.powf(self.unary()?)),
+            false => Ok(base),
+        }
+    }
+
+    fn primary(&mut self) -> Result<f64, String> {
+        match self.next() {
+            Some(Token::Number(n)) => Ok(n),
+            Some(Token::Symbol('(')) => {
+                let value = self.expression()?;
+                self.expect(')')?;
+                Ok(value)
+            }
+            Some(Token::Name(name)) if self.eat('(') => {
+                let mut args = vec![self.expression()?];
+                while self.eat(',') {
+                    args.push(self.expression()?);
+                }
+                self.expect(')')?;
+                call(&name, &args)
+            }
+            Some(Token::Name(name)) => match ((self.variable)(&name), name.as_str()) {
+                (Some(value), _) => Ok(value),
+                (None, "pi")     => Ok(PI),
+                (None, "tau")    => Ok(TAU),
+                (None, _)        => Err(format!("unknown variable `{}`", name)),
+            },
+            Some(token) => Err(format!("unexpected `{}`", token)),
+            None        => Err("unexpected end of expression".to_string()),
+        }
+    }
+}
+
+fn call(name: &str, args: &[f64]) -> Result<f64, String> {
+    match (name, args) {
+        ("sin", &[x])     => Ok(x.sin()),
+        ("cos", &[x])     => Ok(x.cos()),
+        ("tan", &[x])     => Ok(x.tan()),
+        ("sqrt", &[x])    => Ok(x.sqrt()),
+        ("abs", &[x])     => Ok(x.abs()),
+        ("floor", &[x])   => Ok(x.floor()),
+        ("ceil", &[x])    => Ok(x.ceil()),
+        ("radians", &[x]) => Ok(x.to_radians()),
+        ("degrees", &[x]) => Ok(x.to_degrees()),
+        ("min", &[x, y])  => Ok(x.min(y)),
+        ("max", &[x, y])  => Ok(x.max(y)),
+        ("sin" | "cos" | "tan" | "sqrt" | "abs" | "floor" | "ceil" | "radians" | "degrees" | "min" | "max", _) => {
+            Err(format!("wrong number of arguments for `{}`", name))
+        }
+        _ => Err(format!("unknown function `{}`", name)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn eval(source: &str) -> Result<f64, String> {
+        evaluate(source, &|name| match name {
+            "radius"      => Some(1.5),
+            "ring-count"  => Some(4.0),
+            _             => None,
+        })
+    }
+
+    #[test]
+    fn evaluating_arithmetic_with_precedence() {
+        // Then
+        assert_eq!(eval("1 + 2 * 3"), Ok(7.0));
+        assert_eq!(eval("(1 + 2) * 3"), Ok(9.0));
+        assert_eq!(eval("10 - 4 - 3"), Ok(3.0));
+        assert_eq!(eval("2 ^ 3 ^ 2"), Ok(512.0));
+        assert_eq!(eval("-2 ^ 2"), Ok(-4.0));
+        assert_eq!(eval("7 % 4 / 2"), Ok(1.5));
+        assert_eq!(eval("1.5e1 + .5"), Ok(15.5));
+    }
+
+    #[test]
+    fn evaluating_variables_constants_and_functions() {
+        // Then
+        assert_eq!(eval("radius * 2"), Ok(3.0));
+        assert_eq!(eval("ring-count-1"), Ok(3.0));
+        assert_eq!(eval("pi / 4"), Ok(PI / 4.0));
+        assert_eq!(eval("max(radius, sqrt(9))"), Ok(3.0));
+        assert_eq!(eval("degrees(tau)"), Ok(360.0));
+    }
+
+    #[test]
+    fn invalid_expressions_are_reported() {
+        // Then
+        assert_eq!(eval("diameter / 2"), Err("unknown variable `diameter`".to_string()));
+        assert_eq!(eval("(1 + 2"), Err("expected `)`".to_string()));
+        assert_eq!(eval("1 + 2)"), Err("unexpected `)` in `1 + 2)`".to_string()));
+        assert_eq!(eval("spin(1)"), Err("unknown function `spin`".to_string()));
+        assert_eq!(eval("min(1)"), Err("wrong number of arguments for `min`".to_string()));
+        assert_eq!(eval("2 $ 3"), Err("unexpected `$` in `2 $ 3`".to_string()));
+        assert_eq!(eval("1 +"), Err("unexpected end of expression".to_string()));
+        assert_eq!(eval("1 / 0"), Err("`1 / 0` is not a finite number".to_string()));
+        assert_eq!(eval("radius % 0"), Err("`radius % 0` is not a finite number".to_string()));
+        assert_eq!(eval("sqrt(-1)"), Err("`sqrt(-1)` is not a finite number".to_string()));
+    }
+}
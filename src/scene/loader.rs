@@ -0,0 +1,900 @@
+use std::collections::HashMap;
+use std::f64::consts::{PI, TAU};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::animation::{Interpolate, Interpolation, Keyframe, Track};
+use crate::bsdf::conductor::Conductor;
+use crate::bsdf::dielectric::{Dielectric, Dispersion};
+use crate::bsdf::glossy::Glossy;
+use crate::bsdf::lambert::Lambert;
+use crate::bsdf::Bsdf;
+use crate::bump::{Bump, ImageMap};
+use crate::camera::equirectangular::EquirectangularCamera;
+use crate::camera::fisheye::FisheyeCamera;
+use crate::camera::orthographic::OrthographicCamera;
+use crate::camera::perspective::{ApertureShape, PerspectiveCamera};
+use crate::camera::stereo::StereoCamera;
+use crate::camera::Camera;
+use crate::color::Color;
+use crate::core::float::ApproxEq;
+use crate::core::matrix::Matrix4;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::light::area::AreaLight;
+use crate::light::directional::DirectionalLight;
+use crate::light::environment::{EnvironmentLight, EnvironmentMap, Sky};
+use crate::light::point::PointLight;
+use crate::light::preetham::PreethamSky;
+use crate::light::spot::SpotLight;
+use crate::light::{Falloff, Light};
+use crate::material::Material;
+use crate::medium::density::{Density, DensityGrid, NoiseDensity};
+use crate::medium::{Medium, Volume};
+use crate::obj::ObjFile;
+use crate::scene::expression::evaluate;
+use crate::scene::node::{Node, Value};
+use crate::scene::{Scene, SceneError};
+use crate::shape::csg::{Csg, CsgOperation};
+use crate::shape::cube::Cube;
+use crate::shape::cylinder::Cylinder;
+use crate::shape::group::Group;
+use crate::shape::plane::Plane;
+use crate::shape::sphere::Sphere;
+use crate::shape::Shape;
+use crate::world::World;
+
+const SHAPE_KEYS: [&str; 4] = ["add", "transform", "end-transform", "material"];
+
+/// The most times a `for` loop may repeat its items, so that a mistake can't fill up the memory.
+const MAX_REPEATS: f64 = 1e6;
+
+/// Everything added by a list of items.
+#[derive(Default)]
+struct Contents {
+    camera: Option<Box<dyn Camera>>,
+    /// Whether the camera focuses on what it sees once the world is built.
+    autofocus: bool,
+    lights: Vec<Box<dyn Light>>,
+    objects: Vec<Box<dyn Shape>>,
+    volumes: Vec<Volume>,
+    fog: Option<Medium>,
+}
+
+/// Turns the nodes of a scene file into a scene at a point in time, keeping track of what has
+/// been defined so far.
+pub(crate) struct Loader {
+    base: PathBuf,
+    time: f64,
+    defines: HashMap<String, Node>,
+    variables: HashMap<String, f64>,
+    includes: Vec<PathBuf>,
+}
+
+impl Loader {
+    /// A loader that resolves relative paths in nodes that do not come from a file against
+    /// `base`. Paths in nodes from a file are relative to that file. Animated values take their
+    /// value at `time`, which is also available to expressions as the variable `time`.
+    pub(crate) fn new(base: &Path, time: f64) -> Self {
+        Self {
+            base: base.to_path_buf(),
+            time,
+            defines: HashMap::new(),
+            variables: HashMap::from([("time".to_string(), time)]),
+            includes: vec![],
+        }
+    }
+
+    pub(crate) fn load_file(mut self, path: &Path) -> Result<Scene, SceneError> {
+        let source = fs::read_to_string(path).map_err(|e| SceneError {
+            file: Some(path.to_path_buf()),
+            ..SceneError::new(0, format!("could not read file: {}", e))
+        })?;
+        self.includes.push(fs::canonicalize(path).unwrap_or(path.to_path_buf()));
+
+        self.load(&Node::parse_file(&source, path)?)
+    }
+
+    pub(crate) fn load(mut self, root: &Node) -> Result<Scene, SceneError> {
+        let mut contents = Contents::default();
+        self.items(root, &mut contents)?;
+
+        let mut camera = contents.camera.ok_or_else(|| root.error("the scene has no camera"))?;
+        let mut world = World::new();
+        for object in contents.objects {
+            world.add_object(object);
+        }
+        world.lights = contents.lights;
+        for volume in contents.volumes {
+            world.add_volume(volume);
+        }
+        world.fog = contents.fog;
+        world.build_bvh();
+        if contents.autofocus {
+            camera.autofocus(&world);
+        }
+
+        Ok(Scene { camera, world })
+    }
+
+    fn items(&mut self, list: &Node, contents: &mut Contents) -> Result<(), SceneError> {
+        for item in list.as_list()? {
+            if let Some(name) = item.get("define") {
+                self.define(item, name)?;
+            } else if let Some(path) = item.get("include") {
+                self.include(item, path, contents)?;
+            } else if let Some(variable) = item.get("for") {
+                self.repeat(item, variable, contents)?;
+            } else {
+                match item.require("add")?.as_str()? {
+                    "camera" => {
+                        let (camera, autofocus) = self.camera(item)?;
+                        contents.camera = Some(camera);
+                        contents.autofocus = autofocus;
+                    }
+                    "light" | "area-light" | "directional-light" | "spot-light" => contents.lights.push(self.light(item)?),
+                    "environment" => contents.lights.push(Box::new(self.environment(item)?)),
+                    "fog" => {
+                        item.check_keys(&["add", "absorption", "scattering", "anisotropy"])?;
+                        contents.fog = Some(self.medium(item)?);
+                    }
+                    "volume" => {
+                        item.check_keys(&["add", "boundary", "absorption", "scattering", "anisotropy", "density"])?;
+                        let mut medium = self.medium(item)?;
+                        if let Some(density) = item.get("density") {
+                            medium.density = self.density(density)?;
+                        }
+                        contents.volumes.push(Volume::new(self.shape(item.require("boundary")?)?, medium));
+                    }
+                    "sky" => {
+                        let (sky, sun) = self.sky(item)?;
+                        contents.lights.push(Box::new(sky));
+                        contents.lights.push(Box::new(sun));
+                    }
+                    _ => contents.objects.push(self.shape(item)?),
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Defines a variable if the value is a number, an expression or animated, and a macro
+    /// otherwise.
+    fn define(&mut self, item: &Node, name: &Node) -> Result<(), SceneError> {
+        item.check_keys(&["define", "extend", "value"])?;
+        let name = name.as_str()?;
+        let mut value = item.require("value")?.clone();
+
+        if matches!(value.value, Value::Scalar(_)) || value.get("keyframes").is_some() {
+            let number = self.number(&value)?;
+            self.variables.insert(name.to_string(), number);
+            return Ok(());
+        }
+
+        if let Some(base) = item.get("extend") {
+            value = self.lookup(base)?.merge(&value)?;
+        }
+        self.defines.insert(name.to_string(), value);
+
+        Ok(())
+    }
+
+    /// Loads the items of another file as if they were written in place of the `include`.
+    fn include(&mut self, item: &Node, path: &Node, contents: &mut Contents) -> Result<(), SceneError> {
+        item.check_keys(&["include"])?;
+        let file = self.resolve(path)?;
+        let canonical = fs::canonicalize(&file).unwrap_or(file.clone());
+
+        if self.includes.contains(&canonical) {
+            return Err(path.error(format!("{} includes itself", file.display())));
+        }
+        let source = fs::read_to_string(&file)
+            .map_err(|e| path.error(format!("could not read {}: {}", file.display(), e)))?;
+        let root = Node::parse_file(&source, &file)?;
+
+        self.includes.push(canonical);
+        let result = self.items(&root, contents);
+        self.includes.pop();
+
+        result
+    }
+
+    /// Repeats the items in `do` for each value of the variable from `from` (0 if left out) up to
+    /// but not including `to`, in increments of `step` (1 if left out).
+    fn repeat(&mut self, item: &Node, variable: &Node, contents: &mut Contents) -> Result<(), SceneError> {
+        item.check_keys(&["for", "from", "to", "step", "do"])?;
+        let name = variable.as_str()?.to_string();
+        let from = item.get("from").map_or(Ok(0.0), |n| self.number(n))?;
+        let to = self.number(item.require("to")?)?;
+        let step = item.get("step").map_or(Ok(1.0), |n| self.number(n))?;
+        let body = item.require("do")?;
+
+        if step == 0.0 {
+            return Err(item.require("step")?.error("the step cannot be 0"));
+        }
+
+        let count = ((to - from) / step).ceil().max(0.0);
+        if count > MAX_REPEATS {
+            return Err(item.error(format!("a loop can't repeat more than {} times", MAX_REPEATS)));
+        }
+
+        let previous = self.variables.get(&name).copied();
+        for i in 0..count as usize {
+            self.variables.insert(name.clone(), from + i as f64 * step);
+            self.items(body, contents)?;
+        }
+
+        match previous {
+            Some(value) => self.variables.insert(name, value),
+            None        => self.variables.remove(&name),
+        };
+
+        Ok(())
+    }
+
+    fn lookup(&self, name: &Node) -> Result<&Node, SceneError> {
+        let key = name.as_str()?;
+        self.defines.get(key).ok_or_else(|| name.error(format!("`{}` has not been defined", key)))
+    }
+
+    /// The path in `node`, relative to the file it was read from.
+    fn resolve(&self, node: &Node) -> Result<PathBuf, SceneError> {
+        let directory = match node.file.as_deref() {
+            Some(file) => file.parent().unwrap_or(Path::new("")),
+            None       => &self.base,
+        };
+
+        Ok(directory.join(node.as_str()?))
+    }
+
+    /// The value read by `value` from `node`, or if `node` has a list of keyframes instead, the
+    /// value at the current time interpolated between them.
+    fn animated<T>(&self, node: &Node, value: impl Fn(&Node) -> Result<T, SceneError>) -> Result<T, SceneError>
+    where
+        T: Interpolate + Clone,
+    {
+        let keyframes = match node.get("keyframes") {
+            Some(keyframes) => keyframes,
+            None            => return value(node),
+        };
+        node.check_keys(&["keyframes"])?;
+
+        let keyframes = keyframes.as_list()?.iter()
+            .map(|key| {
+                key.check_keys(&["time", "value", "interpolation"])?;
+                let interpolation = match key.get("interpolation") {
+                    Some(interpolation) => self.interpolation(interpolation)?,
+                    None                => Interpolation::Linear,
+                };
+                Ok(Keyframe::new(self.number(key.require("time")?)?, value(key.require("value")?)?, interpolation))
+            })
+            .collect::<Result<Vec<_>, SceneError>>()?;
+
+        match keyframes.is_empty() {
+            true  => Err(node.error("expected at least one keyframe")),
+            false => Ok(Track::new(keyframes).value_at(self.time)),
+        }
+    }
+
+    fn interpolation(&self, node: &Node) -> Result<Interpolation, SceneError> {
+        if let Value::List(parts) = &node.value {
+            return match &parts[..] {
+                [name, x1, y1, x2, y2] if name.as_str()? == "bezier" => {
+                    let (x1, x2) = (self.number(x1)?, self.number(x2)?);
+                    match (0.0..=1.0).contains(&x1) && (0.0..=1.0).contains(&x2) {
+                        true  => Ok(Interpolation::Bezier(x1, self.number(y1)?, x2, self.number(y2)?)),
+                        false => Err(node.error("the x coordinates of a bezier have to be between 0 and 1")),
+                    }
+                }
+                _ => Err(node.error("expected [bezier, x1, y1, x2, y2]")),
+            };
+        }
+
+        match node.as_str()? {
+            "linear" => Ok(Interpolation::Linear),
+            "ease"   => Ok(Interpolation::Ease),
+            s        => Err(node.error(format!("unknown interpolation `{}`", s))),
+        }
+    }
+
+    /// A number, which may be written as an expression using the variables defined so far.
+    fn number(&self, node: &Node) -> Result<f64, SceneError> {
+        self.animated(node, |node| {
+            let source = node.as_str()?;
+            match source.parse::<f64>() {
+                Ok(number) if number.is_finite() => Ok(number),
+                Ok(_)                            => Err(node.error(format!("`{}` is not a finite number", source))),
+                Err(_)                           => evaluate(source, &|name| self.variables.get(name).copied()).map_err(|e| node.error(e)),
+            }
+        })
+    }
+
+    fn count(&self, node: &Node) -> Result<usize, SceneError> {
+        match self.number(node)? {
+            n if n >= 0.0 && n.fract() == 0.0 => Ok(n as usize),
+            n                                 => Err(node.error(format!("expected a whole number, found {}", n))),
+        }
+    }
+
+    /// A whole number of at least 1, for the number of something there has to be some of.
+    fn positive_count(&self, node: &Node) -> Result<usize, SceneError> {
+        match self.count(node)? {
+            0 => Err(node.error("expected at least 1")),
+            n => Ok(n),
+        }
+    }
+
+    fn triple(&self, node: &Node) -> Result<(f64, f64, f64), SceneError> {
+        match node.as_list()? {
+            [x, y, z] => Ok((self.number(x)?, self.number(y)?, self.number(z)?)),
+            _         => Err(node.error("expected a list of three numbers")),
+        }
+    }
+
+    fn point(&self, node: &Node) -> Result<Point, SceneError> {
+        self.animated(node, |node| {
+            let (x, y, z) = self.triple(node)?;
+            Ok(Point::new(x, y, z))
+        })
+    }
+
+    fn vector(&self, node: &Node) -> Result<Vector, SceneError> {
+        self.animated(node, |node| {
+            let (x, y, z) = self.triple(node)?;
+            Ok(Vector::new(x, y, z))
+        })
+    }
+
+    /// A vector for a direction, which can't be zero.
+    fn direction(&self, node: &Node) -> Result<Vector, SceneError> {
+        let direction = self.vector(node)?;
+        match direction.magnitude().approx_eq(0.0) {
+            true  => Err(node.error("a direction can't be zero")),
+            false => Ok(direction),
+        }
+    }
+
+    fn color(&self, node: &Node) -> Result<Color, SceneError> {
+        self.animated(node, |node| {
+            let (r, g, b) = self.triple(node)?;
+            Ok(Color::new(r, g, b))
+        })
+    }
+
+    /// The camera, and whether it should focus on what it sees.
+    fn camera(&self, item: &Node) -> Result<(Box<dyn Camera>, bool), SceneError> {
+        const CAMERA_KEYS: [&str; 9] = ["add", "projection", "width", "height", "eye-separation", "shutter", "from", "to", "up"];
+
+        let separation = item.get("eye-separation").map(|s| self.number(s)).transpose()?;
+        let size = || -> Result<(usize, usize), SceneError> {
+            let node = item.require("width")?;
+            let width = self.positive_count(node)?;
+            // Each eye of a stereo camera gets half of the width.
+            let width = match separation {
+                Some(_) if width < 2 => return Err(node.error("a stereo camera needs a width of at least 2")),
+                Some(_)              => width / 2,
+                None                 => width,
+            };
+            Ok((width, self.positive_count(item.require("height")?)?))
+        };
+        // Fisheye cameras can see all around, but others only less than half a turn.
+        let field_of_view = |below: fn(f64) -> bool, name: &str| -> Result<f64, SceneError> {
+            let fov = item.require("field-of-view")?;
+            match self.number(fov)? {
+                f if f > 0.0 && below(f) => Ok(f),
+                _                        => Err(fov.error(format!("the field of view has to be between 0 and {}", name))),
+            }
+        };
+
+        let mut autofocus = false;
+        let projection = item.get("projection").map(Node::as_str).transpose()?.unwrap_or("perspective");
+        let eye: Box<dyn Camera> = match projection {
+            "perspective" => {
+                item.check_keys(&[&CAMERA_KEYS[..], &["field-of-view", "aperture", "aperture-blades", "focal-distance"]].concat())?;
+                let (width, height) = size()?;
+                let mut camera = PerspectiveCamera::new(width, height, field_of_view(|f| f < PI, "pi")?);
+                if let Some(aperture) = item.get("aperture") {
+                    camera.aperture = self.number(aperture)?;
+                }
+                if let Some(blades) = item.get("aperture-blades") {
+                    camera.aperture_shape = match self.count(blades)? {
+                        n if n >= 3 => ApertureShape::Blades(n),
+                        _           => return Err(blades.error("an aperture needs at least 3 blades")),
+                    };
+                }
+                match item.get("focal-distance") {
+                    Some(f) if f.as_str() == Ok("auto") => autofocus = true,
+                    Some(f)                             => camera.focal_distance = self.number(f)?,
+                    None                                => (),
+                }
+                Box::new(camera)
+            }
+            "fisheye" => {
+                item.check_keys(&[&CAMERA_KEYS[..], &["field-of-view"]].concat())?;
+                let (width, height) = size()?;
+                Box::new(FisheyeCamera::new(width, height, field_of_view(|f| f <= TAU, "tau")?))
+            }
+            "orthographic" => {
+                item.check_keys(&[&CAMERA_KEYS[..], &["view-size"]].concat())?;
+                let (width, height) = size()?;
+                let size = item.require("view-size")?;
+                match self.number(size)? {
+                    s if s > 0.0 => Box::new(OrthographicCamera::new(width, height, s)),
+                    _            => return Err(size.error("the view size has to be more than 0")),
+                }
+            }
+            "equirectangular" => {
+                item.check_keys(&CAMERA_KEYS)?;
+                let (width, height) = size()?;
+                Box::new(EquirectangularCamera::new(width, height))
+            }
+            s => return Err(item.require("projection")?.error(format!("unknown projection `{}`", s))),
+        };
+
+        let mut camera: Box<dyn Camera> = match separation {
+            Some(separation) => {
+                let mut stereo = StereoCamera::new(eye, separation);
+                stereo.omnidirectional = projection == "equirectangular";
+                Box::new(stereo)
+            }
+            None => eye,
+        };
+        if let Some(shutter) = item.get("shutter") {
+            match shutter.as_list()? {
+                [open, close] => camera.set_shutter(self.number(open)?, self.number(close)?),
+                _             => return Err(shutter.error("expected a list of two numbers")),
+            }
+        }
+        let up = match item.get("up") {
+            Some(up) => self.vector(up)?,
+            None     => Vector::new(0.0, 1.0, 0.0),
+        };
+        camera.set_transform(Matrix4::view_transform(self.point(item.require("from")?)?, self.point(item.require("to")?)?, up));
+
+        Ok((camera, autofocus))
+    }
+
+    fn light(&self, item: &Node) -> Result<Box<dyn Light>, SceneError> {
+        // Parsed once the keys of the light have been checked.
+        let intensity = || self.color(item.require("intensity")?);
+        let falloff = || match item.get("falloff") {
+            Some(f) => match f.as_str()? {
+                "none"           => Ok(Falloff::None),
+                "inverse-square" => Ok(Falloff::InverseSquare),
+                s                => Err(f.error(format!("unknown falloff `{}`", s))),
+            },
+            None => Ok(Falloff::None),
+        };
+
+        let kind = item.require("add")?;
+        let light: Box<dyn Light> = match kind.as_str()? {
+            "light" => {
+                item.check_keys(&["add", "at", "intensity", "falloff"])?;
+                let mut light = PointLight::new(self.point(item.require("at")?)?, intensity()?);
+                light.falloff = falloff()?;
+                Box::new(light)
+            }
+            "area-light" => {
+                item.check_keys(&["add", "corner", "uvec", "usteps", "vvec", "vsteps", "jitter", "intensity", "falloff"])?;
+                let mut light = AreaLight::new(
+                    self.point(item.require("corner")?)?,
+                    self.vector(item.require("uvec")?)?,
+                    self.positive_count(item.require("usteps")?)?,
+                    self.vector(item.require("vvec")?)?,
+                    self.positive_count(item.require("vsteps")?)?,
+                    intensity()?,
+                );
+                light.falloff = falloff()?;
+                if let Some(jitter) = item.get("jitter") {
+                    light.jitter = jitter.as_bool()?;
+                }
+                Box::new(light)
+            }
+            "directional-light" => {
+                item.check_keys(&["add", "direction", "intensity"])?;
+                Box::new(DirectionalLight::new(self.direction(item.require("direction")?)?, intensity()?))
+            }
+            "spot-light" => {
+                item.check_keys(&["add", "at", "direction", "inner-angle", "outer-angle", "intensity", "falloff"])?;
+                let mut light = SpotLight::new(
+                    self.point(item.require("at")?)?,
+                    self.direction(item.require("direction")?)?,
+                    self.number(item.require("inner-angle")?)?,
+                    self.number(item.require("outer-angle")?)?,
+                    intensity()?,
+                );
+                light.falloff = falloff()?;
+                Box::new(light)
+            }
+            s => return Err(kind.error(format!("unknown light `{}`", s))),
+        };
+
+        Ok(light)
+    }
+
+    /// The light of the sky, from a panorama in a `file` or else a `color` or a gradient from
+    /// `bottom` to `top`.
+    fn environment(&self, item: &Node) -> Result<EnvironmentLight, SceneError> {
+        item.check_keys(&["add", "file", "color", "bottom", "top", "intensity", "samples", "transform"])?;
+        let sky = match (item.get("file"), item.get("color"), item.get("top")) {
+            (Some(file), None, None) => {
+                let path = self.resolve(file)?;
+                let map = EnvironmentMap::load(&path)
+                    .map_err(|e| file.error(format!("could not read {}: {}", path.display(), e)))?;
+                Sky::Map(map)
+            }
+            (None, Some(color), None) => Sky::Constant { color: self.color(color)? },
+            (None, None, Some(top))   => Sky::Gradient { bottom: self.color(item.require("bottom")?)?, top: self.color(top)? },
+            _ => return Err(item.error("an environment needs one of a `file`, a `color` or a `top` and `bottom`")),
+        };
+
+        let mut light = EnvironmentLight::new(sky);
+        if let Some(intensity) = item.get("intensity") {
+            light.intensity = self.color(intensity)?;
+        }
+        if let Some(samples) = item.get("samples") {
+            light.samples = self.positive_count(samples)?;
+        }
+        if let Some(transform) = item.get("transform") {
+            light.set_transform(self.transform(transform)?);
+        }
+
+        Ok(light)
+    }
+
+    /// A daylight sky and its sun, which are both scaled by the `intensity`.
+    fn sky(&self, item: &Node) -> Result<(EnvironmentLight, DirectionalLight), SceneError> {
+        item.check_keys(&["add", "sun", "turbidity", "ground-albedo", "intensity", "samples"])?;
+        let turbidity = match item.get("turbidity") {
+            Some(turbidity) => self.number(turbidity)?,
+            None            => 3.0,
+        };
+        if !(2.0..=20.0).contains(&turbidity) {
+            return Err(item.require("turbidity")?.error("the turbidity has to be between 2 and 20"));
+        }
+        let ground_albedo = match item.get("ground-albedo") {
+            Some(albedo) => self.color(albedo)?,
+            None         => Color::new(0.2, 0.2, 0.2),
+        };
+
+        let sky = PreethamSky::new(self.direction(item.require("sun")?)?, turbidity, ground_albedo);
+        let mut sun = sky.sun_light();
+        let mut light = EnvironmentLight::new(Sky::Preetham(sky));
+        if let Some(intensity) = item.get("intensity") {
+            light.intensity = self.color(intensity)?;
+            sun.intensity = sun.intensity * light.intensity;
+        }
+        if let Some(samples) = item.get("samples") {
+            light.samples = self.positive_count(samples)?;
+        }
+
+        Ok((light, sun))
+    }
+
+    /// A medium of constant density, which neither absorbs nor scatters light unless told to.
+    fn medium(&self, item: &Node) -> Result<Medium, SceneError> {
+        let color = |key| match item.get(key) {
+            Some(node) => match self.color(node)? {
+                c if c.r >= 0.0 && c.g >= 0.0 && c.b >= 0.0 => Ok(c),
+                _                                          => Err(node.error(format!("the {} can't be negative", key))),
+            },
+            None => Ok(Color::default()),
+        };
+        let anisotropy = match item.get("anisotropy") {
+            Some(g) => match self.number(g)? {
+                g if (-1.0..=1.0).contains(&g) => g,
+                _                              => return Err(g.error("the anisotropy has to be between -1 and 1")),
+            },
+            None => 0.0,
+        };
+
+        Ok(Medium::new(color("absorption")?, color("scattering")?, anisotropy))
+    }
+
+    /// A density that is either a number, `noise` or a `grid` of numbers.
+    fn density(&self, node: &Node) -> Result<Density, SceneError> {
+        let density = |node: &Node| match self.number(node)? {
+            d if d >= 0.0 => Ok(d),
+            _             => Err(node.error("the density can't be negative")),
+        };
+        if let Value::Scalar(_) = node.value {
+            return Ok(Density::Constant(density(node)?));
+        }
+        node.check_keys(&["noise", "grid"])?;
+
+        if let Some(noise) = node.get("noise") {
+            noise.check_keys(&["frequency", "octaves", "cutoff"])?;
+            let number = |key, default| noise.get(key).map_or(Ok(default), |n| self.number(n));
+            let octaves = noise.get("octaves").map_or(Ok(4), |n| self.count(n))?;
+            return Ok(Density::Noise(NoiseDensity::new(number("frequency", 1.0)?, octaves, number("cutoff", 0.0)?)));
+        }
+
+        let grid = node.require("grid")?;
+        grid.check_keys(&["size", "values"])?;
+        let size = grid.require("size")?;
+        let [width, height, depth] = match size.as_list()? {
+            [w, h, d] => [self.count(w)?, self.count(h)?, self.count(d)?],
+            _         => return Err(size.error("expected a list of three numbers")),
+        };
+        let values = grid.require("values")?;
+        let numbers = values.as_list()?.iter()
+            .map(density)
+            .collect::<Result<Vec<_>, _>>()?;
+
+        DensityGrid::new(width, height, depth, numbers)
+            .map(Density::Grid)
+            .ok_or_else(|| values.error(format!("expected {} values for a grid of {}x{}x{}", width * height * depth, width, height, depth)))
+    }
+
+    /// Bumps from a `normal-map` or a `height-map` image, or from `noise`, of which the last two
+    /// are as high as their `scale`.
+    fn bump(&self, node: &Node) -> Result<Bump, SceneError> {
+        node.check_keys(&["normal-map", "height-map", "noise", "scale"])?;
+        let image = |file: &Node| {
+            let path = self.resolve(file)?;
+            ImageMap::load(&path).map_err(|e| file.error(format!("could not read {}: {}", path.display(), e)))
+        };
+        let scale = || node.get("scale").map_or(Ok(1.0), |s| self.number(s));
+
+        match (node.get("normal-map"), node.get("height-map"), node.get("noise")) {
+            (Some(file), None, None) => match node.get("scale") {
+                Some(scale) => Err(scale.error("a normal map has no `scale`")),
+                None        => Ok(Bump::Normal(image(file)?)),
+            },
+            (None, Some(file), None) => Ok(Bump::Height { map: image(file)?, scale: scale()? }),
+            (None, None, Some(noise)) => {
+                noise.check_keys(&["frequency", "octaves"])?;
+                let frequency = noise.get("frequency").map_or(Ok(1.0), |n| self.number(n))?;
+                let octaves = noise.get("octaves").map_or(Ok(4), |n| self.count(n))?;
+                Ok(Bump::Noise { frequency, octaves, scale: scale()? })
+            }
+            _ => Err(node.error("bumps need one of a `normal-map`, a `height-map` or `noise`")),
+        }
+    }
+
+    fn dispersion(&self, node: &Node) -> Result<Dispersion, SceneError> {
+        if let Value::Scalar(_) = node.value {
+            return match node.as_str()? {
+                "bk7"     => Ok(Dispersion::BK7),
+                "diamond" => Ok(Dispersion::DIAMOND),
+                s         => Err(node.error(format!("unknown glass `{}`", s))),
+            };
+        }
+        node.check_keys(&["cauchy", "sellmeier"])?;
+        let numbers = |node: &Node, n: usize| {
+            let numbers = node.as_list()?.iter()
+                .map(|value| self.number(value))
+                .collect::<Result<Vec<_>, _>>()?;
+            match numbers.len() == n {
+                true  => Ok(numbers),
+                false => Err(node.error(format!("expected a list of {} numbers", n))),
+            }
+        };
+
+        match (node.get("cauchy"), node.get("sellmeier")) {
+            (Some(cauchy), None) => {
+                let ab = numbers(cauchy, 2)?;
+                Ok(Dispersion::Cauchy { a: ab[0], b: ab[1] })
+            }
+            (None, Some(sellmeier)) => {
+                sellmeier.check_keys(&["b", "c"])?;
+                let (b, c) = (numbers(sellmeier.require("b")?, 3)?, numbers(sellmeier.require("c")?, 3)?);
+                Ok(Dispersion::Sellmeier { b: [b[0], b[1], b[2]], c: [c[0], c[1], c[2]] })
+            }
+            _ => Err(node.error("a dispersion needs one of `cauchy` or `sellmeier`")),
+        }
+    }
+
+    fn shape(&mut self, item: &Node) -> Result<Box<dyn Shape>, SceneError> {
+        let kind = item.require("add")?;
+
+        let mut shape: Box<dyn Shape> = match kind.as_str()? {
+            "sphere" => {
+                item.check_keys(&SHAPE_KEYS)?;
+                Box::new(Sphere::new())
+            }
+            "plane" => {
+                item.check_keys(&SHAPE_KEYS)?;
+                Box::new(Plane::new())
+            }
+            "cube" => {
+                item.check_keys(&SHAPE_KEYS)?;
+                Box::new(Cube::new())
+            }
+            "cylinder" => {
+                item.check_keys(&[&SHAPE_KEYS[..], &["minimum", "maximum", "closed"]].concat())?;
+                let mut cylinder = Cylinder::new();
+                if let Some(minimum) = item.get("minimum") {
+                    cylinder.minimum = self.number(minimum)?;
+                }
+                if let Some(maximum) = item.get("maximum") {
+                    cylinder.maximum = self.number(maximum)?;
+                }
+                if let Some(closed) = item.get("closed") {
+                    cylinder.closed = closed.as_bool()?;
+                }
+                Box::new(cylinder)
+            }
+            "group" => {
+                item.check_keys(&[&SHAPE_KEYS[..], &["children"]].concat())?;
+                let children = item.require("children")?;
+                let mut contents = Contents::default();
+                self.items(children, &mut contents)?;
+
+                if contents.camera.is_some() || !contents.lights.is_empty() || contents.fog.is_some() || !contents.volumes.is_empty() {
+                    return Err(children.error("only shapes can be added to a group"));
+                }
+                let mut group = Group::new();
+                for child in contents.objects {
+                    group.add_child(child);
+                }
+                Box::new(group)
+            }
+            "csg" => {
+                item.check_keys(&[&SHAPE_KEYS[..], &["operation", "left", "right"]].concat())?;
+                let operation = item.require("operation")?;
+                let operation = match operation.as_str()? {
+                    "union"        => CsgOperation::Union,
+                    "intersection" => CsgOperation::Intersection,
+                    "difference"   => CsgOperation::Difference,
+                    s              => return Err(operation.error(format!("unknown operation `{}`", s))),
+                };
+                let left = self.shape(item.require("left")?)?;
+                let right = self.shape(item.require("right")?)?;
+                Box::new(Csg::new(operation, left, right))
+            }
+            "obj" => {
+                item.check_keys(&[&SHAPE_KEYS[..], &["file"]].concat())?;
+                let file = item.require("file")?;
+                let path = self.resolve(file)?;
+                let obj = ObjFile::load(&path)
+                    .map_err(|e| file.error(format!("could not read {}: {}", path.display(), e)))?;
+                let mut group = Group::new();
+                for triangle in obj.into_shapes() {
+                    group.add_child(triangle);
+                }
+                Box::new(group)
+            }
+            _ => {
+                // A defined shape, with the entries of this item added to or replacing its own.
+                let defined = self.lookup(kind)?;
+                let mut merged = defined.merge(item)?;
+                if let Value::Map(entries) = &mut merged.value {
+                    entries.retain(|(k, _)| k != "add");
+                    entries.insert(0, ("add".to_string(), defined.require("add")?.clone()));
+                }
+                return self.shape(&merged);
+            }
+        };
+
+        if let Some(transform) = item.get("transform") {
+            shape.set_transform(self.transform(transform)?);
+        }
+        if let Some(end) = item.get("end-transform") {
+            shape.set_end_transform(self.transform(end)?);
+        }
+        if let Some(material) = item.get("material") {
+            shape.set_material(self.material(material)?);
+        }
+
+        Ok(shape)
+    }
+
+    /// Combines a list of transforms, applied in order, into one matrix. Names in the list refer
+    /// to defined lists of transforms. Keyframes of whole transforms are interpolated through
+    /// their decompositions, which turns the short way round, so rotations of half a turn or
+    /// more are better animated through their angle.
+    fn transform(&self, node: &Node) -> Result<Matrix4, SceneError> {
+        let transform = self.animated(node, |node| {
+            let mut transform = Matrix4::identity();
+
+            for item in node.as_list()? {
+                let next = match &item.value {
+                    Value::Scalar(_) => self.transform(self.lookup(item)?)?,
+                    _                => self.single_transform(item)?,
+                };
+                transform = next * transform;
+            }
+
+            Ok(transform)
+        })?;
+
+        match transform.is_invertible() {
+            true  => Ok(transform),
+            false => Err(node.error("the transform has no inverse")),
+        }
+    }
+
+    fn single_transform(&self, item: &Node) -> Result<Matrix4, SceneError> {
+        let parts = item.as_list()?;
+        let (name, args) = parts.split_first().ok_or_else(|| item.error("empty transform"))?;
+        let args = args.iter().map(|a| self.number(a)).collect::<Result<Vec<_>, _>>()?;
+
+        let transform = match (name.as_str()?, &args[..]) {
+            ("translate", &[x, y, z])            => Matrix4::translation(x, y, z),
+            ("scale", &[x, y, z])                => Matrix4::scaling(x, y, z),
+            ("rotate-x", &[r])                   => Matrix4::rotation_x(r),
+            ("rotate-y", &[r])                   => Matrix4::rotation_y(r),
+            ("rotate-z", &[r])                   => Matrix4::rotation_z(r),
+            ("shear", &[xy, xz, yx, yz, zx, zy]) => Matrix4::shearing(xy, xz, yx, yz, zx, zy),
+            (name @ ("translate" | "scale" | "rotate-x" | "rotate-y" | "rotate-z" | "shear"), _) => {
+                return Err(item.error(format!("wrong number of arguments for `{}`", name)));
+            }
+            (name, _) => return Err(parts[0].error(format!("unknown transform `{}`", name))),
+        };
+
+        // Shapes are intersected in their own space, which a transform without an inverse
+        // squashes everything into a plane or less of.
+        match transform.is_invertible() {
+            true  => Ok(transform),
+            false => Err(item.error(format!("`{}` with these arguments has no inverse", name.as_str()?))),
+        }
+    }
+
+    fn material(&self, node: &Node) -> Result<Material, SceneError> {
+        let node = match &node.value {
+            Value::Scalar(_) => self.lookup(node)?,
+            _                => node,
+        };
+        const MATERIAL_KEYS: [&str; 8] = ["color", "ambient", "diffuse", "specular", "shininess", "emission", "model", "bump"];
+        let model = node.get("model").map(Node::as_str).transpose()?.unwrap_or("phong");
+        node.check_keys(&[&MATERIAL_KEYS[..], match model {
+            "glossy"     => &["roughness", "metallic"],
+            "dielectric" => &["ior", "dispersion", "roughness"],
+            "conductor"  => &["metal", "eta", "k", "roughness"],
+            _            => &[],
+        }].concat())?;
+
+        let mut material = Material::default();
+        if let Some(color) = node.get("color") {
+            material.color = self.color(color)?;
+        }
+        if let Some(ambient) = node.get("ambient") {
+            material.ambient = self.number(ambient)?;
+        }
+        if let Some(diffuse) = node.get("diffuse") {
+            material.diffuse = self.number(diffuse)?;
+        }
+        if let Some(specular) = node.get("specular") {
+            material.specular = self.number(specular)?;
+        }
+        if let Some(shininess) = node.get("shininess") {
+            material.shininess = self.number(shininess)?;
+        }
+        if let Some(emission) = node.get("emission") {
+            material.emission = self.color(emission)?;
+        }
+        if let Some(bump) = node.get("bump") {
+            material.bump = Some(self.bump(bump)?);
+        }
+
+        let roughness = || node.get("roughness").map_or(Ok(0.0), |r| self.number(r));
+        material.bsdf = match model {
+            "phong"      => None,
+            "lambert"    => Some(Bsdf::Lambert(Lambert::new(material.color))),
+            "glossy"     => {
+                let metallic = node.get("metallic").map_or(Ok(0.0), |m| self.number(m))?;
+                Some(Bsdf::Glossy(Glossy::new(material.color, roughness()?, metallic)))
+            }
+            "dielectric" => Some(Bsdf::Dielectric(match (node.get("ior"), node.get("dispersion")) {
+                (Some(ior), None)        => Dielectric::new(self.number(ior)?, roughness()?),
+                (None, Some(dispersion)) => Dielectric::with_dispersion(self.dispersion(dispersion)?, roughness()?),
+                _                        => return Err(node.error("a dielectric needs one of an `ior` or a `dispersion`")),
+            })),
+            "conductor"  => Some(Bsdf::Conductor(match node.get("metal") {
+                Some(metal) => match metal.as_str()? {
+                    "gold"      => Conductor::gold(roughness()?),
+                    "copper"    => Conductor::copper(roughness()?),
+                    "aluminium" => Conductor::aluminium(roughness()?),
+                    s           => return Err(metal.error(format!("unknown metal `{}`", s))),
+                },
+                None => Conductor::new(self.color(node.require("eta")?)?, self.color(node.require("k")?)?, roughness()?),
+            })),
+            s => return Err(node.require("model")?.error(format!("unknown material model `{}`", s))),
+        };
+
+        Ok(material)
+    }
+}
@@ -0,0 +1,849 @@
+pub mod expression;
+mod loader;
+pub mod node;
+
+use std::error::Error;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::camera::Camera;
+use crate::scene::loader::Loader;
+use crate::scene::node::Node;
+use crate::world::World;
+
+/// A world and the camera to render it with, as described by a YAML scene file: a list of
+/// cameras, lights and shapes to `add`, and materials, transforms and shapes to `define` for
+/// them. The format is described in `docs/scene-format.md`.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Scene {
+    pub camera: Box<dyn Camera>,
+    pub world: World,
+}
+
+impl Scene {
+    /// Loads a scene file. Paths in it, such as those of included or OBJ files, are relative to
+    /// the file they appear in.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SceneError> {
+        Self::load_at(path, 0.0)
+    }
+
+    /// Loads a scene file as it is at `time`.
+    pub fn load_at(path: impl AsRef<Path>, time: f64) -> Result<Self, SceneError> {
+        Loader::new(Path::new(""), time).load_file(path.as_ref())
+    }
+
+    /// Parses a scene, resolving relative paths in it against the working directory.
+    pub fn parse(source: &str) -> Result<Self, SceneError> {
+        Self::parse_at(source, 0.0)
+    }
+
+    /// Parses a scene as it is at `time`.
+    pub fn parse_at(source: &str, time: f64) -> Result<Self, SceneError> {
+        Loader::new(Path::new(""), time).load(&Node::parse(source)?)
+    }
+}
+
+/// An error in a scene, with the file and line it was found on. Scenes parsed from a string have
+/// no file, and errors that are not about a particular line have line 0.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SceneError {
+    pub file: Option<PathBuf>,
+    pub line: usize,
+    pub message: String,
+}
+
+impl SceneError {
+    pub fn new(line: usize, message: impl Into<String>) -> Self {
+        Self { file: None, line, message: message.into(), }
+    }
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.file, self.line) {
+            (Some(file), 0) => write!(f, "{}: {}", file.display(), self.message),
+            (Some(file), l) => write!(f, "{}:{}: {}", file.display(), l, self.message),
+            (None, 0)       => write!(f, "{}", self.message),
+            (None, l)       => write!(f, "line {}: {}", l, self.message),
+        }
+    }
+}
+
+impl Error for SceneError {}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::PI;
+    use std::fs;
+
+    use crate::bsdf::conductor::Conductor;
+    use crate::bsdf::dielectric::{Dielectric, Dispersion};
+    use crate::bsdf::glossy::Glossy;
+    use crate::bsdf::Bsdf;
+    use crate::bump::Bump;
+    use crate::camera::perspective::PerspectiveCamera;
+    use crate::color::Color;
+    use crate::core::matrix::Matrix4;
+    use crate::core::point::Point;
+    use crate::core::tuple::Tuple;
+    use crate::core::vector::Vector;
+    use crate::light::preetham::PreethamSky;
+    #[cfg(feature = "serde")]
+    use crate::light::Light;
+    use crate::medium::Medium;
+    use crate::ray::Ray;
+    use crate::shape::cube::Cube;
+    use crate::shape::Shape;
+
+    use super::*;
+
+    const SCENE: &str = "
+- add: camera
+  width: 100
+  height: 50
+  field-of-view: 0.785
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+  up: [0, 1, 0]
+
+- add: light
+  at: [-10, 10, -10]
+  intensity: [1, 1, 1]
+
+- define: white-material
+  value:
+    color: [1, 1, 1]
+    diffuse: 0.7
+    ambient: 0.1
+
+- define: blue-material
+  extend: white-material
+  value:
+    color: [0.537, 0.831, 0.914]
+
+- define: standard-transform
+  value:
+    - [translate, 1, -1, 1]
+    - [scale, 0.5, 0.5, 0.5]
+
+- define: large-object
+  value:
+    - standard-transform
+    - [scale, 3.5, 3.5, 3.5]
+
+- add: cube
+  material: blue-material
+  transform:
+    - large-object
+    - [translate, 1, 0, 0]
+
+- add: group
+  material:
+    color: [1, 0, 0]
+  children:
+    - add: sphere
+    - add: cylinder
+      minimum: 0
+      maximum: 1
+      closed: true
+";
+
+    #[test]
+    fn parsing_a_scene() {
+        // When
+        let scene = Scene::parse(SCENE).unwrap();
+
+        // Then
+        assert_eq!(scene.camera.hsize(), 100);
+        assert_eq!(scene.camera.vsize(), 50);
+        assert_eq!(*scene.camera.transform(), Matrix4::scaling(-1.0, 1.0, -1.0) * Matrix4::translation(0.0, 0.0, 5.0));
+        assert_eq!(scene.camera.local_ray(0.0, 0.0), PerspectiveCamera::new(100, 50, 0.785).local_ray(0.0, 0.0));
+        assert_eq!(scene.world.lights.len(), 1);
+        assert_eq!(scene.world.lights[0].intensity(), Color::new(1.0, 1.0, 1.0));
+        assert_eq!(scene.world.objects().len(), 2);
+    }
+
+    #[test]
+    fn defines_can_extend_materials_and_transforms() {
+        // When
+        let scene = Scene::parse(SCENE).unwrap();
+        let cube = &scene.world.objects()[0];
+
+        // Then
+        let expected = Matrix4::translation(1.0, 0.0, 0.0)
+            * Matrix4::scaling(3.5, 3.5, 3.5)
+            * Matrix4::scaling(0.5, 0.5, 0.5)
+            * Matrix4::translation(1.0, -1.0, 1.0);
+        assert_eq!(*cube.transform(), expected);
+        assert_eq!(cube.material().color, Color::new(0.537, 0.831, 0.914));
+        assert_eq!(cube.material().diffuse, 0.7);
+        assert_eq!(cube.material().specular, 0.9);
+    }
+
+    #[test]
+    fn group_children_inherit_the_group_material() {
+        // When
+        let scene = Scene::parse(SCENE).unwrap();
+        let group = &scene.world.objects()[1];
+
+        // Then
+        assert_eq!(group.children().len(), 2);
+        assert_eq!(group.children()[1].material().color, Color::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn defined_shapes_can_be_added_with_extra_settings() {
+        // Given
+        let source = "
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+- define: ball
+  value:
+    add: sphere
+    material:
+      color: [0, 1, 0]
+- add: ball
+  transform:
+    - [rotate-y, 3.141592653589793]
+    - [translate, 0, 2, 0]
+";
+
+        // When
+        let scene = Scene::parse(source).unwrap();
+        let ball = &scene.world.objects()[0];
+
+        // Then
+        assert_eq!(*ball.transform(), Matrix4::translation(0.0, 2.0, 0.0) * Matrix4::rotation_y(PI));
+        assert_eq!(ball.material().color, Color::new(0.0, 1.0, 0.0));
+        assert_eq!(ball.normal_at(Point::new(0.0, 3.0, 0.0)), Vector::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn materials_can_describe_how_they_scatter_light() {
+        // Given
+        let source = "
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+- add: sphere
+  material:
+    color: [1, 0.8, 0.3]
+    model: glossy
+    roughness: 0.4
+    metallic: 1
+- add: sphere
+  material:
+    model: dielectric
+    ior: 1.5
+- add: sphere
+  material:
+    model: conductor
+    metal: gold
+    roughness: 0.2
+- add: sphere
+  material:
+    model: dielectric
+    dispersion: diamond
+- add: sphere
+  material:
+    model: dielectric
+    dispersion:
+      cauchy: [1.5, 0.004]
+- add: plane
+  material:
+    emission: [4, 4, 4]
+    bump:
+      noise: { frequency: 4, octaves: 2 }
+      scale: 0.1
+";
+
+        // When
+        let scene = Scene::parse(source).unwrap();
+        let objects = &scene.world.objects();
+
+        // Then
+        assert_eq!(objects[0].material().bsdf, Some(Bsdf::Glossy(Glossy::new(Color::new(1.0, 0.8, 0.3), 0.4, 1.0))));
+        assert_eq!(objects[1].material().bsdf, Some(Bsdf::Dielectric(Dielectric::new(1.5, 0.0))));
+        assert_eq!(objects[2].material().bsdf, Some(Bsdf::Conductor(Conductor::gold(0.2))));
+        assert_eq!(objects[3].material().bsdf, Some(Bsdf::Dielectric(Dielectric::with_dispersion(Dispersion::DIAMOND, 0.0))));
+        assert_eq!(objects[4].material().bsdf, Some(Bsdf::Dielectric(Dielectric::with_dispersion(Dispersion::Cauchy { a: 1.5, b: 0.004 }, 0.0))));
+        assert_eq!(objects[5].material().bsdf, None);
+        assert_eq!(objects[5].material().emission, Color::new(4.0, 4.0, 4.0));
+        assert_eq!(objects[5].material().bump, Some(Bump::Noise { frequency: 4.0, octaves: 2, scale: 0.1 }));
+        assert_eq!(objects[0].material().bump, None);
+    }
+
+    #[test]
+    fn errors_report_the_line_they_occur_on() {
+        // Given
+        let cases = [
+            ("- add: sphere\n  material: shiny\n", 2, "`shiny` has not been defined"),
+            ("- add: sphere\n  transform:\n    - [translate, 1, 2]\n", 3, "wrong number of arguments for `translate`"),
+            ("- add: sphere\n  transform:\n    - [spin, 1]\n", 3, "unknown transform `spin`"),
+            ("- add: sphere\n  transform:\n    - [rotate-x, pi / 0]\n", 3, "`pi / 0` is not a finite number"),
+            ("- add: sphere\n  material:\n    diffuse: inf\n", 3, "`inf` is not a finite number"),
+            ("- add: sphere\n  transform:\n    - [scale, 0, 0, 0]\n", 3, "`scale` with these arguments has no inverse"),
+            ("- add: sphere\n  transform:\n    - [scale, 0.01, 0.01, 0.01]\n    - [scale, 0.01, 0.01, 0.01]\n", 3, "the transform has no inverse"),
+            ("- add: cube\n  radius: 2\n", 2, "unknown key `radius`"),
+            ("- add: teapot\n", 1, "`teapot` has not been defined"),
+            ("- add: light\n  at: [0, 0]\n  intensity: [1, 1, 1]\n", 2, "expected a list of three numbers"),
+            ("- add: light\n  at: [0, 0, 0]\n  intensity: [1, 1, 1]\n  falloff: linear\n  color: [1, 1, 1]\n", 5, "unknown key `color`"),
+            ("- add: light\n  at: [0, 0, 0]\n  intensity: [1, 1, 1]\n  falloff: linear\n", 4, "unknown falloff `linear`"),
+            ("- add: area-light\n  corner: [0, 0, 0]\n  uvec: [1, 0, 0]\n  usteps: 0\n  vvec: [0, 1, 0]\n  vsteps: 2\n  intensity: [1, 1, 1]\n", 4, "expected at least 1"),
+            ("- add: area-light\n  corner: [0, 0, 0]\n  uvec: [1, 0, 0]\n  usteps: 2\n  vvec: [0, 1, 0]\n  vsteps: 0\n  intensity: [1, 1, 1]\n", 6, "expected at least 1"),
+            ("- add: directional-light\n  direction: [0, 0, 0]\n  intensity: [1, 1, 1]\n", 2, "a direction can't be zero"),
+            ("- add: spot-light\n  at: [0, 0, 0]\n  direction: [0, 0, 0]\n  inner-angle: 0.1\n  outer-angle: 0.2\n  intensity: [1, 1, 1]\n", 3, "a direction can't be zero"),
+            ("- add: sphere\n", 1, "the scene has no camera"),
+            ("- add: camera\n  width: 0\n  height: 1\n  field-of-view: 1\n", 2, "expected at least 1"),
+            ("- add: camera\n  width: 1\n  height: 0\n  field-of-view: 1\n", 3, "expected at least 1"),
+            ("- add: camera\n  width: 1\n  height: 1\n  field-of-view: 0\n", 4, "the field of view has to be between 0 and pi"),
+            ("- add: camera\n  width: 1\n  height: 1\n  field-of-view: pi\n", 4, "the field of view has to be between 0 and pi"),
+            ("- add: camera\n  projection: fisheye\n  width: 1\n  height: 1\n  field-of-view: 7\n", 5, "the field of view has to be between 0 and tau"),
+            ("- add: camera\n  projection: orthographic\n  width: 1\n  height: 1\n  view-size: -2\n", 5, "the view size has to be more than 0"),
+            ("- add: camera\n  width: 1\n  height: 1\n  field-of-view: 1\n  eye-separation: 0.1\n", 2, "a stereo camera needs a width of at least 2"),
+            ("- add: camera\n  projection: cylindrical\n  width: 1\n  height: 1\n", 2, "unknown projection `cylindrical`"),
+            ("- add: camera\n  projection: orthographic\n  field-of-view: 1\n", 3, "unknown key `field-of-view`"),
+            ("- add: camera\n  width: 1\n  height: 1\n  field-of-view: 1\n  aperture-blades: 2\n", 5, "an aperture needs at least 3 blades"),
+            ("- add: camera\n  width: 1\n  height: 1\n  field-of-view: 1\n  shutter: [0]\n", 5, "expected a list of two numbers"),
+            ("- add: sphere\n  material:\n    model: velvet\n", 3, "unknown material model `velvet`"),
+            ("- add: sphere\n  material:\n    model: lambert\n    roughness: 1\n", 4, "unknown key `roughness`"),
+            ("- add: sphere\n  material:\n    model: conductor\n    metal: tin\n", 4, "unknown metal `tin`"),
+            ("- add: sphere\n  material:\n    model: dielectric\n    ior: 1.5\n    dispersion: bk7\n", 3, "a dielectric needs one of an `ior` or a `dispersion`"),
+            ("- add: sphere\n  material:\n    model: dielectric\n    dispersion:\n      sellmeier:\n        b: [1, 2]\n        c: [1, 2, 3]\n", 6, "expected a list of 3 numbers"),
+            ("- add: sphere\n  material:\n    model: dielectric\n    dispersion:\n      cauchy: [1.5, 0.004]\n      sellmeier:\n        b: [1, 2, 3]\n        c: [1, 2, 3]\n", 5, "a dispersion needs one of `cauchy` or `sellmeier`"),
+            ("- add: sphere\n  material:\n    bump:\n      normal-map: a.png\n      noise: {}\n", 4, "bumps need one of a `normal-map`, a `height-map` or `noise`"),
+            ("- add: sphere\n  material:\n    bump:\n      normal-map: a.png\n      scale: 2\n", 5, "a normal map has no `scale`"),
+            ("- add: environment\n  color: [1, 1, 1]\n  top: [1, 1, 1]\n", 1, "an environment needs one of a `file`, a `color` or a `top` and `bottom`"),
+            ("- add: environment\n  top: [1, 1, 1]\n", 1, "missing `bottom`"),
+            ("- add: environment\n  color: [1, 1, 1]\n  samples: 0\n", 3, "expected at least 1"),
+            ("- add: sky\n  sun: [0, 1, 0]\n  turbidity: 40\n", 3, "the turbidity has to be between 2 and 20"),
+            ("- add: sky\n  sun: [0, 1, 0]\n  turbidity: 1.5\n", 3, "the turbidity has to be between 2 and 20"),
+            ("- add: sky\n  sun: [0, 0, 0]\n", 2, "a direction can't be zero"),
+            ("- add: sky\n  sun: [0, 1, 0]\n  samples: 0\n", 3, "expected at least 1"),
+            ("- add: fog\n  anisotropy: 2\n", 2, "the anisotropy has to be between -1 and 1"),
+            ("- add: fog\n  scattering: [0.1, -0.1, 0.1]\n", 2, "the scattering can't be negative"),
+            ("- add: volume\n  boundary:\n    add: cube\n  absorption: [-1, 0, 0]\n", 4, "the absorption can't be negative"),
+            ("- add: volume\n  boundary:\n    add: cube\n  density: -0.5\n", 4, "the density can't be negative"),
+            ("- add: volume\n  boundary:\n    add: cube\n  density:\n    grid:\n      size: [2, 1, 1]\n      values: [1, -1]\n", 7, "the density can't be negative"),
+            ("- add: group\n  children:\n    - add: fog\n", 3, "only shapes can be added to a group"),
+            ("- add: group\n  children:\n    - add: volume\n      boundary:\n        add: cube\n", 3, "only shapes can be added to a group"),
+            ("- add: volume\n  boundary:\n    add: cube\n  density:\n    grid:\n      size: [2, 1, 1]\n      values: [1]\n", 7, "expected 2 values for a grid of 2x1x1"),
+            ("- for: i\n  to: 1e18\n  do: []\n", 1, "a loop can't repeat more than 1000000 times"),
+            ("- define: x\n  value:\n    keyframes:\n      - { time: 0, value: 1, interpolation: bounce }\n", 4, "unknown interpolation `bounce`"),
+        ];
+
+        for (source, line, message) in cases {
+            // When
+            let error = Scene::parse(source).unwrap_err();
+
+            // Then
+            assert_eq!(error, SceneError::new(line, message));
+        }
+    }
+
+    #[test]
+    fn cameras_can_use_other_projections() {
+        // Given
+        let orthographic = "
+- add: camera
+  projection: orthographic
+  width: 20
+  height: 10
+  view-size: 4
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+";
+        let stereo = "
+- add: camera
+  projection: equirectangular
+  width: 400
+  height: 100
+  eye-separation: 0.1
+  from: [0, 0, 0]
+  to: [0, 0, -1]
+";
+
+        // When
+        let orthographic = Scene::parse(orthographic).unwrap().camera;
+        let stereo = Scene::parse(stereo).unwrap().camera;
+
+        // Then
+        assert_eq!(orthographic.ray_through(0.0, 0.0).unwrap().with_time(0.0), Ray::new(Point::new(-2.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0)));
+        assert_eq!((stereo.hsize(), stereo.vsize()), (400, 100));
+        assert_eq!(stereo.ray_through(150.0, 50.0).unwrap().with_time(0.0), Ray::new(Point::new(0.0, 0.0, -0.05), Vector::new(-1.0, 0.0, 0.0)));
+        assert_eq!(stereo.ray_through(350.0, 50.0).unwrap().with_time(0.0), Ray::new(Point::new(0.0, 0.0, 0.05), Vector::new(-1.0, 0.0, 0.0)));
+    }
+
+    #[test]
+    fn cameras_can_focus_on_what_they_see() {
+        // Given
+        let source = "
+- add: camera
+  width: 11
+  height: 11
+  field-of-view: 1
+  aperture: 0.2
+  aperture-blades: 5
+  focal-distance: auto
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+- add: sphere
+";
+
+        // When
+        let scene = Scene::parse(source).unwrap();
+
+        // Then
+        for _ in 0..10 {
+            let ray = scene.camera.ray_for_pixel(5, 5).unwrap();
+            assert_eq!(ray.position((-1.0 - ray.origin.z()) / ray.direction.z()), Point::new(0.0, 0.0, -1.0));
+        }
+    }
+
+    #[test]
+    fn shapes_can_move_while_the_shutter_is_open() {
+        // Given
+        let source = "
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  shutter: [0.25, 0.75]
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+- add: sphere
+  transform:
+    - [translate, -1, 0, 0]
+  end-transform:
+    - [translate, 1, 0, 0]
+";
+
+        // When
+        let scene = Scene::parse(source).unwrap();
+
+        // Then
+        assert_eq!(scene.camera.shutter(), (0.25, 0.75));
+        assert_eq!(scene.world.objects()[0].end_transform(), Some(&Matrix4::translation(1.0, 0.0, 0.0)));
+        for _ in 0..10 {
+            let time = scene.camera.ray_for_pixel(5, 5).unwrap().time;
+            assert!((0.25..0.75).contains(&time));
+        }
+    }
+
+    #[test]
+    fn animated_values_take_their_value_at_the_time_of_the_scene() {
+        // Given
+        let source = "
+- define: height
+  value:
+    keyframes:
+      - { time: 0, value: 0 }
+      - { time: 2, value: 4 }
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from:
+    keyframes:
+      - { time: 0, value: [0, 0, -5] }
+      - { time: 2, value: [0, 0, -10], interpolation: ease }
+  to: [0, 0, 0]
+- add: light
+  at: [0, height, 0]
+  intensity:
+    keyframes:
+      - { time: 1, value: [0, 0, 0], interpolation: [bezier, 0.25, 0.25, 0.75, 0.75] }
+      - { time: 2, value: [1, 0.5, 0] }
+- add: sphere
+  transform:
+    - [rotate-y, time * pi]
+  material:
+    color:
+      keyframes:
+        - { time: 0, value: [1, 1, 1] }
+- add: cube
+  transform:
+    keyframes:
+      - { time: 0, value: [[translate, 0, 0, 0]] }
+      - { time: 2, value: [[scale, 3, 3, 3], [translate, 4, 0, 0]] }
+";
+
+        // When
+        let start = Scene::parse_at(source, 0.0).unwrap();
+        let middle = Scene::parse_at(source, 1.5).unwrap();
+        let end = Scene::parse_at(source, 3.0).unwrap();
+
+        // Then
+        assert_eq!(*start.camera.transform(), Matrix4::view_transform(Point::new(0.0, 0.0, -5.0), Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)));
+        assert_eq!(*end.camera.transform(), Matrix4::view_transform(Point::new(0.0, 0.0, -10.0), Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)));
+        assert_eq!(start.world.lights[0].intensity(), Color::new(0.0, 0.0, 0.0));
+        assert_eq!(middle.world.lights[0].intensity(), Color::new(0.5, 0.25, 0.0));
+        assert_eq!(middle.world.lights[0].samples(Point::new(0.0, 0.0, 0.0))[0].direction, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(*middle.world.objects()[0].transform(), Matrix4::rotation_y(1.5 * PI));
+        assert_eq!(middle.world.objects()[0].material().color, Color::new(1.0, 1.0, 1.0));
+        assert_eq!(*end.world.objects()[1].transform(), Matrix4::translation(4.0, 0.0, 0.0) * Matrix4::scaling(3.0, 3.0, 3.0));
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn scenes_survive_a_json_round_trip() {
+        use crate::render::{render, RenderSettings};
+
+        // Given
+        let source = "
+- add: camera
+  width: 40
+  height: 30
+  field-of-view: 1.0
+  from: [0, 2, -6]
+  to: [0, 0.5, 0]
+- add: area-light
+  corner: [-4, 6, -6]
+  uvec: [2, 0, 0]
+  usteps: 2
+  vvec: [0, 2, 0]
+  vsteps: 2
+  jitter: false
+  intensity: [0.8, 0.8, 0.8]
+- add: spot-light
+  at: [0, 5, 0]
+  direction: [0, -1, 0]
+  inner-angle: 0.3
+  outer-angle: 0.5
+  intensity: [0.5, 0.5, 0.5]
+  falloff: inverse-square
+- add: plane
+  material:
+    color: [0.8, 0.8, 0.7]
+    bump:
+      noise: { frequency: 2 }
+      scale: 0.05
+- add: cylinder
+  transform:
+    - [scale, 0.2, 1, 0.2]
+    - [translate, 2, 0, 2]
+- add: group
+  transform:
+    - [translate, -1, 0.5, 0]
+  material:
+    color: [1, 0.2, 0.2]
+  children:
+    - add: csg
+      operation: difference
+      left:
+        add: cube
+        transform:
+          - [scale, 0.5, 0.5, 0.5]
+      right:
+        add: sphere
+        transform:
+          - [scale, 0.65, 0.65, 0.65]
+    - add: cylinder
+      minimum: 0
+      maximum: 1
+      closed: true
+      transform:
+        - [scale, 0.3, 1, 0.3]
+        - [translate, 1.5, -0.5, 0]
+";
+        let mut scene = Scene::parse(source).unwrap();
+        scene.world.add_object(Box::new(crate::shape::triangle::Triangle::new(
+            Point::new(0.5, 0.0, -1.0),
+            Point::new(1.5, 0.0, -1.0),
+            Point::new(1.0, 1.5, -1.0),
+        ).with_uvs([(0.0, 0.0), (1.0, 0.0), (0.5, 1.0)])));
+        scene.world.build_bvh();
+
+        // When
+        let json = serde_json::to_string(&scene).unwrap();
+        let loaded: Scene = serde_json::from_str(&json).unwrap();
+
+        // Then
+        assert_eq!(serde_json::to_string(&loaded).unwrap(), json);
+        let settings = RenderSettings::default();
+        let (expected, actual) = (render(scene.camera.as_ref(), &scene.world, &settings), render(loaded.camera.as_ref(), &loaded.world, &settings));
+        for y in 0..30 {
+            for x in 0..40 {
+                assert_eq!(expected[(x, y)].to_rgb(), actual[(x, y)].to_rgb(), "pixel {}, {}", x, y);
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn scenes_can_be_written_by_hand_in_json() {
+        // Given
+        let json = r#"{
+            "camera": { "type": "perspective", "data": { "hsize": 10, "vsize": 10 }, "field_of_view": 1.0 },
+            "world": {
+                "objects": [
+                    { "type": "sphere", "data": { "material": { "color": { "r": 1, "g": 0, "b": 0 }, "ambient": 0.1, "diffuse": 0.9, "specular": 0.9, "shininess": 200 } } },
+                    { "type": "cylinder", "data": { "transform": [[1, 0, 0, 2], [0, 1, 0, 0], [0, 0, 1, 0], [0, 0, 0, 1]] } }
+                ],
+                "lights": [
+                    { "type": "point-light", "position": { "x": -10, "y": 10, "z": -10 }, "intensity": { "r": 1, "g": 1, "b": 1 }, "falloff": "none" }
+                ]
+            }
+        }"#;
+
+        // When
+        let scene: Scene = serde_json::from_str(json).unwrap();
+
+        // Then
+        assert_eq!(*scene.camera.transform(), Matrix4::identity());
+        assert_eq!(scene.world.objects()[0].material().color, Color::new(1.0, 0.0, 0.0));
+        assert_eq!(*scene.world.objects()[1].transform(), Matrix4::translation(2.0, 0.0, 0.0));
+        assert!(!scene.world.objects()[1].bounds().is_finite());
+        assert_eq!(scene.world.lights.len(), 1);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn light_directions_are_normalized_when_deserialized() {
+        // Given
+        let directional = r#"{ "type": "directional-light", "direction": { "x": 0, "y": -2, "z": 0 }, "intensity": { "r": 1, "g": 1, "b": 1 } }"#;
+        let spot = r#"{
+            "type": "spot-light", "position": { "x": 0, "y": 5, "z": 0 }, "direction": { "x": 0, "y": 0, "z": 3 },
+            "inner_angle": 0.2, "outer_angle": 0.4, "intensity": { "r": 1, "g": 1, "b": 1 }, "falloff": "inverse-square"
+        }"#;
+
+        // When
+        let directional: Box<dyn Light> = serde_json::from_str(directional).unwrap();
+        let spot: Box<dyn Light> = serde_json::from_str(spot).unwrap();
+
+        // Then
+        assert_eq!(directional.samples(Point::zero())[0].direction, Vector::new(0.0, 1.0, 0.0));
+        assert!(serde_json::to_string(&spot).unwrap().contains(r#""direction":{"x":0.0,"y":0.0,"z":1.0}"#));
+        assert!(serde_json::to_string(&spot).unwrap().contains(r#""falloff":"inverse-square""#));
+        assert!(serde_json::from_str::<Box<dyn Light>>(r#"{ "type": "directional-light", "direction": { "x": 0, "y": 0, "z": 0 }, "intensity": { "r": 1, "g": 1, "b": 1 } }"#).is_err());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn matrices_of_the_wrong_size_are_rejected() {
+        // Then
+        assert!(serde_json::from_str::<Matrix4>("[[1, 0], [0, 1]]").is_err());
+        assert_eq!(serde_json::from_str::<Matrix4>("[[1, 0, 0, 0], [0, 1, 0, 0], [0, 0, 1, 0], [0, 0, 0, 1]]").unwrap(), Matrix4::identity());
+    }
+
+    #[test]
+    fn variables_expressions_and_loops() {
+        // Given
+        let source = "
+- define: radius
+  value: 0.5
+- define: diameter
+  value: radius * 2
+- add: camera
+  width: 8 * 10
+  height: 60
+  field-of-view: pi / 3
+  from: [0, diameter, -5]
+  to: [0, 0, 0]
+- for: i
+  to: 3
+  do:
+    - add: sphere
+      transform:
+        - [scale, radius, radius, radius]
+        - [translate, i * diameter, 0, 0]
+- add: group
+  children:
+    - for: angle
+      from: 0
+      to: tau
+      step: pi / 2
+      do:
+        - add: cube
+          transform:
+            - [rotate-y, angle]
+";
+
+        // When
+        let scene = Scene::parse(source).unwrap();
+
+        // Then
+        assert_eq!(scene.camera.hsize(), 80);
+        assert_eq!(scene.camera.local_ray(0.0, 0.0), PerspectiveCamera::new(80, 60, PI / 3.0).local_ray(0.0, 0.0));
+        assert_eq!(scene.world.objects().len(), 4);
+        assert_eq!(*scene.world.objects()[2].transform(), Matrix4::translation(2.0, 0.0, 0.0) * Matrix4::scaling(0.5, 0.5, 0.5));
+        assert_eq!(scene.world.objects()[3].children().len(), 4);
+        assert_eq!(*scene.world.objects()[3].children()[3].transform(), Matrix4::rotation_y(3.0 * PI / 2.0));
+    }
+
+    #[test]
+    fn loop_variables_only_exist_inside_the_loop() {
+        // Given
+        let source = "
+- for: i
+  to: 2
+  do:
+    - add: sphere
+- add: sphere
+  transform:
+    - [translate, i, 0, 0]
+";
+
+        // When
+        let error = Scene::parse(source).unwrap_err();
+
+        // Then
+        assert_eq!(error, SceneError::new(8, "unknown variable `i`"));
+    }
+
+    #[test]
+    fn environments_light_the_scene_from_all_around() {
+        // Given
+        let dir = std::env::temp_dir().join(format!("punytracer-environment-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut sky = b"#?RADIANCE\nFORMAT=32-bit_rle_rgbe\n\n-Y 1 +X 2\n".to_vec();
+        sky.extend([128, 128, 128, 129, 128, 0, 0, 129]);
+        fs::write(dir.join("sky.hdr"), sky).unwrap();
+        let source = "\
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+- add: environment
+  file: sky.hdr
+  intensity: [2, 2, 2]
+  transform:
+    - [rotate-y, pi]
+";
+        fs::write(dir.join("scene.yml"), source).unwrap();
+        fs::write(dir.join("missing.yml"), source.replace("sky.hdr", "night.hdr")).unwrap();
+
+        // When
+        let scene = Scene::load(dir.join("scene.yml")).unwrap();
+        let missing = Scene::load(dir.join("missing.yml")).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Then: the red half of the sky is turned from -x to +x.
+        let background = |x| scene.world.background(&Ray::new(Point::zero(), Vector::new(x, 0.0, 0.0)));
+        assert_eq!(background(1.0), Color::new(2.0, 0.0, 0.0));
+        assert_eq!(background(-1.0), Color::new(2.0, 2.0, 2.0));
+        assert_eq!(missing.line, 8);
+        assert!(missing.message.starts_with("could not read"));
+    }
+
+    #[test]
+    fn skies_come_with_their_sun() {
+        // Given
+        let source = "
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+- add: sky
+  sun: [0, 1, 1]
+  turbidity: 2.5
+  intensity: [2, 2, 2]
+";
+
+        // When
+        let scene = Scene::parse(source).unwrap();
+        let sky = PreethamSky::new(Vector::new(0.0, 1.0, 1.0), 2.5, Color::new(0.2, 0.2, 0.2));
+
+        // Then
+        let sun = scene.world.lights[1].samples(Point::zero())[0];
+        assert_eq!(sun.direction, Vector::new(0.0, 1.0, 1.0).normalize());
+        assert_eq!(sun.intensity, sky.sun_irradiance() * 2.0);
+        let up = Ray::new(Point::zero(), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(scene.world.background(&up), sky.radiance(up.direction) * 2.0);
+    }
+
+    #[test]
+    fn fog_and_volumes_fill_the_world_with_media() {
+        // Given
+        let source = "
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+- add: fog
+  absorption: [0.1, 0.1, 0.1]
+- add: volume
+  boundary:
+    add: cube
+    transform:
+      - [scale, 2, 2, 2]
+  scattering: [1, 0.5, 0.5]
+  anisotropy: 0.3
+  density:
+    grid:
+      size: [1, 1, 2]
+      values: [0, 1]
+";
+
+        // When
+        let scene = Scene::parse(source).unwrap();
+        let volume = &scene.world.volumes()[0];
+
+        // Then
+        assert_eq!(scene.world.fog, Some(Medium::new(Color::new(0.1, 0.1, 0.1), Color::default(), 0.0)));
+        assert_eq!(volume.medium.phase.g, 0.3);
+        assert_eq!(volume.boundary.bounds(), Cube::new().bounds());
+        let spans = scene.world.media_along(&Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0)), f64::INFINITY);
+        assert_eq!(spans.iter().map(|s| (s.start, s.end)).collect::<Vec<_>>(), vec![(3.0, 7.0), (0.0, 7.0)]);
+        assert_eq!(spans[0].coefficients(Point::new(0.0, 0.0, 1.0)), (Color::default(), Color::new(0.75, 0.375, 0.375)));
+    }
+
+    #[test]
+    fn including_files_relative_to_the_including_file() {
+        // Given
+        let dir = std::env::temp_dir().join(format!("punytracer-include-{}", std::process::id()));
+        fs::create_dir_all(dir.join("parts")).unwrap();
+        fs::write(dir.join("scene.yml"), "\
+- include: parts/camera.yml
+- include: parts/materials.yml
+- add: sphere
+  material: red
+").unwrap();
+        fs::write(dir.join("parts/camera.yml"), "\
+- add: camera
+  width: 10
+  height: 10
+  field-of-view: 1
+  from: [0, 0, -5]
+  to: [0, 0, 0]
+").unwrap();
+        fs::write(dir.join("parts/materials.yml"), "\
+- include: colors.yml
+- define: red
+  value:
+    color: [redness, 0, 0]
+").unwrap();
+        fs::write(dir.join("parts/colors.yml"), "- define: redness\n  value: 0.9\n").unwrap();
+        fs::write(dir.join("broken.yml"), "- include: parts/broken.yml\n").unwrap();
+        fs::write(dir.join("parts/broken.yml"), "- define: x\n  value: 1\n- add: sphere\n  material: blue\n").unwrap();
+        fs::write(dir.join("cycle.yml"), "- include: cycle.yml\n").unwrap();
+
+        // When
+        let scene = Scene::load(dir.join("scene.yml")).unwrap();
+        let broken = Scene::load(dir.join("broken.yml")).unwrap_err();
+        let cycle = Scene::load(dir.join("cycle.yml")).unwrap_err();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Then
+        assert_eq!(scene.world.objects()[0].material().color, Color::new(0.9, 0.0, 0.0));
+        assert_eq!(broken.file, Some(dir.join("parts/broken.yml")));
+        assert_eq!(broken.line, 4);
+        assert_eq!(broken.to_string(), format!("{}:4: `blue` has not been defined", dir.join("parts/broken.yml").display()));
+        assert_eq!(cycle.line, 1);
+        assert!(cycle.message.ends_with("cycle.yml includes itself"));
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+use std::path::Path;
+use std::rc::Rc;
+
+use yaml_rust2::parser::{Event, MarkedEventReceiver, Parser};
+use yaml_rust2::scanner::Marker;
+
+use crate::scene::SceneError;
+
+/// A YAML value along with the file and line it starts on, so that errors can point at it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Node {
+    pub value: Value,
+    pub line: usize,
+    pub file: Option<Rc<Path>>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Scalar(String),
+    List(Vec<Node>),
+    Map(Vec<(String, Node)>),
+}
+
+impl Node {
+    pub fn new(value: Value, line: usize) -> Self {
+        Self { value, line, file: None, }
+    }
+
+    /// Parses the first document in `source`, which is an empty list if there is none.
+    pub fn parse(source: &str) -> Result<Node, SceneError> {
+        Self::parse_from(source, None)
+    }
+
+    /// Like `parse`, but remembers that the nodes come from `file`.
+    pub fn parse_file(source: &str, file: &Path) -> Result<Node, SceneError> {
+        Self::parse_from(source, Some(Rc::from(file)))
+    }
+
+    fn parse_from(source: &str, file: Option<Rc<Path>>) -> Result<Node, SceneError> {
+        let mut builder = Builder { file: file.clone(), ..Builder::default() };
+        Parser::new_from_str(source)
+            .load(&mut builder, false)
+            .map_err(|e| error_at(&file, e.marker().line(), e.info()))?;
+
+        if let Some(error) = builder.error {
+            return Err(error);
+        }
+
+        Ok(builder.document.unwrap_or(Node { file, ..Node::new(Value::List(vec![]), 1) }))
+    }
+
+    pub fn error(&self, message: impl Into<String>) -> SceneError {
+        error_at(&self.file, self.line, message)
+    }
+
+    pub fn as_str(&self) -> Result<&str, SceneError> {
+        match &self.value {
+            Value::Scalar(s) => Ok(s),
+            _                => Err(self.error("expected a plain value")),
+        }
+    }
+
+    pub fn as_f64(&self) -> Result<f64, SceneError> {
+        let s = self.as_str()?;
+        s.parse().map_err(|_| self.error(format!("expected a number, found `{}`", s)))
+    }
+
+    pub fn as_usize(&self) -> Result<usize, SceneError> {
+        let s = self.as_str()?;
+        s.parse().map_err(|_| self.error(format!("expected a whole number, found `{}`", s)))
+    }
+
+    pub fn as_bool(&self) -> Result<bool, SceneError> {
+        match self.as_str()? {
+            "true"  => Ok(true),
+            "false" => Ok(false),
+            s       => Err(self.error(format!("expected true or false, found `{}`", s))),
+        }
+    }
+
+    pub fn as_list(&self) -> Result<&[Node], SceneError> {
+        match &self.value {
+            Value::List(items) => Ok(items),
+            _                  => Err(self.error("expected a list")),
+        }
+    }
+
+    pub fn as_map(&self) -> Result<&[(String, Node)], SceneError> {
+        match &self.value {
+            Value::Map(entries) => Ok(entries),
+            _                   => Err(self.error("expected a mapping")),
+        }
+    }
+
+    /// The value of `key` if this is a mapping that contains it.
+    pub fn get(&self, key: &str) -> Option<&Node> {
+        match &self.value {
+            Value::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _                   => None,
+        }
+    }
+
+    /// Like `get`, but `key` has to be present.
+    pub fn require(&self, key: &str) -> Result<&Node, SceneError> {
+        self.get(key).ok_or_else(|| self.error(format!("missing `{}`", key)))
+    }
+
+    /// Fails on the first key of this mapping that is not in `allowed`.
+    pub fn check_keys(&self, allowed: &[&str]) -> Result<(), SceneError> {
+        match self.as_map()?.iter().find(|(k, _)| !allowed.contains(&k.as_str())) {
+            Some((key, value)) => Err(value.error(format!("unknown key `{}`", key))),
+            None               => Ok(()),
+        }
+    }
+
+    /// This mapping with the entries of `other` added, replacing those with the same key.
+    pub fn merge(&self, other: &Node) -> Result<Node, SceneError> {
+        let mut entries = self.as_map()?.to_vec();
+        for (key, value) in other.as_map()? {
+            match entries.iter_mut().find(|(k, _)| k == key) {
+                Some(entry) => entry.1 = value.clone(),
+                None        => entries.push((key.clone(), value.clone())),
+            }
+        }
+
+        Ok(Node { value: Value::Map(entries), line: other.line, file: other.file.clone() })
+    }
+}
+
+fn error_at(file: &Option<Rc<Path>>, line: usize, message: impl Into<String>) -> SceneError {
+    SceneError {
+        file: file.as_deref().map(Path::to_path_buf),
+        ..SceneError::new(line, message)
+    }
+}
+
+/// Assembles nodes from the events of the YAML parser.
+#[derive(Default)]
+struct Builder {
+    file: Option<Rc<Path>>,
+    stack: Vec<(Node, usize, Option<String>)>,
+    anchors: HashMap<usize, Node>,
+    document: Option<Node>,
+    error: Option<SceneError>,
+}
+
+impl Builder {
+    fn node(&self, value: Value, line: usize) -> Node {
+        Node { file: self.file.clone(), ..Node::new(value, line) }
+    }
+
+    fn insert(&mut self, node: Node, anchor: usize) {
+        if anchor > 0 {
+            self.anchors.insert(anchor, node.clone());
+        }
+
+        match self.stack.last_mut() {
+            Some((Node { value: Value::List(items), .. }, _, _)) => items.push(node),
+            Some((Node { value: Value::Map(entries), .. }, _, key)) => match key.take() {
+                Some(key) => entries.push((key, node)),
+                None      => match node.value {
+                    Value::Scalar(s) => *key = Some(s),
+                    _                => { self.error.get_or_insert(node.error("keys must be plain values")); }
+                },
+            },
+            Some(_) => unreachable!("only lists and mappings are pushed on the stack"),
+            None    => self.document = Some(node),
+        }
+    }
+}
+
+impl MarkedEventReceiver for Builder {
+    fn on_event(&mut self, event: Event, mark: Marker) {
+        let line = mark.line();
+
+        match event {
+            Event::Scalar(s, _, anchor, _)    => self.insert(self.node(Value::Scalar(s), line), anchor),
+            Event::SequenceStart(anchor, _)   => self.stack.push((self.node(Value::List(vec![]), line), anchor, None)),
+            Event::MappingStart(anchor, _)    => self.stack.push((self.node(Value::Map(vec![]), line), anchor, None)),
+            Event::SequenceEnd | Event::MappingEnd => {
+                let (node, anchor, _) = self.stack.pop().expect("the parser balances starts and ends");
+                self.insert(node, anchor);
+            }
+            Event::Alias(anchor) => match self.anchors.get(&anchor).cloned() {
+                Some(node) => self.insert(Node { line, ..node }, 0),
+                None       => { self.error.get_or_insert(error_at(&self.file, line, "unknown alias")); }
+            },
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_nested_values_with_their_lines() {
+        // Given
+        let source = "- add: sphere\n\
+                      \x20 at: [1, 2.5, -3]\n\
+                      - define: x\n";
+
+        // When
+        let node = Node::parse(source).unwrap();
+        let items = node.as_list().unwrap();
+
+        // Then
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].require("add").unwrap().as_str().unwrap(), "sphere");
+        let at = items[0].require("at").unwrap();
+        assert_eq!(at.line, 2);
+        assert_eq!(at.as_list().unwrap()[1].as_f64().unwrap(), 2.5);
+        assert_eq!(items[1].line, 3);
+    }
+
+    #[test]
+    fn errors_point_at_the_offending_line() {
+        // Given
+        let node = Node::parse("a: 1\nb: two\n").unwrap();
+
+        // When
+        let error = node.require("b").unwrap().as_f64().unwrap_err();
+
+        // Then
+        assert_eq!(error.line, 2);
+        assert_eq!(node.require("c").unwrap_err().line, 1);
+        assert_eq!(node.check_keys(&["a"]).unwrap_err().line, 2);
+        assert_eq!(Node::parse("a: [1, 2\n").unwrap_err().line, 2);
+    }
+
+    #[test]
+    fn merging_mappings() {
+        // Given
+        let base = Node::parse("color: [1, 1, 1]\ndiffuse: 0.7\n").unwrap();
+        let other = Node::parse("diffuse: 0.2\nspecular: 0.1\n").unwrap();
+
+        // When
+        let merged = base.merge(&other).unwrap();
+
+        // Then
+        assert_eq!(merged.as_map().unwrap().len(), 3);
+        assert_eq!(merged.require("diffuse").unwrap().as_f64().unwrap(), 0.2);
+        assert!(merged.get("color").is_some());
+    }
+
+    #[test]
+    fn aliases_repeat_anchored_values() {
+        // Given
+        let node = Node::parse("a: &v [1, 2]\nb: *v\n").unwrap();
+
+        // Then
+        assert_eq!(node.require("b").unwrap().as_list().unwrap().len(), 2);
+    }
+}
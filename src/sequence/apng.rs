@@ -0,0 +1,113 @@
+use std::io::{self, Write};
+
+use crate::canvas::Canvas;
+use crate::sequence::{check_size, frame_rate, SequenceWriter};
+
+/// Writes frames to an animated PNG that loops forever. The number of frames is stored before
+/// the first one, so it has to be known up front.
+pub struct ApngWriter<W: Write> {
+    output: Option<W>,
+    writer: Option<png::Writer<W>>,
+    frame_count: u32,
+    frames_written: u32,
+    fps: f64,
+    size: Option<(u32, u32)>,
+}
+
+impl<W: Write> ApngWriter<W> {
+    pub fn new(output: W, frame_count: u32, fps: f64) -> Self {
+        Self { output: Some(output), writer: None, frame_count, frames_written: 0, fps, size: None, }
+    }
+
+    fn start(&mut self, frame: &Canvas) -> io::Result<png::Writer<W>> {
+        let output = self.output.take().expect("the output is only taken once");
+        let mut encoder = png::Encoder::new(output, frame.width(), frame.height());
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        encoder.set_animated(self.frame_count, 0)?;
+
+        // The delay of a frame is the inverse of the frame rate.
+        let (numerator, denominator) = frame_rate(self.fps);
+        match (u16::try_from(denominator), u16::try_from(numerator)) {
+            (Ok(numerator), Ok(denominator)) => encoder.set_frame_delay(numerator, denominator)?,
+            _                                => encoder.set_frame_delay(1, self.fps.round().clamp(1.0, u16::MAX as f64) as u16)?,
+        }
+
+        Ok(encoder.write_header()?)
+    }
+}
+
+impl<W: Write> SequenceWriter for ApngWriter<W> {
+    fn write_frame(&mut self, frame: &Canvas) -> io::Result<()> {
+        check_size(&mut self.size, frame)?;
+        let writer = match &mut self.writer {
+            Some(writer) => writer,
+            None         => {
+                let writer = self.start(frame)?;
+                self.writer.insert(writer)
+            }
+        };
+
+        writer.write_image_data(&frame.to_rgb())?;
+        self.frames_written += 1;
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match (self.writer.take(), self.frames_written == self.frame_count) {
+            (Some(writer), true) => Ok(writer.finish()?),
+            _                    => Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("{} of the {} frames of the animated PNG were written", self.frames_written, self.frame_count),
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+
+    use super::*;
+
+    #[test]
+    fn writing_an_animated_png() {
+        // Given
+        let mut red = Canvas::new(3, 2);
+        red[(1, 1)] = Color::new(1.0, 0.0, 0.0);
+        let mut output = vec![];
+        let mut writer = ApngWriter::new(&mut output, 2, 25.0);
+
+        // When
+        writer.write_frame(&Canvas::new(3, 2)).unwrap();
+        writer.write_frame(&red).unwrap();
+        writer.finish().unwrap();
+        drop(writer);
+
+        // Then
+        let mut reader = png::Decoder::new(&output[..]).read_info().unwrap();
+        let control = reader.info().animation_control().unwrap();
+        assert_eq!((control.num_frames, control.num_plays), (2, 0));
+        let mut pixels = vec![0; reader.output_buffer_size()];
+        reader.next_frame(&mut pixels).unwrap();
+        assert!(pixels.iter().all(|&p| p == 0));
+        reader.next_frame(&mut pixels).unwrap();
+        let delay = reader.info().frame_control().unwrap();
+        assert_eq!((delay.delay_num, delay.delay_den), (1, 25));
+        assert_eq!(&pixels[12..15], &[255, 0, 0]);
+    }
+
+    #[test]
+    fn all_frames_have_to_be_written() {
+        // Given
+        let mut output = vec![];
+        let mut writer = ApngWriter::new(&mut output, 3, 24.0);
+
+        // When
+        writer.write_frame(&Canvas::new(2, 2)).unwrap();
+
+        // Then
+        assert!(writer.finish().is_err());
+    }
+}
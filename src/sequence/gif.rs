@@ -0,0 +1,99 @@
+use std::io::{self, Write};
+
+use crate::canvas::Canvas;
+use crate::sequence::{check_size, SequenceWriter};
+
+/// How hard the quantizer works to find a palette, from 1 (best) to 30 (fastest).
+const QUANTIZER_SPEED: i32 = 10;
+
+/// Writes frames to an animated GIF that loops forever. Each frame gets its own palette of 256
+/// colors, quantized from its pixels. GIF stores delays in hundredths of a second, so the frame
+/// rate is rounded to the nearest one that can be represented.
+pub struct GifWriter<W: Write> {
+    output: Option<W>,
+    encoder: Option<gif::Encoder<W>>,
+    delay: u16,
+    size: Option<(u32, u32)>,
+}
+
+impl<W: Write> GifWriter<W> {
+    pub fn new(output: W, fps: f64) -> Self {
+        Self {
+            output: Some(output),
+            encoder: None,
+            delay: (100.0 / fps).round().clamp(1.0, u16::MAX as f64) as u16,
+            size: None,
+        }
+    }
+}
+
+impl<W: Write> SequenceWriter for GifWriter<W> {
+    fn write_frame(&mut self, frame: &Canvas) -> io::Result<()> {
+        check_size(&mut self.size, frame)?;
+        let (width, height) = match (u16::try_from(frame.width()), u16::try_from(frame.height())) {
+            (Ok(width), Ok(height)) => (width, height),
+            _                       => return Err(io::Error::new(io::ErrorKind::InvalidInput, "a GIF can be at most 65535 pixels wide and high")),
+        };
+
+        let encoder = match &mut self.encoder {
+            Some(encoder) => encoder,
+            None          => {
+                let output = self.output.take().expect("the output is only taken once");
+                let mut encoder = gif::Encoder::new(output, width, height, &[]).map_err(io::Error::other)?;
+                encoder.set_repeat(gif::Repeat::Infinite).map_err(io::Error::other)?;
+                self.encoder.insert(encoder)
+            }
+        };
+
+        let mut gif_frame = gif::Frame::from_rgb_speed(width, height, &frame.to_rgb(), QUANTIZER_SPEED);
+        gif_frame.delay = self.delay;
+        encoder.write_frame(&gif_frame).map_err(io::Error::other)
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        match self.encoder.take() {
+            Some(encoder) => encoder.into_inner()?.flush(),
+            None          => Err(io::Error::new(io::ErrorKind::InvalidInput, "a GIF needs at least one frame")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+
+    use super::*;
+
+    #[test]
+    fn writing_an_animated_gif() {
+        // Given
+        let mut frames = [Canvas::new(4, 4), Canvas::new(4, 4)];
+        for y in 0..4 {
+            for x in 0..4 {
+                frames[0][(x, y)] = Color::new(1.0, 0.0, 0.0);
+                frames[1][(x, y)] = Color::new(0.0, 0.0, x as f64 / 3.0);
+            }
+        }
+        let mut output = vec![];
+        let mut writer = GifWriter::new(&mut output, 20.0);
+
+        // When
+        for frame in &frames {
+            writer.write_frame(frame).unwrap();
+        }
+        writer.finish().unwrap();
+        drop(writer);
+
+        // Then
+        let mut options = gif::DecodeOptions::new();
+        options.set_color_output(gif::ColorOutput::RGBA);
+        let mut decoder = options.read_info(&output[..]).unwrap();
+        assert_eq!(decoder.repeat(), gif::Repeat::Infinite);
+        let first = decoder.read_next_frame().unwrap().unwrap();
+        assert_eq!(first.delay, 5);
+        assert!(first.buffer.chunks(4).all(|p| p[0] > 250 && p[1] < 5 && p[2] < 5));
+        let second = decoder.read_next_frame().unwrap().unwrap();
+        assert!(second.buffer[0..4][2] < 5 && second.buffer[12..16][2] > 250);
+        assert!(decoder.read_next_frame().unwrap().is_none());
+    }
+}
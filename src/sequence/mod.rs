@@ -0,0 +1,104 @@
+pub mod apng;
+pub mod gif;
+pub mod numbered;
+pub mod y4m;
+
+use std::io;
+use std::path::PathBuf;
+
+use crate::canvas::Canvas;
+
+/// Encodes a sequence of frames of the same size one at a time, so that a long animation never
+/// has to be held in memory.
+pub trait SequenceWriter {
+    fn write_frame(&mut self, frame: &Canvas) -> io::Result<()>;
+
+    /// Completes the output after the last frame.
+    fn finish(&mut self) -> io::Result<()>;
+}
+
+/// The file name for `frame`. The first run of `#` in `pattern` is replaced by the frame number,
+/// padded with zeros to the length of the run. Without one, the number is added before the
+/// extension with four digits.
+pub fn frame_path(pattern: &str, frame: usize) -> PathBuf {
+    match pattern.find('#') {
+        Some(start) => {
+            let width = pattern[start..].find(|c| c != '#').unwrap_or(pattern.len() - start);
+            PathBuf::from(format!("{}{:0width$}{}", &pattern[..start], frame, &pattern[start + width..]))
+        }
+        None => {
+            let path = PathBuf::from(pattern);
+            let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+            let name = match path.extension() {
+                Some(extension) => format!("{}-{:04}.{}", stem, frame, extension.to_string_lossy()),
+                None            => format!("{}-{:04}", stem, frame),
+            };
+            path.with_file_name(name)
+        }
+    }
+}
+
+/// `fps` as a fraction in lowest terms, exact for whole numbers and to a thousandth otherwise.
+pub(crate) fn frame_rate(fps: f64) -> (u32, u32) {
+    let (mut numerator, mut denominator) = match fps.fract() == 0.0 {
+        true  => (fps as u32, 1),
+        false => ((fps * 1000.0).round() as u32, 1000),
+    };
+
+    let (mut a, mut b) = (numerator, denominator);
+    while b != 0 {
+        (a, b) = (b, a % b);
+    }
+    if a > 1 {
+        numerator /= a;
+        denominator /= a;
+    }
+
+    (numerator, denominator)
+}
+
+/// Remembers the size of the first frame and fails on frames of any other size.
+pub(crate) fn check_size(size: &mut Option<(u32, u32)>, frame: &Canvas) -> io::Result<()> {
+    let (width, height) = *size.get_or_insert((frame.width(), frame.height()));
+
+    match (frame.width(), frame.height()) == (width, height) {
+        true  => Ok(()),
+        false => Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("a {}x{} frame in a {}x{} sequence", frame.width(), frame.height(), width, height),
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn numbering_frame_files() {
+        // Then
+        assert_eq!(frame_path("out/frame-####.png", 7), PathBuf::from("out/frame-0007.png"));
+        assert_eq!(frame_path("##.png", 123), PathBuf::from("123.png"));
+        assert_eq!(frame_path("out/clip.png", 12), PathBuf::from("out/clip-0012.png"));
+        assert_eq!(frame_path("clip", 3), PathBuf::from("clip-0003"));
+    }
+
+    #[test]
+    fn frame_rates_as_fractions() {
+        // Then
+        assert_eq!(frame_rate(24.0), (24, 1));
+        assert_eq!(frame_rate(29.97), (2997, 100));
+        assert_eq!(frame_rate(12.5), (25, 2));
+    }
+
+    #[test]
+    fn frames_have_to_be_the_same_size() {
+        // Given
+        let mut size = None;
+
+        // Then
+        assert!(check_size(&mut size, &Canvas::new(4, 3)).is_ok());
+        assert!(check_size(&mut size, &Canvas::new(4, 3)).is_ok());
+        assert_eq!(check_size(&mut size, &Canvas::new(3, 4)).unwrap_err().to_string(), "a 3x4 frame in a 4x3 sequence");
+    }
+}
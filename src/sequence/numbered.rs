@@ -0,0 +1,60 @@
+use std::fs::File;
+use std::io::{self, BufWriter};
+
+use crate::canvas::Canvas;
+use crate::sequence::{check_size, frame_path, SequenceWriter};
+
+/// Writes each frame to its own PNG file, numbered as described by `frame_path`.
+#[derive(Debug)]
+pub struct NumberedPngWriter {
+    pattern: String,
+    frame: usize,
+    size: Option<(u32, u32)>,
+}
+
+impl NumberedPngWriter {
+    pub fn new(pattern: impl Into<String>) -> Self {
+        Self { pattern: pattern.into(), frame: 0, size: None, }
+    }
+}
+
+impl SequenceWriter for NumberedPngWriter {
+    fn write_frame(&mut self, frame: &Canvas) -> io::Result<()> {
+        check_size(&mut self.size, frame)?;
+        let file = File::create(frame_path(&self.pattern, self.frame))?;
+        frame.write_png(BufWriter::new(file))?;
+        self.frame += 1;
+
+        Ok(())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+
+    #[test]
+    fn writing_a_file_per_frame() {
+        // Given
+        let dir = std::env::temp_dir().join(format!("punytracer-numbered-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let mut writer = NumberedPngWriter::new(dir.join("frame-##.png").to_string_lossy());
+
+        // When
+        writer.write_frame(&Canvas::new(4, 3)).unwrap();
+        writer.write_frame(&Canvas::new(4, 3)).unwrap();
+        writer.finish().unwrap();
+        let mut files = fs::read_dir(&dir).unwrap().map(|f| f.unwrap().file_name()).collect::<Vec<_>>();
+        files.sort();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // Then
+        assert_eq!(files, ["frame-00.png", "frame-01.png"]);
+    }
+}
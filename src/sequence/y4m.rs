@@ -0,0 +1,91 @@
+use std::io::{self, Write};
+
+use crate::canvas::Canvas;
+use crate::sequence::{check_size, frame_rate, SequenceWriter};
+
+/// Writes frames as an uncompressed YUV4MPEG2 stream, which video encoders such as ffmpeg and
+/// x264 read directly, for example from a pipe. Colors are converted to Y'CbCr with the BT.601
+/// coefficients in limited range, and the chroma planes are subsampled 4:2:0.
+pub struct Y4mWriter<W: Write> {
+    output: W,
+    fps: f64,
+    size: Option<(u32, u32)>,
+}
+
+impl<W: Write> Y4mWriter<W> {
+    pub fn new(output: W, fps: f64) -> Self {
+        Self { output, fps, size: None, }
+    }
+}
+
+impl<W: Write> SequenceWriter for Y4mWriter<W> {
+    fn write_frame(&mut self, frame: &Canvas) -> io::Result<()> {
+        if self.size.is_none() {
+            let (numerator, denominator) = frame_rate(self.fps);
+            writeln!(
+                self.output,
+                "YUV4MPEG2 W{} H{} F{}:{} Ip A1:1 C420jpeg",
+                frame.width(), frame.height(), numerator, denominator,
+            )?;
+        }
+        check_size(&mut self.size, frame)?;
+
+        let width = frame.width() as usize;
+        let chroma_width = width.div_ceil(2);
+        let chroma_height = (frame.height() as usize).div_ceil(2);
+
+        // Each chroma sample is the average of the (up to) four pixels it covers.
+        let mut luma = Vec::with_capacity(frame.width() as usize * frame.height() as usize);
+        let mut chroma = vec![(0.0, 0.0, 0.0); chroma_width * chroma_height];
+        for (i, pixel) in frame.to_rgb().chunks_exact(3).enumerate() {
+            let [r, g, b] = [pixel[0], pixel[1], pixel[2]].map(|c| c as f64 / 255.0);
+            luma.push((16.0 + 65.481 * r + 128.553 * g + 24.966 * b).round() as u8);
+
+            let (cb, cr, count) = &mut chroma[(i / width / 2) * chroma_width + (i % width) / 2];
+            *cb += -37.797 * r - 74.203 * g + 112.0 * b;
+            *cr += 112.0 * r - 93.786 * g - 18.214 * b;
+            *count += 1.0;
+        }
+
+        self.output.write_all(b"FRAME\n")?;
+        self.output.write_all(&luma)?;
+        self.output.write_all(&chroma.iter().map(|(cb, _, n)| (128.0 + cb / n).round() as u8).collect::<Vec<_>>())?;
+        self.output.write_all(&chroma.iter().map(|(_, cr, n)| (128.0 + cr / n).round() as u8).collect::<Vec<_>>())
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        self.output.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::color::Color;
+
+    use super::*;
+
+    #[test]
+    fn writing_a_y4m_stream() {
+        // Given
+        let mut frame = Canvas::new(3, 1);
+        frame[(0, 0)] = Color::new(1.0, 1.0, 1.0);
+        frame[(1, 0)] = Color::new(1.0, 1.0, 1.0);
+        frame[(2, 0)] = Color::new(1.0, 0.0, 0.0);
+        let mut output = vec![];
+        let mut writer = Y4mWriter::new(&mut output, 29.97);
+
+        // When
+        writer.write_frame(&frame).unwrap();
+        writer.write_frame(&Canvas::new(3, 1)).unwrap();
+        writer.finish().unwrap();
+
+        // Then
+        let header = b"YUV4MPEG2 W3 H1 F2997:100 Ip A1:1 C420jpeg\n";
+        let expected = [
+            &header[..],
+            b"FRAME\n", &[235, 235, 81], &[128, 90], &[128, 240],
+            b"FRAME\n", &[16, 16, 16], &[128, 128], &[128, 128],
+        ].concat();
+        assert_eq!(output, expected);
+    }
+}
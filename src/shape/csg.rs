@@ -0,0 +1,328 @@
+use std::sync::OnceLock;
+
+use crate::bounds::BoundingBox;
+use crate::core::matrix::Matrix4;
+use crate::core::point::Point;
+use crate::core::vector::Vector;
+use crate::intersection::Intersection;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeData};
+
+/// The boolean operation a `Csg` applies to its two shapes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(rename_all = "kebab-case"))]
+pub enum CsgOperation {
+    Union,
+    Intersection,
+    Difference,
+}
+
+impl CsgOperation {
+    /// Whether an intersection with the left (`lhit`) or right shape is part of the surface of
+    /// the result, given whether the ray is currently inside the left and the right shape.
+    pub fn allows(&self, lhit: bool, inl: bool, inr: bool) -> bool {
+        match self {
+            CsgOperation::Union        => (lhit && !inr) || (!lhit && !inl),
+            CsgOperation::Intersection => (lhit && inr) || (!lhit && inl),
+            CsgOperation::Difference   => (lhit && !inr) || (!lhit && inl),
+        }
+    }
+}
+
+/// Constructive solid geometry: two shapes combined by a boolean operation. Either shape may be
+/// an aggregate such as a group or another `Csg`. Like groups, a `Csg` passes its transform and
+/// material down to both shapes. Its bounds are worked out on the first intersection and kept
+/// until the `Csg` changes.
+#[derive(Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "CsgRepr"))]
+pub struct Csg {
+    operation: CsgOperation,
+    shapes: [Box<dyn Shape>; 2],
+    data: ShapeData,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bounds: OnceLock<BoundingBox>,
+}
+
+impl Csg {
+    pub fn new(operation: CsgOperation, left: Box<dyn Shape>, right: Box<dyn Shape>) -> Self {
+        let mut csg = Self {
+            operation,
+            shapes: [left, right],
+            data: ShapeData::new(),
+            bounds: OnceLock::new(),
+        };
+        csg.propagate();
+
+        csg
+    }
+
+    pub fn operation(&self) -> CsgOperation {
+        self.operation
+    }
+
+    pub fn left(&self) -> &dyn Shape {
+        self.shapes[0].as_ref()
+    }
+
+    pub fn right(&self) -> &dyn Shape {
+        self.shapes[1].as_ref()
+    }
+
+    /// Keeps only the intersections that lie on the surface of the combined shape. The
+    /// intersections must be sorted by `t`.
+    pub fn filter_intersections<'a>(&self, xs: Vec<Intersection<'a>>) -> Vec<Intersection<'a>> {
+        let (mut inl, mut inr) = (false, false);
+        let mut result = vec![];
+
+        for x in xs {
+            let lhit = includes(self.left(), x.object);
+
+            if self.operation.allows(lhit, inl, inr) {
+                result.push(x);
+            }
+
+            match lhit {
+                true  => inl = !inl,
+                false => inr = !inr,
+            }
+        }
+
+        result
+    }
+
+    fn propagate(&mut self) {
+        for shape in self.shapes.iter_mut() {
+            shape.inherit(&self.data);
+        }
+        self.bounds = OnceLock::new();
+    }
+}
+
+/// Whether `object` is `shape` itself or one of the shapes it is composed of.
+fn includes(shape: &dyn Shape, object: &dyn Shape) -> bool {
+    std::ptr::addr_eq(shape, object) || shape.children().iter().any(|child| includes(child.as_ref(), object))
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct CsgRepr {
+    operation: CsgOperation,
+    shapes: [Box<dyn Shape>; 2],
+    #[serde(default)]
+    data: ShapeData,
+}
+
+#[cfg(feature = "serde")]
+impl From<CsgRepr> for Csg {
+    fn from(repr: CsgRepr) -> Self {
+        let [left, right] = repr.shapes;
+        let mut csg = Self::new(repr.operation, left, right);
+        csg.data = repr.data;
+        csg.propagate();
+
+        csg
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "csg"))]
+impl Shape for Csg {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        if !self.bounds().intersects(ray) {
+            return vec![];
+        }
+
+        let mut xs = self.shapes.iter().flat_map(|s| s.intersect(ray)).collect::<Vec<_>>();
+        xs.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        self.filter_intersections(xs)
+    }
+
+    fn local_normal_at(&self, _point: Point) -> Vector {
+        unreachable!("Intersections are always with the shapes of a CSG, never with the CSG itself")
+    }
+
+    fn children(&self) -> &[Box<dyn Shape>] {
+        &self.shapes
+    }
+
+    /// Conservative bounds for the result of the operation: a union can reach as far as either
+    /// shape, an intersection only where both shapes overlap and a difference never beyond the
+    /// left shape.
+    fn bounds(&self) -> BoundingBox {
+        *self.bounds.get_or_init(|| {
+            let (left, right) = (self.left().parent_space_bounds(), self.right().parent_space_bounds());
+
+            match self.operation {
+                CsgOperation::Union        => left.merge(&right),
+                CsgOperation::Intersection => left.overlap(&right),
+                CsgOperation::Difference   => left,
+            }
+        })
+    }
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.data.set_transform(transform);
+        self.propagate();
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.data.set_material(material);
+        self.propagate();
+    }
+
+    fn set_end_transform(&mut self, end: Matrix4) {
+        self.data.set_end_transform(end);
+        self.propagate();
+    }
+
+    fn inherit(&mut self, parent: &ShapeData) {
+        self.data.inherit(parent);
+        self.propagate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::tuple::Tuple;
+    use crate::shape::cube::Cube;
+    use crate::shape::group::Group;
+    use crate::shape::sphere::Sphere;
+
+    use super::*;
+
+    fn sphere(transform: Matrix4) -> Box<dyn Shape> {
+        let mut s = Sphere::new();
+        s.set_transform(transform);
+        Box::new(s)
+    }
+
+    #[test]
+    fn csg_is_created_with_an_operation_and_two_shapes() {
+        // Given
+        let mut c = Csg::new(CsgOperation::Union, Box::new(Sphere::new()), Box::new(Cube::new()));
+
+        // When
+        c.set_transform(Matrix4::translation(1.0, 0.0, 0.0));
+
+        // Then
+        assert_eq!(c.operation(), CsgOperation::Union);
+        assert_eq!(c.children().len(), 2);
+        assert_eq!(c.left().world_to_object(Point::new(1.0, 0.0, 0.0)), Point::new(0.0, 0.0, 0.0));
+        assert_eq!(c.right().world_to_object(Point::new(1.0, 0.0, 0.0)), Point::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn evaluating_the_rule_for_a_csg_operation() {
+        // Given
+        let cases = [
+            (CsgOperation::Union, [false, true, false, true, false, false, true, true]),
+            (CsgOperation::Intersection, [true, false, true, false, true, true, false, false]),
+            (CsgOperation::Difference, [false, true, false, true, true, true, false, false]),
+        ];
+
+        for (operation, expected) in cases {
+            for (i, allowed) in expected.into_iter().enumerate() {
+                // When
+                let (lhit, inl, inr) = (i < 4, i % 4 < 2, i % 2 == 0);
+
+                // Then
+                assert_eq!(operation.allows(lhit, inl, inr), allowed, "{:?} {} {} {}", operation, lhit, inl, inr);
+            }
+        }
+    }
+
+    #[test]
+    fn filtering_a_list_of_intersections() {
+        // Given
+        let cases = [
+            (CsgOperation::Union, [0, 3]),
+            (CsgOperation::Intersection, [1, 2]),
+            (CsgOperation::Difference, [0, 1]),
+        ];
+
+        for (operation, [x0, x1]) in cases {
+            let c = Csg::new(operation, Box::new(Sphere::new()), Box::new(Cube::new()));
+            let xs = vec![
+                Intersection::new(1.0, c.left()),
+                Intersection::new(2.0, c.right()),
+                Intersection::new(3.0, c.left()),
+                Intersection::new(4.0, c.right()),
+            ];
+
+            // When
+            let result = c.filter_intersections(xs.clone());
+
+            // Then
+            assert_eq!(result, vec![xs[x0], xs[x1]]);
+        }
+    }
+
+    #[test]
+    fn ray_misses_a_csg_object() {
+        // Given
+        let c = Csg::new(CsgOperation::Union, Box::new(Sphere::new()), Box::new(Cube::new()));
+        let r = Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // Then
+        assert!(c.local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn ray_hits_a_csg_object() {
+        // Given
+        let c = Csg::new(CsgOperation::Union, Box::new(Sphere::new()), sphere(Matrix4::translation(0.0, 0.0, 0.5)));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let xs = c.local_intersect(&r);
+
+        // Then
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert!(std::ptr::addr_eq(xs[0].object, c.left()));
+        assert_eq!(xs[1].t, 6.5);
+        assert!(std::ptr::addr_eq(xs[1].object, c.right()));
+    }
+
+    #[test]
+    fn difference_with_a_group_of_shapes() {
+        // Given
+        let mut holes = Group::new();
+        holes.add_child(sphere(Matrix4::translation(0.0, 0.0, -1.0) * Matrix4::scaling(0.5, 0.5, 0.5)));
+        holes.add_child(sphere(Matrix4::translation(0.0, 0.0, 1.0) * Matrix4::scaling(0.5, 0.5, 0.5)));
+        let c = Csg::new(CsgOperation::Difference, Box::new(Cube::new()), Box::new(holes));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let xs = c.local_intersect(&r);
+
+        // Then
+        assert_eq!(xs.iter().map(|x| x.t).collect::<Vec<_>>(), vec![4.5, 5.5]);
+        assert!(std::ptr::addr_eq(xs[0].object, c.right().children()[0].as_ref()));
+        assert!(std::ptr::addr_eq(xs[1].object, c.right().children()[1].as_ref()));
+    }
+
+    #[test]
+    fn bounds_depend_on_the_operation() {
+        // Given
+        let shapes = || (Box::new(Cube::new()), sphere(Matrix4::translation(1.0, 0.0, 0.0)));
+        let bounds = |operation| {
+            let (left, right) = shapes();
+            Csg::new(operation, left, right).bounds()
+        };
+
+        // Then
+        assert_eq!(bounds(CsgOperation::Union), BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(2.0, 1.0, 1.0)));
+        assert_eq!(bounds(CsgOperation::Intersection), BoundingBox::new(Point::new(0.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0)));
+        assert_eq!(bounds(CsgOperation::Difference), BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0)));
+    }
+}
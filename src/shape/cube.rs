@@ -0,0 +1,160 @@
+use crate::bounds::BoundingBox;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::intersection::Intersection;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeData, SurfaceCoordinates};
+
+/// An axis-aligned cube extending from -1 to 1 on every axis in object space.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct Cube {
+    data: ShapeData,
+}
+
+impl Cube {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "cube"))]
+impl Shape for Cube {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        match self.bounds().intersection_range(ray) {
+            Some((tmin, tmax)) => vec![Intersection::new(tmin, self), Intersection::new(tmax, self)],
+            None               => vec![],
+        }
+    }
+
+    fn local_normal_at(&self, point: Point) -> Vector {
+        let (x, y, z) = (point.x().abs(), point.y().abs(), point.z().abs());
+
+        match x.max(y).max(z) {
+            m if m == x => Vector::new(point.x(), 0.0, 0.0),
+            m if m == y => Vector::new(0.0, point.y(), 0.0),
+            _           => Vector::new(0.0, 0.0, point.z()),
+        }
+    }
+
+    /// Every face has a whole map of its own, which is upright on the sides.
+    fn local_surface_at(&self, point: Point) -> SurfaceCoordinates {
+        let (x, y, z) = (point.x(), point.y(), point.z());
+        let face = |u: f64, v: f64, dpdu: Vector, dpdv: Vector| SurfaceCoordinates::new((u + 1.0) / 2.0, (v + 1.0) / 2.0, dpdu * 2.0, dpdv * 2.0);
+        let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+
+        match ax.max(ay).max(az) {
+            m if m == ax && x > 0.0 => face(-z, y, Vector::new(0.0, 0.0, -1.0), Vector::new(0.0, 1.0, 0.0)),
+            m if m == ax            => face(z, y, Vector::new(0.0, 0.0, 1.0), Vector::new(0.0, 1.0, 0.0)),
+            m if m == ay && y > 0.0 => face(x, -z, Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 0.0, -1.0)),
+            m if m == ay            => face(x, z, Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0)),
+            _ if z > 0.0            => face(x, y, Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            _                       => face(-x, y, Vector::new(-1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_intersects_a_cube() {
+        // Given
+        let c = Cube::new();
+        let cases = [
+            (Point::new(5.0, 0.5, 0.0), Vector::new(-1.0, 0.0, 0.0), 4.0, 6.0),
+            (Point::new(-5.0, 0.5, 0.0), Vector::new(1.0, 0.0, 0.0), 4.0, 6.0),
+            (Point::new(0.5, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0), 4.0, 6.0),
+            (Point::new(0.5, -5.0, 0.0), Vector::new(0.0, 1.0, 0.0), 4.0, 6.0),
+            (Point::new(0.5, 0.0, 5.0), Vector::new(0.0, 0.0, -1.0), 4.0, 6.0),
+            (Point::new(0.5, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 4.0, 6.0),
+            (Point::new(0.0, 0.5, 0.0), Vector::new(0.0, 0.0, 1.0), -1.0, 1.0),
+        ];
+
+        for (origin, direction, t1, t2) in cases {
+            // When
+            let xs = c.local_intersect(&Ray::new(origin, direction));
+
+            // Then
+            assert_eq!(xs.len(), 2);
+            assert_eq!(xs[0].t, t1);
+            assert_eq!(xs[1].t, t2);
+        }
+    }
+
+    #[test]
+    fn ray_misses_a_cube() {
+        // Given
+        let c = Cube::new();
+        let cases = [
+            (Point::new(-2.0, 0.0, 0.0), Vector::new(0.2673, 0.5345, 0.8018)),
+            (Point::new(0.0, -2.0, 0.0), Vector::new(0.8018, 0.2673, 0.5345)),
+            (Point::new(0.0, 0.0, -2.0), Vector::new(0.5345, 0.8018, 0.2673)),
+            (Point::new(2.0, 0.0, 2.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(0.0, 2.0, 2.0), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(2.0, 2.0, 0.0), Vector::new(-1.0, 0.0, 0.0)),
+        ];
+
+        for (origin, direction) in cases {
+            // Then
+            assert!(c.local_intersect(&Ray::new(origin, direction)).is_empty());
+        }
+    }
+
+    #[test]
+    fn normal_on_the_surface_of_a_cube() {
+        // Given
+        let c = Cube::new();
+        let cases = [
+            (Point::new(1.0, 0.5, -0.8), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(-1.0, -0.2, 0.9), Vector::new(-1.0, 0.0, 0.0)),
+            (Point::new(-0.4, 1.0, -0.1), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.3, -1.0, -0.7), Vector::new(0.0, -1.0, 0.0)),
+            (Point::new(-0.6, 0.3, 1.0), Vector::new(0.0, 0.0, 1.0)),
+            (Point::new(0.4, 0.4, -1.0), Vector::new(0.0, 0.0, -1.0)),
+            (Point::new(1.0, 1.0, 1.0), Vector::new(1.0, 0.0, 0.0)),
+            (Point::new(-1.0, -1.0, -1.0), Vector::new(-1.0, 0.0, 0.0)),
+        ];
+
+        for (point, normal) in cases {
+            // Then
+            assert_eq!(c.local_normal_at(point), normal);
+        }
+    }
+
+    #[test]
+    fn every_face_of_a_cube_has_a_whole_map() {
+        // Given
+        let c = Cube::new();
+        let cases = [
+            (Point::new(-0.5, 0.5, -1.0), (0.75, 0.75), Vector::new(-2.0, 0.0, 0.0)),
+            (Point::new(1.0, 0.5, -0.5), (0.75, 0.75), Vector::new(0.0, 0.0, -2.0)),
+            (Point::new(-0.5, 1.0, 0.5), (0.25, 0.25), Vector::new(2.0, 0.0, 0.0)),
+            (Point::new(0.5, -0.5, 1.0), (0.75, 0.25), Vector::new(2.0, 0.0, 0.0)),
+        ];
+
+        for (point, (u, v), dpdu) in cases {
+            // When
+            let surface = c.local_surface_at(point);
+
+            // Then
+            assert_eq!((surface.u, surface.v), (u, v));
+            assert_eq!(surface.dpdu, dpdu);
+            assert_eq!(surface.dpdu.cross(surface.dpdv).normalize(), c.local_normal_at(point));
+        }
+    }
+}
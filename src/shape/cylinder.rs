@@ -0,0 +1,264 @@
+use std::f64::consts::TAU;
+
+use crate::bounds::BoundingBox;
+use crate::core::float::ApproxEq;
+use crate::core::float::EPSILON_OFFSET;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::intersection::Intersection;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeData, SurfaceCoordinates};
+
+/// A cylinder with radius 1 around the y axis, truncated at `minimum` and `maximum` (exclusive)
+/// and optionally capped at both ends.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct Cylinder {
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_infinite"))]
+    pub minimum: f64,
+    #[cfg_attr(feature = "serde", serde(skip_serializing_if = "is_infinite"))]
+    pub maximum: f64,
+    pub closed: bool,
+    data: ShapeData,
+}
+
+impl Cylinder {
+    pub fn new() -> Self {
+        Self {
+            minimum: f64::NEG_INFINITY,
+            maximum: f64::INFINITY,
+            closed: false,
+            data: ShapeData::new(),
+        }
+    }
+
+    /// Whether the ray at `t` is within the radius of the cylinder.
+    fn check_cap(ray: &Ray, t: f64) -> bool {
+        let x = ray.origin.x() + t * ray.direction.x();
+        let z = ray.origin.z() + t * ray.direction.z();
+
+        x * x + z * z <= 1.0
+    }
+
+    fn intersect_caps<'a>(&'a self, ray: &Ray, xs: &mut Vec<Intersection<'a>>) {
+        if !self.closed || ray.direction.y().approx_eq(0.0) {
+            return;
+        }
+
+        for y in [self.minimum, self.maximum] {
+            let t = (y - ray.origin.y()) / ray.direction.y();
+            if Self::check_cap(ray, t) {
+                xs.push(Intersection::new(t, self));
+            }
+        }
+    }
+}
+
+/// Infinite ends are left out when serializing, as JSON has no way to express them.
+#[cfg(feature = "serde")]
+fn is_infinite(value: &f64) -> bool {
+    value.is_infinite()
+}
+
+impl Default for Cylinder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "cylinder"))]
+impl Shape for Cylinder {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut xs = vec![];
+        let a = ray.direction.x().powi(2) + ray.direction.z().powi(2);
+
+        // Rays parallel to the y axis can only hit the caps.
+        if !a.approx_eq(0.0) {
+            let b = 2.0 * ray.origin.x() * ray.direction.x() + 2.0 * ray.origin.z() * ray.direction.z();
+            let c = ray.origin.x().powi(2) + ray.origin.z().powi(2) - 1.0;
+            let discriminant = b * b - 4.0 * a * c;
+
+            if discriminant < 0.0 {
+                return vec![];
+            }
+
+            let t0 = (-b - discriminant.sqrt()) / (2.0 * a);
+            let t1 = (-b + discriminant.sqrt()) / (2.0 * a);
+
+            for t in [t0, t1] {
+                let y = ray.origin.y() + t * ray.direction.y();
+                if self.minimum < y && y < self.maximum {
+                    xs.push(Intersection::new(t, self));
+                }
+            }
+        }
+
+        self.intersect_caps(ray, &mut xs);
+
+        xs
+    }
+
+    fn local_normal_at(&self, point: Point) -> Vector {
+        let distance = point.x().powi(2) + point.z().powi(2);
+
+        match point.y() {
+            y if distance < 1.0 && y >= self.maximum - EPSILON_OFFSET => Vector::new(0.0, 1.0, 0.0),
+            y if distance < 1.0 && y <= self.minimum + EPSILON_OFFSET => Vector::new(0.0, -1.0, 0.0),
+            _ => Vector::new(point.x(), 0.0, point.z()),
+        }
+    }
+
+    /// Maps are wrapped around the side like those of a sphere, and repeat along y. The caps have
+    /// them along x and z like a plane.
+    fn local_surface_at(&self, point: Point) -> SurfaceCoordinates {
+        match self.local_normal_at(point).y() == 0.0 {
+            true  => SurfaceCoordinates::new(
+                0.5 - point.x().atan2(point.z()) / TAU,
+                point.y(),
+                Vector::new(-point.z(), 0.0, point.x()) * TAU,
+                Vector::new(0.0, 1.0, 0.0),
+            ),
+            false => SurfaceCoordinates::new(point.x(), point.z(), Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0)),
+        }
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, self.minimum, -1.0), Point::new(1.0, self.maximum, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_misses_a_cylinder() {
+        // Given
+        let cyl = Cylinder::new();
+        let cases = [
+            (Point::new(1.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 1.0, 0.0)),
+            (Point::new(0.0, 0.0, -5.0), Vector::new(1.0, 1.0, 1.0)),
+        ];
+
+        for (origin, direction) in cases {
+            // Then
+            assert!(cyl.local_intersect(&Ray::new(origin, direction.normalize())).is_empty());
+        }
+    }
+
+    #[test]
+    fn ray_strikes_a_cylinder() {
+        // Given
+        let cyl = Cylinder::new();
+        let cases = [
+            (Point::new(1.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 5.0, 5.0),
+            (Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 4.0, 6.0),
+            (Point::new(0.5, 0.0, -5.0), Vector::new(0.1, 1.0, 1.0), 6.80798, 7.08872),
+        ];
+
+        for (origin, direction, t0, t1) in cases {
+            // When
+            let xs = cyl.local_intersect(&Ray::new(origin, direction.normalize()));
+
+            // Then
+            assert_eq!(xs.len(), 2);
+            assert!(xs[0].t.approx_eq_low_precision(t0));
+            assert!(xs[1].t.approx_eq_low_precision(t1));
+        }
+    }
+
+    #[test]
+    fn normal_vector_on_a_cylinder() {
+        // Given
+        let cyl = Cylinder::new();
+
+        // Then
+        assert_eq!(cyl.local_normal_at(Point::new(1.0, 0.0, 0.0)), Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(cyl.local_normal_at(Point::new(0.0, 5.0, -1.0)), Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(cyl.local_normal_at(Point::new(0.0, -2.0, 1.0)), Vector::new(0.0, 0.0, 1.0));
+        assert_eq!(cyl.local_normal_at(Point::new(-1.0, 1.0, 0.0)), Vector::new(-1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn intersecting_a_constrained_cylinder() {
+        // Given
+        let mut cyl = Cylinder::new();
+        cyl.minimum = 1.0;
+        cyl.maximum = 2.0;
+        let cases = [
+            (Point::new(0.0, 1.5, 0.0), Vector::new(0.1, 1.0, 0.0), 0),
+            (Point::new(0.0, 3.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0), 0),
+            (Point::new(0.0, 1.5, -2.0), Vector::new(0.0, 0.0, 1.0), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            // Then
+            assert_eq!(cyl.local_intersect(&Ray::new(origin, direction.normalize())).len(), count);
+        }
+    }
+
+    #[test]
+    fn intersecting_the_caps_of_a_closed_cylinder() {
+        // Given
+        let mut cyl = Cylinder::new();
+        cyl.minimum = 1.0;
+        cyl.maximum = 2.0;
+        cyl.closed = true;
+        let cases = [
+            (Point::new(0.0, 3.0, 0.0), Vector::new(0.0, -1.0, 0.0), 2),
+            (Point::new(0.0, 3.0, -2.0), Vector::new(0.0, -1.0, 2.0), 2),
+            (Point::new(0.0, 4.0, -2.0), Vector::new(0.0, -1.0, 1.0), 2),
+            (Point::new(0.0, 0.0, -2.0), Vector::new(0.0, 1.0, 2.0), 2),
+            (Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 1.0), 2),
+        ];
+
+        for (origin, direction, count) in cases {
+            // Then
+            assert_eq!(cyl.local_intersect(&Ray::new(origin, direction.normalize())).len(), count);
+        }
+    }
+
+    #[test]
+    fn normal_vector_on_a_cylinders_end_caps() {
+        // Given
+        let mut cyl = Cylinder::new();
+        cyl.minimum = 1.0;
+        cyl.maximum = 2.0;
+        cyl.closed = true;
+
+        // Then
+        assert_eq!(cyl.local_normal_at(Point::new(0.0, 1.0, 0.0)), Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(cyl.local_normal_at(Point::new(0.5, 1.0, 0.0)), Vector::new(0.0, -1.0, 0.0));
+        assert_eq!(cyl.local_normal_at(Point::new(0.0, 2.0, 0.5)), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(cyl.local_normal_at(Point::new(0.5, 2.0, 0.0)), Vector::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn bounding_box_of_a_cylinder() {
+        // Given
+        let mut cyl = Cylinder::new();
+        cyl.minimum = -5.0;
+        cyl.maximum = 3.0;
+
+        // When
+        let b = cyl.bounds();
+
+        // Then
+        assert_eq!(b.min, Point::new(-1.0, -5.0, -1.0));
+        assert_eq!(b.max, Point::new(1.0, 3.0, 1.0));
+        assert!(!Cylinder::new().bounds().is_finite());
+    }
+}
@@ -0,0 +1,357 @@
+use std::sync::OnceLock;
+
+use crate::bounds::BoundingBox;
+use crate::bvh::Bvh;
+use crate::core::matrix::Matrix4;
+use crate::core::point::Point;
+use crate::core::vector::Vector;
+use crate::intersection::Intersection;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeData};
+
+/// A collection of shapes that are transformed as one. Children inherit the transform of the
+/// group on top of their own, and its material unless they have one set explicitly.
+///
+/// Rays are intersected with the children through a bounding volume hierarchy, which is built on
+/// the first intersection after children have been added.
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "GroupRepr"))]
+pub struct Group {
+    data: ShapeData,
+    children: Vec<Box<dyn Shape>>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bvh: OnceLock<Bvh>,
+}
+
+impl Group {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_child(&mut self, mut child: Box<dyn Shape>) {
+        child.inherit(&self.data);
+        self.children.push(child);
+        self.bvh = OnceLock::new();
+    }
+
+    fn propagate(&mut self) {
+        for child in self.children.iter_mut() {
+            child.inherit(&self.data);
+        }
+        self.bvh = OnceLock::new();
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct GroupRepr {
+    #[serde(default)]
+    data: ShapeData,
+    #[serde(default)]
+    children: Vec<Box<dyn Shape>>,
+}
+
+/// Children are added one by one so that they inherit from the group again.
+#[cfg(feature = "serde")]
+impl From<GroupRepr> for Group {
+    fn from(repr: GroupRepr) -> Self {
+        let mut group = Self { data: repr.data, ..Self::default() };
+        for child in repr.children {
+            group.add_child(child);
+        }
+
+        group
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "group"))]
+impl Shape for Group {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let bvh = self.bvh.get_or_init(|| Bvh::build(&self.children));
+        let mut xs = bvh.intersect(&self.children, ray);
+        xs.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        xs
+    }
+
+    fn local_normal_at(&self, _point: Point) -> Vector {
+        unreachable!("Intersections are always with the children of a group, never with the group itself")
+    }
+
+    fn children(&self) -> &[Box<dyn Shape>] {
+        &self.children
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        self.children.iter()
+            .fold(BoundingBox::empty(), |b, child| b.merge(&child.parent_space_bounds()))
+    }
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.data.set_transform(transform);
+        self.propagate();
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.data.set_material(material);
+        self.propagate();
+    }
+
+    fn set_end_transform(&mut self, end: Matrix4) {
+        self.data.set_end_transform(end);
+        self.propagate();
+    }
+
+    fn inherit(&mut self, parent: &ShapeData) {
+        self.data.inherit(parent);
+        self.propagate();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+    use crate::color::Color;
+    use crate::core::float::ApproxEq;
+    use crate::core::tuple::Tuple;
+    use crate::shape::cube::Cube;
+    use crate::shape::cylinder::Cylinder;
+    use crate::shape::sphere::Sphere;
+
+    use super::*;
+
+    fn sphere(transform: Matrix4) -> Box<dyn Shape> {
+        let mut s = Sphere::new();
+        s.set_transform(transform);
+        Box::new(s)
+    }
+
+    #[test]
+    fn creating_a_new_group() {
+        // Given
+        let g = Group::new();
+
+        // Then
+        assert_eq!(*g.transform(), Matrix4::identity());
+        assert!(g.children().is_empty());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_an_empty_group() {
+        // Given
+        let g = Group::new();
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+
+        // Then
+        assert!(g.local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn intersecting_a_ray_with_a_nonempty_group() {
+        // Given
+        let mut g = Group::new();
+        g.add_child(sphere(Matrix4::identity()));
+        g.add_child(sphere(Matrix4::translation(0.0, 0.0, -3.0)));
+        g.add_child(sphere(Matrix4::translation(5.0, 0.0, 0.0)));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let xs = g.local_intersect(&r);
+
+        // Then
+        assert_eq!(xs.len(), 4);
+        assert!(std::ptr::addr_eq(xs[0].object, g.children()[1].as_ref()));
+        assert!(std::ptr::addr_eq(xs[1].object, g.children()[1].as_ref()));
+        assert!(std::ptr::addr_eq(xs[2].object, g.children()[0].as_ref()));
+        assert!(std::ptr::addr_eq(xs[3].object, g.children()[0].as_ref()));
+    }
+
+    #[test]
+    fn intersecting_a_transformed_group() {
+        // Given
+        let mut g = Group::new();
+        g.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        g.add_child(sphere(Matrix4::translation(5.0, 0.0, 0.0)));
+        let r = Ray::new(Point::new(10.0, 0.0, -10.0), Vector::new(0.0, 0.0, 1.0));
+
+        // Then
+        assert_eq!(g.intersect(&r).len(), 2);
+    }
+
+    #[test]
+    fn converting_a_point_from_world_to_object_space() {
+        // Given
+        let mut g1 = Group::new();
+        g1.set_transform(Matrix4::rotation_y(PI / 2.0));
+        let mut g2 = Group::new();
+        g2.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+        g2.add_child(sphere(Matrix4::translation(5.0, 0.0, 0.0)));
+        g1.add_child(Box::new(g2));
+
+        // When
+        let s = &g1.children()[0].children()[0];
+
+        // Then
+        assert_eq!(s.world_to_object(Point::new(-2.0, 0.0, -10.0)), Point::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn converting_a_normal_from_object_to_world_space() {
+        // Given
+        let mut g1 = Group::new();
+        g1.set_transform(Matrix4::rotation_y(PI / 2.0));
+        let mut g2 = Group::new();
+        g2.set_transform(Matrix4::scaling(1.0, 2.0, 3.0));
+        g2.add_child(sphere(Matrix4::translation(5.0, 0.0, 0.0)));
+        g1.add_child(Box::new(g2));
+
+        // When
+        let s = &g1.children()[0].children()[0];
+        let k = 3f64.sqrt() / 3.0;
+
+        // Then
+        assert_eq!(s.normal_to_world(Vector::new(k, k, k)), Vector::new(0.2857, 0.4286, -0.8571));
+    }
+
+    #[test]
+    fn moving_groups_carry_their_children_along() {
+        // Given
+        let mut g2 = Group::new();
+        g2.add_child(sphere(Matrix4::translation(5.0, 0.0, 0.0)));
+        let mut g1 = Group::new();
+        g1.add_child(Box::new(g2));
+        g1.set_end_transform(Matrix4::rotation_y(PI / 2.0));
+        let center = Point::new(5.0 * FRAC_1_SQRT_2, 0.0, -5.0 * FRAC_1_SQRT_2);
+
+        // When
+        let xs = g1.intersect(&Ray::new(center + Vector::new(0.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0)).with_time(0.5));
+        let s = &g1.children()[0].children()[0];
+
+        // Then
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t.approx_eq(4.0) && xs[1].t.approx_eq(6.0));
+        assert_eq!(s.normal_at_time(center + Vector::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2), 0.5), Vector::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2));
+        assert_eq!(s.normal_at_time(Point::new(0.0, 0.0, -4.0), 1.0), Vector::new(0.0, 0.0, 1.0));
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_child_object() {
+        // Given
+        let mut g2 = Group::new();
+        g2.set_transform(Matrix4::scaling(1.0, 2.0, 3.0));
+        g2.add_child(sphere(Matrix4::translation(5.0, 0.0, 0.0)));
+        let mut g1 = Group::new();
+        g1.add_child(Box::new(g2));
+
+        // Transforming the outer group after its children were added still reaches the sphere.
+        g1.set_transform(Matrix4::rotation_y(PI / 2.0));
+
+        // When
+        let s = &g1.children()[0].children()[0];
+
+        // Then
+        assert_eq!(s.normal_at(Point::new(1.7321, 1.1547, -5.5774)), Vector::new(0.2857, 0.4286, -0.8571));
+    }
+
+    #[test]
+    fn intersections_with_nested_children_shade_in_world_space() {
+        // Given
+        let mut g = Group::new();
+        g.set_transform(Matrix4::translation(0.0, 0.0, 5.0));
+        g.add_child(sphere(Matrix4::scaling(2.0, 2.0, 2.0)));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let xs = g.intersect(&r);
+        let comps = xs[0].prepare_computations(&r);
+
+        // Then
+        assert_eq!(xs[0].t, 8.0);
+        assert_eq!(comps.point, Point::new(0.0, 0.0, 3.0));
+        assert_eq!(comps.normalv, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn children_inherit_the_material_of_their_group() {
+        // Given
+        let red = Material { color: Color::new(1.0, 0.0, 0.0), ..Material::default() };
+        let blue = Material { color: Color::new(0.0, 0.0, 1.0), ..Material::default() };
+        let mut explicit = Sphere::new();
+        explicit.set_material(blue.clone());
+        let mut inner = Group::new();
+        inner.add_child(Box::new(Sphere::new()));
+        let mut g = Group::new();
+        g.add_child(Box::new(Sphere::new()));
+        g.add_child(Box::new(explicit));
+        g.add_child(Box::new(inner));
+
+        // When
+        g.set_material(red.clone());
+
+        // Then
+        assert_eq!(g.children()[0].material().color, red.color);
+        assert_eq!(g.children()[1].material().color, blue.color);
+        assert_eq!(g.children()[2].children()[0].material().color, red.color);
+    }
+
+    #[test]
+    fn group_has_a_bounding_box_that_contains_its_children() {
+        // Given
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::translation(2.0, 5.0, -3.0) * Matrix4::scaling(2.0, 2.0, 2.0));
+        let mut c = Cylinder::new();
+        c.minimum = -2.0;
+        c.maximum = 2.0;
+        c.set_transform(Matrix4::translation(-4.0, -1.0, 4.0) * Matrix4::scaling(0.5, 1.0, 0.5));
+        let mut g = Group::new();
+        g.add_child(Box::new(s));
+        g.add_child(Box::new(c));
+
+        // When
+        let b = g.bounds();
+
+        // Then
+        assert_eq!(b.min, Point::new(-4.5, -3.0, -5.0));
+        assert_eq!(b.max, Point::new(4.0, 7.0, 4.5));
+    }
+
+    #[test]
+    fn building_a_chair_from_cubes_and_cylinders() {
+        // Given
+        let mut seat = Cube::new();
+        seat.set_transform(Matrix4::translation(0.0, 1.0, 0.0) * Matrix4::scaling(1.0, 0.1, 1.0));
+        let mut chair = Group::new();
+        chair.add_child(Box::new(seat));
+        for (x, z) in [(-0.9, -0.9), (0.9, -0.9), (-0.9, 0.9), (0.9, 0.9)] {
+            let mut leg = Cylinder::new();
+            leg.minimum = 0.0;
+            leg.maximum = 1.0;
+            leg.closed = true;
+            leg.set_transform(Matrix4::translation(x, 0.0, z) * Matrix4::scaling(0.1, 1.0, 0.1));
+            chair.add_child(Box::new(leg));
+        }
+
+        // When
+        chair.set_transform(Matrix4::translation(10.0, 0.0, 0.0) * Matrix4::rotation_y(PI / 4.0));
+        let down = Ray::new(Point::new(10.0, 5.0, 0.0), Vector::new(0.0, -1.0, 0.0));
+        let leg = Ray::new(Point::new(10.0, 0.5, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let xs = chair.intersect(&down);
+        let comps = xs[0].prepare_computations(&down);
+
+        // Then
+        assert_eq!(xs[0].t, 3.9);
+        assert_eq!(comps.normalv, Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(chair.intersect(&leg).len(), 4);
+        assert!(chair.intersect(&Ray::new(Point::new(10.0, 0.5, -5.0), Vector::new(FRAC_1_SQRT_2, 0.0, FRAC_1_SQRT_2))).is_empty());
+    }
+}
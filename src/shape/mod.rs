@@ -0,0 +1,515 @@
+pub mod csg;
+pub mod cube;
+pub mod cylinder;
+pub mod group;
+pub mod plane;
+pub mod sphere;
+pub mod triangle;
+
+use std::fmt::Debug;
+
+use crate::animation::Interpolate;
+use crate::bounds::BoundingBox;
+use crate::core::matrix::{Decomposition, Matrix4};
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::intersection::Intersection;
+use crate::material::Material;
+use crate::ray::Ray;
+use crate::sampling::Frame;
+
+/// A transform that changes while the shutter is open, from `start` when it opens to `end` when
+/// it closes. In between, the parts of both transforms are blended on their own, so that a
+/// turning shape keeps its size.
+#[derive(Clone, Copy, Debug)]
+pub struct Motion {
+    start: Matrix4,
+    end: Matrix4,
+    parts: Option<(Decomposition, Decomposition)>,
+}
+
+impl Motion {
+    pub fn new(start: Matrix4, end: Matrix4) -> Self {
+        Self { start, end, parts: (start != end).then(|| (start.decompose(), end.decompose())) }
+    }
+
+    /// A transform that stays the same.
+    pub fn fixed(transform: Matrix4) -> Self {
+        Self::new(transform, transform)
+    }
+
+    pub fn start(&self) -> &Matrix4 {
+        &self.start
+    }
+
+    pub fn end(&self) -> &Matrix4 {
+        &self.end
+    }
+
+    pub fn is_moving(&self) -> bool {
+        self.parts.is_some()
+    }
+
+    /// The transform at `time`, from 0.0 when the shutter opens to 1.0 when it closes.
+    pub fn at(&self, time: f64) -> Matrix4 {
+        match self.parts {
+            Some(_) if time >= 1.0 => self.end,
+            Some((start, end)) if time > 0.0 => Matrix4::compose(&start.interpolate(&end, time)),
+            _ => self.start,
+        }
+    }
+}
+
+/// State shared by all shapes: their transform and material, and what they inherit from the
+/// groups they are part of.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "ShapeDataRepr", into = "ShapeDataRepr"))]
+pub struct ShapeData {
+    transform: Matrix4,
+    inverse: Matrix4,
+    /// How the transform changes while the shutter is open, if it was given an end transform.
+    motion: Option<Motion>,
+    parent_transform: Matrix4,
+    parent_inverse: Matrix4,
+    /// The transforms of all the groups above the shape, outermost first, if any of them moves.
+    parent_motion: Option<Vec<Motion>>,
+    world_inverse: Matrix4,
+    material: Material,
+    explicit_material: bool,
+}
+
+impl ShapeData {
+    pub fn new() -> Self {
+        Self {
+            transform: Matrix4::identity(),
+            inverse: Matrix4::identity(),
+            motion: None,
+            parent_transform: Matrix4::identity(),
+            parent_inverse: Matrix4::identity(),
+            parent_motion: None,
+            world_inverse: Matrix4::identity(),
+            material: Material::default(),
+            explicit_material: false,
+        }
+    }
+
+    /// The transform from object space all the way up to world space.
+    pub fn world_transform(&self) -> Matrix4 {
+        self.parent_transform * self.transform
+    }
+
+    /// The transform from world space all the way down to object space.
+    pub fn world_inverse(&self) -> Matrix4 {
+        self.world_inverse
+    }
+
+    /// Whether the shape itself moves while the shutter is open.
+    pub fn is_moving(&self) -> bool {
+        self.motion.is_some_and(|m| m.is_moving())
+    }
+
+    /// The transform to the space of the parent at `time`.
+    pub fn transform_at(&self, time: f64) -> Matrix4 {
+        match self.motion {
+            Some(motion) => motion.at(time),
+            None         => self.transform,
+        }
+    }
+
+    /// The transform from the space of the parent to object space at `time`.
+    pub fn inverse_at(&self, time: f64) -> Matrix4 {
+        match self.is_moving() {
+            true  => self.transform_at(time).invert(),
+            false => self.inverse,
+        }
+    }
+
+    /// The transform from world space all the way down to object space at `time`.
+    pub fn world_inverse_at(&self, time: f64) -> Matrix4 {
+        match (&self.parent_motion, self.is_moving()) {
+            (None, false)    => self.world_inverse,
+            (None, true)     => self.inverse_at(time) * self.parent_inverse,
+            (Some(chain), _) => {
+                let parent_transform = chain.iter().fold(Matrix4::identity(), |acc, m| acc * m.at(time));
+                self.inverse_at(time) * parent_transform.invert()
+            }
+        }
+    }
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.transform = transform;
+        self.inverse = transform.invert();
+        self.motion = self.motion.map(|m| Motion::new(transform, m.end));
+        self.world_inverse = self.inverse * self.parent_inverse;
+    }
+
+    fn set_end_transform(&mut self, end: Matrix4) {
+        self.motion = Some(Motion::new(self.transform, end));
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.material = material;
+        self.explicit_material = true;
+    }
+
+    fn inherit(&mut self, parent: &ShapeData) {
+        self.parent_transform = parent.world_transform();
+        self.parent_inverse = parent.world_inverse;
+        self.world_inverse = self.inverse * self.parent_inverse;
+        self.parent_motion = match (&parent.parent_motion, parent.is_moving()) {
+            (None, false) => None,
+            (chain, _)    => {
+                let mut chain = chain.clone().unwrap_or_else(|| vec![Motion::fixed(parent.parent_transform)]);
+                chain.push(parent.motion.unwrap_or(Motion::fixed(parent.transform)));
+                Some(chain)
+            }
+        };
+
+        if !self.explicit_material {
+            self.material = parent.material.clone();
+        }
+    }
+}
+
+impl Default for ShapeData {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// What is stored of `ShapeData`: the transform, the end transform if the shape moves and the
+/// material if it was set explicitly. Everything else is inherited again when the shape is added
+/// to its group.
+#[cfg(feature = "serde")]
+#[derive(serde::Serialize, serde::Deserialize)]
+struct ShapeDataRepr {
+    #[serde(default = "Matrix4::identity")]
+    transform: Matrix4,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    end_transform: Option<Matrix4>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    material: Option<Material>,
+}
+
+#[cfg(feature = "serde")]
+impl From<ShapeDataRepr> for ShapeData {
+    fn from(repr: ShapeDataRepr) -> Self {
+        let mut data = ShapeData::new();
+        data.set_transform(repr.transform);
+        if let Some(end) = repr.end_transform {
+            data.set_end_transform(end);
+        }
+        if let Some(material) = repr.material {
+            data.set_material(material);
+        }
+
+        data
+    }
+}
+
+#[cfg(feature = "serde")]
+impl From<ShapeData> for ShapeDataRepr {
+    fn from(data: ShapeData) -> Self {
+        Self {
+            transform: data.transform,
+            end_transform: data.motion.map(|m| m.end),
+            material: data.explicit_material.then_some(data.material),
+        }
+    }
+}
+
+/// Where a point is on the surface of a shape as far as the maps of its material are concerned,
+/// and the directions along the surface in which the coordinates `u` and `v` grow, as far as
+/// they go for a change of 1.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SurfaceCoordinates {
+    pub u: f64,
+    pub v: f64,
+    pub dpdu: Vector,
+    pub dpdv: Vector,
+}
+
+impl SurfaceCoordinates {
+    pub fn new(u: f64, v: f64, dpdu: Vector, dpdv: Vector) -> Self {
+        Self { u, v, dpdu, dpdv, }
+    }
+
+    /// The frame around the unit vector `normal` with its tangent along `dpdu` and its bitangent
+    /// on the side of `dpdv`, or None where `u` doesn't change along the surface, like at the
+    /// poles of a sphere.
+    pub fn frame(&self, normal: Vector) -> Option<Frame> {
+        let along = self.dpdu - normal * self.dpdu.dot(normal);
+        if along.magnitude() < 1e-9 {
+            return None;
+        }
+        let tangent = along.normalize();
+        let bitangent = match normal.cross(tangent) {
+            b if b.dot(self.dpdv) < 0.0 => -b,
+            b                           => b,
+        };
+
+        Some(Frame { tangent, bitangent, normal })
+    }
+}
+
+/// A renderable object. Implementors only deal with object space through `local_intersect` and
+/// `local_normal_at`, the conversion from and to world space is shared by all shapes.
+#[cfg_attr(feature = "serde", typetag::serde(tag = "type"))]
+pub trait Shape: Debug + Send + Sync {
+    fn data(&self) -> &ShapeData;
+    fn data_mut(&mut self) -> &mut ShapeData;
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>>;
+    fn local_normal_at(&self, point: Point) -> Vector;
+
+    /// The bounds of the shape in object space.
+    fn bounds(&self) -> BoundingBox;
+
+    /// The surface coordinates at a point in object space. Shapes without coordinates of their
+    /// own get them from any frame around the normal, which the coordinates run along.
+    fn local_surface_at(&self, point: Point) -> SurfaceCoordinates {
+        let frame = Frame::new(self.local_normal_at(point).normalize());
+        let offset = point - Point::zero();
+
+        SurfaceCoordinates::new(offset.dot(frame.tangent), offset.dot(frame.bitangent), frame.tangent, frame.bitangent)
+    }
+
+    fn transform(&self) -> &Matrix4 {
+        &self.data().transform
+    }
+
+    fn inverse(&self) -> &Matrix4 {
+        &self.data().inverse
+    }
+
+    fn set_transform(&mut self, transform: Matrix4) {
+        self.data_mut().set_transform(transform);
+    }
+
+    /// The transform at the moment the shutter closes, if the shape moves from its transform to
+    /// it while the shutter is open.
+    fn end_transform(&self) -> Option<&Matrix4> {
+        self.data().motion.as_ref().map(Motion::end)
+    }
+
+    fn set_end_transform(&mut self, end: Matrix4) {
+        self.data_mut().set_end_transform(end);
+    }
+
+    /// The material of the shape, which is inherited from its group unless it was set explicitly.
+    fn material(&self) -> &Material {
+        &self.data().material
+    }
+
+    fn set_material(&mut self, material: Material) {
+        self.data_mut().set_material(material);
+    }
+
+    /// Passes down the transforms and the material of the parent group. Called by groups
+    /// whenever these change.
+    fn inherit(&mut self, parent: &ShapeData) {
+        self.data_mut().inherit(parent);
+    }
+
+    /// The shapes this shape is composed of, if it is an aggregate such as a group.
+    fn children(&self) -> &[Box<dyn Shape>] {
+        &[]
+    }
+
+    /// The bounds of the shape in the space of its parent, or the world if it has none. They
+    /// enclose everywhere the shape goes while the shutter is open.
+    fn parent_space_bounds(&self) -> BoundingBox {
+        match self.data().motion {
+            Some(motion) if motion.is_moving() => self.bounds().sweep(|time| motion.at(time)),
+            _                                  => self.bounds().transform(*self.transform()),
+        }
+    }
+
+    /// Intersects the shape with a ray given in the space of its parent, where the shape is at
+    /// the time of the ray.
+    fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        self.local_intersect(&ray.transform(self.data().inverse_at(ray.time)))
+    }
+
+    fn world_to_object(&self, point: Point) -> Point {
+        self.data().world_inverse * point
+    }
+
+    fn normal_to_world(&self, normal: Vector) -> Vector {
+        (self.data().world_inverse.transpose() * normal).normalize()
+    }
+
+    /// The surface normal at a point given in world space.
+    fn normal_at(&self, point: Point) -> Vector {
+        self.normal_at_time(point, 0.0)
+    }
+
+    /// The surface normal at a point given in world space, with the shape where it is at `time`.
+    fn normal_at_time(&self, point: Point, time: f64) -> Vector {
+        let inverse = self.data().world_inverse_at(time);
+
+        (inverse.transpose() * self.local_normal_at(inverse * point)).normalize()
+    }
+
+    /// The surface coordinates at a point given in world space, with the shape where it is at
+    /// `time` and the directions they grow in turned into world space as well.
+    fn surface_at(&self, point: Point, time: f64) -> SurfaceCoordinates {
+        let inverse = self.data().world_inverse_at(time);
+        let local = self.local_surface_at(inverse * point);
+        let transform = inverse.invert();
+
+        SurfaceCoordinates { dpdu: transform * local.dpdu, dpdv: transform * local.dpdv, ..local }
+    }
+}
+
+impl PartialEq for dyn Shape + '_ {
+    fn eq(&self, other: &Self) -> bool {
+        std::ptr::addr_eq(self, other)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::{FRAC_1_SQRT_2, PI};
+
+    use crate::core::float::ApproxEq;
+    use crate::shape::cube::Cube;
+    use crate::shape::sphere::Sphere;
+
+    use super::*;
+
+    #[test]
+    fn intersecting_a_scaled_shape_with_a_ray() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::scaling(2.0, 2.0, 2.0));
+
+        // When
+        let xs = s.intersect(&r);
+
+        // Then
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 3.0);
+        assert_eq!(xs[1].t, 7.0);
+    }
+
+    #[test]
+    fn intersecting_a_translated_shape_with_a_ray() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::translation(5.0, 0.0, 0.0));
+
+        // Then
+        assert!(s.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn computing_the_normal_on_a_translated_shape() {
+        // Given
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::translation(0.0, 1.0, 0.0));
+
+        // Then
+        assert_eq!(s.normal_at(Point::new(0.0, 1.0 + FRAC_1_SQRT_2, -FRAC_1_SQRT_2)), Vector::new(0.0, FRAC_1_SQRT_2, -FRAC_1_SQRT_2));
+    }
+
+    #[test]
+    fn computing_the_normal_on_a_transformed_shape() {
+        // Given
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::scaling(1.0, 0.5, 1.0) * Matrix4::rotation_z(PI / 5.0));
+
+        // When
+        let n = s.normal_at(Point::new(0.0, 2f64.sqrt() / 2.0, -(2f64.sqrt()) / 2.0));
+
+        // Then
+        assert_eq!(n, Vector::new(0.0, 0.97014, -0.24254));
+    }
+
+    #[test]
+    fn querying_a_shapes_bounding_box_in_its_parents_space() {
+        // Given
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::translation(1.0, -3.0, 5.0) * Matrix4::scaling(0.5, 2.0, 4.0));
+
+        // When
+        let b = s.parent_space_bounds();
+
+        // Then
+        assert_eq!(b.min, Point::new(0.5, -5.0, 1.0));
+        assert_eq!(b.max, Point::new(1.5, -1.0, 9.0));
+    }
+
+    #[test]
+    fn intersecting_a_moving_shape_where_it_is_at_the_time_of_the_ray() {
+        // Given
+        let mut s = Sphere::new();
+        s.set_end_transform(Matrix4::translation(4.0, 0.0, 0.0));
+        let r = Ray::new(Point::new(2.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let start = s.intersect(&r);
+        let halfway = s.intersect(&r.with_time(0.5));
+
+        // Then
+        assert!(start.is_empty());
+        assert_eq!(halfway.len(), 2);
+        assert!(halfway[0].t.approx_eq(4.0) && halfway[1].t.approx_eq(6.0));
+        assert_eq!(s.normal_at(Point::new(0.0, 0.0, -1.0)), Vector::new(0.0, 0.0, -1.0));
+        assert_eq!(s.normal_at_time(Point::new(3.0, 0.0, 0.0), 0.5), Vector::new(1.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn turning_shapes_keep_their_size() {
+        // Given
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::scaling(2.0, 1.0, 1.0));
+        s.set_end_transform(Matrix4::rotation_y(PI / 2.0) * Matrix4::scaling(2.0, 1.0, 1.0));
+        let axis = Vector::new(FRAC_1_SQRT_2, 0.0, -FRAC_1_SQRT_2);
+
+        // When
+        let xs = s.intersect(&Ray::new(Point::zero() - axis * 5.0, axis).with_time(0.5));
+
+        // Then
+        assert_eq!(xs.len(), 2);
+        assert!(xs[0].t.approx_eq(3.0) && xs[1].t.approx_eq(7.0));
+    }
+
+    #[test]
+    fn the_bounds_of_a_moving_shape_enclose_all_of_its_motion() {
+        // Given
+        let mut moving = Sphere::new();
+        moving.set_end_transform(Matrix4::translation(4.0, 0.0, 0.0));
+        let mut turning = Cube::new();
+        turning.set_end_transform(Matrix4::rotation_y(PI / 2.0));
+
+        // When
+        let moving_bounds = moving.parent_space_bounds();
+        let turning_bounds = turning.parent_space_bounds();
+
+        // Then
+        assert!(moving_bounds.contains_box(&BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(5.0, 1.0, 1.0))));
+        assert!(turning_bounds.contains_point(Point::new(2f64.sqrt(), 1.0, 0.0)));
+        assert!(turning_bounds.contains_point(Point::new(0.0, -1.0, -(2f64.sqrt()))));
+    }
+
+    #[test]
+    fn surface_directions_follow_the_shape_into_the_world() {
+        // Given
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::rotation_y(PI / 2.0) * Matrix4::scaling(2.0, 2.0, 2.0));
+
+        // When
+        let surface = s.surface_at(Point::new(0.0, 0.0, -2.0), 0.0);
+        let frame = surface.frame(Vector::new(0.0, 0.0, -1.0)).unwrap();
+
+        // Then: the side of the sphere at +x turned to face -z.
+        assert!(surface.u.approx_eq(0.25) && surface.v.approx_eq(0.5));
+        assert_eq!(surface.dpdu, Vector::new(4.0 * PI, 0.0, 0.0));
+        assert_eq!(frame.tangent, Vector::new(1.0, 0.0, 0.0));
+        assert_eq!(frame.bitangent, Vector::new(0.0, 1.0, 0.0));
+        assert!(s.surface_at(Point::new(0.0, 2.0, 0.0), 0.0).frame(Vector::new(0.0, 1.0, 0.0)).is_none());
+    }
+}
@@ -0,0 +1,97 @@
+use crate::bounds::BoundingBox;
+use crate::core::float::ApproxEq;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::intersection::Intersection;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeData, SurfaceCoordinates};
+
+/// The infinite xz plane through the object space origin.
+#[derive(Clone, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct Plane {
+    data: ShapeData,
+}
+
+impl Plane {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "plane"))]
+impl Shape for Plane {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        match ray.direction.y().approx_eq(0.0) {
+            true  => vec![],
+            false => vec![Intersection::new(-ray.origin.y() / ray.direction.y(), self)],
+        }
+    }
+
+    fn local_normal_at(&self, _point: Point) -> Vector {
+        Vector::new(0.0, 1.0, 0.0)
+    }
+
+    /// Maps repeat along x and z.
+    fn local_surface_at(&self, point: Point) -> SurfaceCoordinates {
+        SurfaceCoordinates::new(point.x(), point.z(), Vector::new(1.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0))
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(
+            Point::new(f64::NEG_INFINITY, 0.0, f64::NEG_INFINITY),
+            Point::new(f64::INFINITY, 0.0, f64::INFINITY),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normal_of_a_plane_is_constant_everywhere() {
+        // Given
+        let p = Plane::new();
+
+        // Then
+        assert_eq!(p.local_normal_at(Point::new(0.0, 0.0, 0.0)), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(p.local_normal_at(Point::new(10.0, 0.0, -10.0)), Vector::new(0.0, 1.0, 0.0));
+        assert_eq!(p.local_normal_at(Point::new(-5.0, 0.0, 150.0)), Vector::new(0.0, 1.0, 0.0));
+    }
+
+    #[test]
+    fn intersect_with_a_ray_parallel_to_the_plane() {
+        // Given
+        let p = Plane::new();
+
+        // Then
+        assert!(p.local_intersect(&Ray::new(Point::new(0.0, 10.0, 0.0), Vector::new(0.0, 0.0, 1.0))).is_empty());
+        assert!(p.local_intersect(&Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0))).is_empty());
+    }
+
+    #[test]
+    fn ray_intersecting_a_plane_from_above_and_below() {
+        // Given
+        let p = Plane::new();
+
+        // When
+        let above = p.local_intersect(&Ray::new(Point::new(0.0, 1.0, 0.0), Vector::new(0.0, -1.0, 0.0)));
+        let below = p.local_intersect(&Ray::new(Point::new(0.0, -1.0, 0.0), Vector::new(0.0, 1.0, 0.0)));
+
+        // Then
+        assert_eq!(above.len(), 1);
+        assert_eq!(above[0].t, 1.0);
+        assert_eq!(below.len(), 1);
+        assert_eq!(below[0].t, 1.0);
+    }
+}
@@ -0,0 +1,188 @@
+use std::f64::consts::{PI, TAU};
+
+use crate::bounds::BoundingBox;
+use crate::core::point::Point;
+use crate::core::tuple::Tuple;
+use crate::core::vector::Vector;
+use crate::intersection::Intersection;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeData, SurfaceCoordinates};
+
+/// A unit sphere centered on the object space origin.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(default))]
+pub struct Sphere {
+    data: ShapeData,
+}
+
+impl Sphere {
+    pub fn new() -> Self {
+        Self {
+            data: ShapeData::new(),
+        }
+    }
+}
+
+impl Default for Sphere {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "sphere"))]
+impl Shape for Sphere {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let sphere_to_ray = ray.origin - Point::zero();
+
+        let a = ray.direction.dot(ray.direction);
+        let b = 2.0 * ray.direction.dot(sphere_to_ray);
+        let c = sphere_to_ray.dot(sphere_to_ray) - 1.0;
+
+        let discriminant = b * b - 4.0 * a * c;
+        if discriminant < 0.0 {
+            return vec![];
+        }
+
+        let t1 = (-b - discriminant.sqrt()) / (2.0 * a);
+        let t2 = (-b + discriminant.sqrt()) / (2.0 * a);
+
+        vec![Intersection::new(t1, self), Intersection::new(t2, self)]
+    }
+
+    fn local_normal_at(&self, point: Point) -> Vector {
+        point - Point::zero()
+    }
+
+    /// Maps are wrapped around the sphere like a panorama: `u` goes once around the y axis and `v`
+    /// from the bottom pole to the top one.
+    fn local_surface_at(&self, point: Point) -> SurfaceCoordinates {
+        let theta = point.x().atan2(point.z());
+        let phi = point.y().clamp(-1.0, 1.0).acos();
+        let (sin_theta, cos_theta) = theta.sin_cos();
+        let (sin_phi, cos_phi) = phi.sin_cos();
+
+        SurfaceCoordinates::new(
+            0.5 - theta / TAU,
+            1.0 - phi / PI,
+            Vector::new(-point.z(), 0.0, point.x()) * TAU,
+            Vector::new(-cos_phi * sin_theta, sin_phi, -cos_phi * cos_theta) * PI,
+        )
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        BoundingBox::new(Point::new(-1.0, -1.0, -1.0), Point::new(1.0, 1.0, 1.0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ray_intersects_a_sphere_at_two_points() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let s = Sphere::new();
+
+        // When
+        let xs = s.intersect(&r);
+
+        // Then
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 4.0);
+        assert_eq!(xs[1].t, 6.0);
+    }
+
+    #[test]
+    fn ray_intersects_a_sphere_at_a_tangent() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 1.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let s = Sphere::new();
+
+        // When
+        let xs = s.intersect(&r);
+
+        // Then
+        assert_eq!(xs.len(), 2);
+        assert_eq!(xs[0].t, 5.0);
+        assert_eq!(xs[1].t, 5.0);
+    }
+
+    #[test]
+    fn ray_misses_a_sphere() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 2.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let s = Sphere::new();
+
+        // Then
+        assert!(s.intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn ray_originates_inside_a_sphere() {
+        // Given
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let s = Sphere::new();
+
+        // When
+        let xs = s.intersect(&r);
+
+        // Then
+        assert_eq!(xs[0].t, -1.0);
+        assert_eq!(xs[1].t, 1.0);
+    }
+
+    #[test]
+    fn normal_on_a_sphere_at_a_nonaxial_point() {
+        // Given
+        let s = Sphere::new();
+        let k = 3f64.sqrt() / 3.0;
+
+        // When
+        let n = s.normal_at(Point::new(k, k, k));
+
+        // Then
+        assert_eq!(n, Vector::new(k, k, k));
+        assert_eq!(n, n.normalize());
+    }
+
+    #[test]
+    fn sphere_has_a_bounding_box() {
+        // Given
+        let s = Sphere::new();
+
+        // When
+        let b = s.bounds();
+
+        // Then
+        assert_eq!(b.min, Point::new(-1.0, -1.0, -1.0));
+        assert_eq!(b.max, Point::new(1.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn maps_wrap_around_a_sphere() {
+        // Given
+        let s = Sphere::new();
+
+        // When
+        let front = s.local_surface_at(Point::new(0.0, 0.0, -1.0));
+        let side = s.local_surface_at(Point::new(1.0, 0.0, 0.0));
+        let top = s.local_surface_at(Point::new(0.0, 1.0, 0.0));
+
+        // Then
+        assert_eq!((front.u, front.v), (0.0, 0.5));
+        assert_eq!(front.dpdu, Vector::new(TAU, 0.0, 0.0));
+        assert_eq!(front.dpdv, Vector::new(0.0, PI, 0.0));
+        assert_eq!((side.u, side.v), (0.25, 0.5));
+        assert_eq!(side.dpdu, Vector::new(0.0, 0.0, TAU));
+        assert_eq!(top.v, 1.0);
+    }
+}
@@ -0,0 +1,256 @@
+use crate::bounds::BoundingBox;
+use crate::core::float::ApproxEq;
+use crate::core::point::Point;
+use crate::core::vector::Vector;
+use crate::intersection::Intersection;
+use crate::ray::Ray;
+use crate::shape::{Shape, ShapeData, SurfaceCoordinates};
+
+/// A flat triangle between three points, as found in polygon meshes.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "TriangleRepr"))]
+pub struct Triangle {
+    pub p1: Point,
+    pub p2: Point,
+    pub p3: Point,
+    /// The texture coordinates at the corners, if the triangle has any.
+    #[cfg_attr(feature = "serde", serde(default, skip_serializing_if = "Option::is_none"))]
+    pub uvs: Option<[(f64, f64); 3]>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    e1: Vector,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    e2: Vector,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    normal: Vector,
+    /// The directions the texture coordinates grow in along the triangle.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dpdu: Vector,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    dpdv: Vector,
+    data: ShapeData,
+}
+
+impl Triangle {
+    pub fn new(p1: Point, p2: Point, p3: Point) -> Self {
+        let e1 = p2 - p1;
+        let e2 = p3 - p1;
+
+        Self {
+            p1,
+            p2,
+            p3,
+            uvs: None,
+            e1,
+            e2,
+            normal: e2.cross(e1).normalize(),
+            dpdu: e1,
+            dpdv: e2,
+            data: ShapeData::new(),
+        }
+    }
+
+    /// The triangle with texture coordinates at its corners. Without them, the coordinates are
+    /// how far a point is towards `p2` and `p3`.
+    pub fn with_uvs(self, uvs: [(f64, f64); 3]) -> Self {
+        let [(u1, v1), (u2, v2), (u3, v3)] = uvs;
+        let (du1, dv1, du2, dv2) = (u2 - u1, v2 - v1, u3 - u1, v3 - v1);
+        let det = du1 * dv2 - dv1 * du2;
+        // Coordinates that are all in a line don't give the triangle any directions.
+        let (dpdu, dpdv) = match det.approx_eq(0.0) {
+            true  => (self.e1, self.e2),
+            false => ((self.e1 * dv2 - self.e2 * dv1) * (1.0 / det), (self.e2 * du1 - self.e1 * du2) * (1.0 / det)),
+        };
+
+        Self { uvs: Some(uvs), dpdu, dpdv, ..self }
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct TriangleRepr {
+    p1: Point,
+    p2: Point,
+    p3: Point,
+    #[serde(default)]
+    uvs: Option<[(f64, f64); 3]>,
+    #[serde(default)]
+    data: ShapeData,
+}
+
+#[cfg(feature = "serde")]
+impl From<TriangleRepr> for Triangle {
+    fn from(repr: TriangleRepr) -> Self {
+        let triangle = Triangle::new(repr.p1, repr.p2, repr.p3);
+        let triangle = match repr.uvs {
+            Some(uvs) => triangle.with_uvs(uvs),
+            None      => triangle,
+        };
+
+        Self { data: repr.data, ..triangle }
+    }
+}
+
+#[cfg_attr(feature = "serde", typetag::serde(name = "triangle"))]
+impl Shape for Triangle {
+    fn data(&self) -> &ShapeData {
+        &self.data
+    }
+
+    fn data_mut(&mut self) -> &mut ShapeData {
+        &mut self.data
+    }
+
+    /// Möller–Trumbore ray/triangle intersection.
+    fn local_intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let dir_cross_e2 = ray.direction.cross(self.e2);
+        let det = self.e1.dot(dir_cross_e2);
+        if det.approx_eq(0.0) {
+            return vec![];
+        }
+
+        let f = 1.0 / det;
+        let p1_to_origin = ray.origin - self.p1;
+        let u = f * p1_to_origin.dot(dir_cross_e2);
+        if !(0.0..=1.0).contains(&u) {
+            return vec![];
+        }
+
+        let origin_cross_e1 = p1_to_origin.cross(self.e1);
+        let v = f * ray.direction.dot(origin_cross_e1);
+        if v < 0.0 || u + v > 1.0 {
+            return vec![];
+        }
+
+        vec![Intersection::new(f * self.e2.dot(origin_cross_e1), self)]
+    }
+
+    fn local_normal_at(&self, _point: Point) -> Vector {
+        self.normal
+    }
+
+    fn local_surface_at(&self, point: Point) -> SurfaceCoordinates {
+        // How far the point is towards p2 and p3.
+        let offset = point - self.p1;
+        let (d11, d12, d22) = (self.e1.dot(self.e1), self.e1.dot(self.e2), self.e2.dot(self.e2));
+        let (d1, d2) = (offset.dot(self.e1), offset.dot(self.e2));
+        let denominator = d11 * d22 - d12 * d12;
+        let b2 = (d22 * d1 - d12 * d2) / denominator;
+        let b3 = (d11 * d2 - d12 * d1) / denominator;
+
+        let (u, v) = match self.uvs {
+            Some([(u1, v1), (u2, v2), (u3, v3)]) => (u1 + b2 * (u2 - u1) + b3 * (u3 - u1), v1 + b2 * (v2 - v1) + b3 * (v3 - v1)),
+            None                                 => (b2, b3),
+        };
+
+        SurfaceCoordinates::new(u, v, self.dpdu, self.dpdv)
+    }
+
+    fn bounds(&self) -> BoundingBox {
+        let mut bounds = BoundingBox::empty();
+        bounds.add_point(self.p1);
+        bounds.add_point(self.p2);
+        bounds.add_point(self.p3);
+
+        bounds
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::tuple::Tuple;
+
+    use super::*;
+
+    fn triangle() -> Triangle {
+        Triangle::new(Point::new(0.0, 1.0, 0.0), Point::new(-1.0, 0.0, 0.0), Point::new(1.0, 0.0, 0.0))
+    }
+
+    #[test]
+    fn constructing_a_triangle() {
+        // Given
+        let t = triangle();
+
+        // Then
+        assert_eq!(t.e1, Vector::new(-1.0, -1.0, 0.0));
+        assert_eq!(t.e2, Vector::new(1.0, -1.0, 0.0));
+        assert_eq!(t.normal, Vector::new(0.0, 0.0, -1.0));
+    }
+
+    #[test]
+    fn finding_the_normal_on_a_triangle() {
+        // Given
+        let t = triangle();
+
+        // Then
+        assert_eq!(t.local_normal_at(Point::new(0.0, 0.5, 0.0)), t.normal);
+        assert_eq!(t.local_normal_at(Point::new(-0.5, 0.75, 0.0)), t.normal);
+        assert_eq!(t.local_normal_at(Point::new(0.5, 0.25, 0.0)), t.normal);
+    }
+
+    #[test]
+    fn intersecting_a_ray_parallel_to_the_triangle() {
+        // Given
+        let t = triangle();
+        let r = Ray::new(Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 1.0, 0.0));
+
+        // Then
+        assert!(t.local_intersect(&r).is_empty());
+    }
+
+    #[test]
+    fn ray_misses_the_edges_of_the_triangle() {
+        // Given
+        let t = triangle();
+
+        // Then
+        assert!(t.local_intersect(&Ray::new(Point::new(1.0, 1.0, -2.0), Vector::new(0.0, 0.0, 1.0))).is_empty());
+        assert!(t.local_intersect(&Ray::new(Point::new(-1.0, 1.0, -2.0), Vector::new(0.0, 0.0, 1.0))).is_empty());
+        assert!(t.local_intersect(&Ray::new(Point::new(0.0, -1.0, -2.0), Vector::new(0.0, 0.0, 1.0))).is_empty());
+    }
+
+    #[test]
+    fn ray_strikes_a_triangle() {
+        // Given
+        let t = triangle();
+        let r = Ray::new(Point::new(0.0, 0.5, -2.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let xs = t.local_intersect(&r);
+
+        // Then
+        assert_eq!(xs.len(), 1);
+        assert_eq!(xs[0].t, 2.0);
+    }
+
+    #[test]
+    fn triangle_has_a_bounding_box() {
+        // Given
+        let t = Triangle::new(Point::new(-3.0, 7.0, 2.0), Point::new(6.0, 2.0, -4.0), Point::new(2.0, -1.0, -1.0));
+
+        // When
+        let b = t.bounds();
+
+        // Then
+        assert_eq!(b.min, Point::new(-3.0, -1.0, -4.0));
+        assert_eq!(b.max, Point::new(6.0, 7.0, 2.0));
+    }
+
+    #[test]
+    fn texture_coordinates_are_spread_over_a_triangle() {
+        // Given
+        let plain = triangle();
+        let mapped = triangle().with_uvs([(0.5, 1.0), (0.0, 0.0), (1.0, 0.0)]);
+
+        // When
+        let p = Point::new(0.25, 0.25, 0.0);
+        let surface = plain.local_surface_at(p);
+        let mapped_surface = mapped.local_surface_at(p);
+
+        // Then
+        assert!(surface.u.approx_eq(0.25) && surface.v.approx_eq(0.5));
+        assert_eq!((surface.dpdu, surface.dpdv), (plain.e1, plain.e2));
+        assert!(mapped_surface.u.approx_eq(0.625) && mapped_surface.v.approx_eq(0.25));
+        assert_eq!(mapped_surface.dpdu, Vector::new(2.0, 0.0, 0.0));
+        assert_eq!(mapped_surface.dpdv, Vector::new(0.0, 1.0, 0.0));
+    }
+}
@@ -0,0 +1,221 @@
+use crate::color::{Color, D65};
+
+/// The shortest and longest wavelengths of visible light followed in spectral mode, in nm.
+pub const LAMBDA_MIN: f64 = 360.0;
+pub const LAMBDA_MAX: f64 = 830.0;
+
+/// The wavelengths in nm at which the red, green and blue channels of colors that are physical
+/// quantities, like the index of refraction of a metal, are taken to be given.
+pub const CHANNEL_WAVELENGTHS: [f64; 3] = [630.0, 532.0, 465.0];
+
+/// The spectra Smits combines into the spectrum of a color in "An RGB-to-Spectrum Conversion for
+/// Reflectances", given on ten equal steps from 380 to 720 nm.
+const SMITS_START: f64 = 380.0;
+const SMITS_STEP: f64 = 34.0;
+const WHITE: [f64; 10] = [1.0000, 1.0000, 0.9999, 0.9993, 0.9992, 0.9998, 1.0000, 1.0000, 1.0000, 1.0000];
+const CYAN: [f64; 10] = [0.9710, 0.9426, 1.0007, 1.0007, 1.0007, 1.0007, 0.1564, 0.0000, 0.0000, 0.0000];
+const MAGENTA: [f64; 10] = [1.0000, 1.0000, 0.9685, 0.2229, 0.0000, 0.0458, 0.8369, 1.0000, 1.0000, 0.9959];
+const YELLOW: [f64; 10] = [0.0001, 0.0000, 0.1088, 0.6651, 1.0000, 1.0000, 0.9996, 0.9586, 0.9685, 0.9840];
+const RED: [f64; 10] = [0.1012, 0.0515, 0.0000, 0.0000, 0.0000, 0.0000, 0.8325, 1.0149, 1.0149, 1.0149];
+const GREEN: [f64; 10] = [0.0000, 0.0000, 0.0273, 0.7937, 1.0000, 0.9418, 0.1719, 0.0000, 0.0000, 0.0025];
+const BLUE: [f64; 10] = [1.0000, 1.0000, 0.8916, 0.3323, 0.0000, 0.0000, 0.0003, 0.0369, 0.0483, 0.0496];
+
+/// The wavelengths a path carries in spectral mode, with the density each was picked with. The
+/// three channels of the colors along the path then hold the values at these wavelengths, so that
+/// the BSDFs work on them unchanged.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wavelengths {
+    pub lambda: [f64; 3],
+    pub pdf: [f64; 3],
+}
+
+impl Wavelengths {
+    /// Three wavelengths spread over the visible range from a number in `0..1`, more of them where
+    /// the eye is the most sensitive, as given by Radziszewski et al. in "An Improved Technique
+    /// for Full Spectral Rendering".
+    pub fn sample(u: f64) -> Self {
+        let mut lambda = [0.0; 3];
+        let mut pdf = [0.0; 3];
+        for i in 0..3 {
+            let u = (u + i as f64 / 3.0).fract();
+            lambda[i] = 538.0 - 138.888889 * (0.85691062 - 1.82750197 * u).atanh();
+            pdf[i] = match (LAMBDA_MIN..=LAMBDA_MAX).contains(&lambda[i]) {
+                true  => 0.0039398042 / (0.0072 * (lambda[i] - 538.0)).cosh().powi(2),
+                false => 0.0,
+            };
+        }
+
+        Self { lambda, pdf }
+    }
+
+    /// Leaves only the first wavelength, once the path has gone somewhere the others would have
+    /// gone another way, like through a prism. It stands in for all three from then on.
+    pub fn terminate_secondary(&mut self) {
+        if self.is_terminated() {
+            return;
+        }
+        self.pdf = [self.pdf[0] / 3.0, 0.0, 0.0];
+    }
+
+    pub fn is_terminated(&self) -> bool {
+        self.pdf[1] == 0.0 && self.pdf[2] == 0.0
+    }
+
+    /// The values at the wavelengths of the spectrum of a color of the world.
+    pub fn spectrum(&self, color: Color) -> Color {
+        let [a, b, c] = self.lambda.map(|lambda| upsample(color, lambda));
+
+        Color::new(a, b, c)
+    }
+
+    /// The values at the wavelengths of a physical quantity given for the red, green and blue
+    /// channels at `CHANNEL_WAVELENGTHS`, interpolated in between.
+    pub fn interpolate(&self, channels: Color) -> Color {
+        let [a, b, c] = self.lambda.map(|lambda| interpolate(channels, lambda));
+
+        Color::new(a, b, c)
+    }
+
+    /// The linear sRGB color of the light with the given values at the wavelengths, balanced
+    /// so that light with the same value everywhere is white.
+    pub fn to_rgb(&self, values: Color) -> Color {
+        let mut xyz = [0.0; 3];
+        for (i, value) in [values.r, values.g, values.b].into_iter().enumerate() {
+            if self.pdf[i] == 0.0 {
+                continue;
+            }
+            for (total, matching) in xyz.iter_mut().zip(color_matching(self.lambda[i])) {
+                *total += matching * value / self.pdf[i] / 3.0;
+            }
+        }
+        let [x, y, z] = [0, 1, 2].map(|i| xyz[i] / COLOR_MATCHING_INTEGRALS[i] * D65[i]);
+
+        Color::from_xyz(x, y, z)
+    }
+}
+
+/// The CIE 1931 color matching functions at `lambda` in nm, from the fit by Wyman et al. in
+/// "Simple Analytic Approximations to the CIE XYZ Color Matching Functions".
+pub fn color_matching(lambda: f64) -> [f64; 3] {
+    COLOR_MATCHING_LOBES.map(|lobes| lobes.iter().map(|&(a, mu, s1, s2)| a * lobe(lambda, mu, s1, s2)).sum())
+}
+
+/// The Gaussian lobes of the fit, as their height, center and widths below and above it.
+const COLOR_MATCHING_LOBES: [&[(f64, f64, f64, f64)]; 3] = [
+    &[(1.056, 599.8, 37.9, 31.0), (0.362, 442.0, 16.0, 26.7), (-0.065, 501.1, 20.4, 26.2)],
+    &[(0.821, 568.8, 46.9, 40.5), (0.286, 530.9, 16.3, 31.1)],
+    &[(1.217, 437.0, 11.8, 36.0), (0.681, 459.0, 26.0, 13.8)],
+];
+
+/// The integrals of the color matching functions over all wavelengths, each lobe of which
+/// contributes `a √(π / 2) (σ₁ + σ₂)`.
+const COLOR_MATCHING_INTEGRALS: [f64; 3] = [
+    1.2533141373155 * (1.056 * (37.9 + 31.0) + 0.362 * (16.0 + 26.7) - 0.065 * (20.4 + 26.2)),
+    1.2533141373155 * (0.821 * (46.9 + 40.5) + 0.286 * (16.3 + 31.1)),
+    1.2533141373155 * (1.217 * (11.8 + 36.0) + 0.681 * (26.0 + 13.8)),
+];
+
+fn lobe(lambda: f64, mu: f64, below: f64, above: f64) -> f64 {
+    let t = (lambda - mu) / if lambda < mu { below } else { above };
+
+    (-0.5 * t * t).exp()
+}
+
+/// The value at `lambda` in nm of a smooth spectrum with about the color `color`, made of white
+/// and the spectra of the primary and secondary colors as described by Smits.
+pub fn upsample(color: Color, lambda: f64) -> f64 {
+    let at = |spectrum: &[f64; 10]| smits(spectrum, lambda);
+    let Color { r, g, b } = color;
+
+    if r <= g && r <= b {
+        r * at(&WHITE) + match g <= b {
+            true  => (g - r) * at(&CYAN) + (b - g) * at(&BLUE),
+            false => (b - r) * at(&CYAN) + (g - b) * at(&GREEN),
+        }
+    } else if g <= r && g <= b {
+        g * at(&WHITE) + match r <= b {
+            true  => (r - g) * at(&MAGENTA) + (b - r) * at(&BLUE),
+            false => (b - g) * at(&MAGENTA) + (r - b) * at(&RED),
+        }
+    } else {
+        b * at(&WHITE) + match r <= g {
+            true  => (r - b) * at(&YELLOW) + (g - r) * at(&GREEN),
+            false => (g - b) * at(&YELLOW) + (r - g) * at(&RED),
+        }
+    }
+}
+
+/// One of the spectra of Smits at `lambda`, interpolated between the middles of its steps.
+fn smits(spectrum: &[f64; 10], lambda: f64) -> f64 {
+    let x = ((lambda - SMITS_START) / SMITS_STEP - 0.5).clamp(0.0, 9.0);
+    let i = (x as usize).min(8);
+    let t = x - i as f64;
+
+    spectrum[i] + t * (spectrum[i + 1] - spectrum[i])
+}
+
+fn interpolate(channels: Color, lambda: f64) -> f64 {
+    let [red, green, blue] = CHANNEL_WAVELENGTHS;
+    let lerp = |a: f64, b: f64, t: f64| a + t.clamp(0.0, 1.0) * (b - a);
+
+    match lambda < green {
+        true  => lerp(channels.b, channels.g, (lambda - blue) / (green - blue)),
+        false => lerp(channels.g, channels.r, (lambda - green) / (red - green)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::float::ApproxEq;
+    use crate::random::{self, random};
+
+    use super::*;
+
+    /// The mean color of many samples of light with the spectrum of `color`.
+    fn round_trip(color: Color) -> Color {
+        let n = 20000;
+
+        (0..n)
+            .map(|_| {
+                let wavelengths = Wavelengths::sample(random());
+                wavelengths.to_rgb(wavelengths.spectrum(color))
+            })
+            .sum::<Color>() * (1.0 / n as f64)
+    }
+
+    #[test]
+    fn colors_come_back_from_their_spectrum() {
+        // Given
+        random::seed(3);
+        let colors = [Color::new(1.0, 1.0, 1.0), Color::new(0.8, 0.2, 0.1), Color::new(0.1, 0.5, 0.2), Color::new(0.2, 0.3, 0.9)];
+
+        for color in colors {
+            // When
+            let back = round_trip(color);
+
+            // Then
+            for (a, b) in [(back.r, color.r), (back.g, color.g), (back.b, color.b)] {
+                assert!((a - b).abs() < 0.06, "{:?} came back as {:?}", color, back);
+            }
+        }
+    }
+
+    #[test]
+    fn wavelengths_are_spread_over_the_visible_range() {
+        // Given
+        let mut w = Wavelengths::sample(0.1);
+
+        // When
+        w.terminate_secondary();
+
+        // Then
+        let all = Wavelengths::sample(0.1);
+        assert!(all.lambda.iter().all(|l| (LAMBDA_MIN..=LAMBDA_MAX).contains(l)));
+        assert!(all.pdf.iter().all(|&p| p > 0.0));
+        assert!(Wavelengths::sample(0.5).lambda[0].approx_eq_low_precision(538.0 - 138.888889 * (0.85691062f64 - 0.91375099).atanh()));
+        assert_eq!(w.pdf, [all.pdf[0] / 3.0, 0.0, 0.0]);
+        assert!(w.is_terminated() && !all.is_terminated());
+        assert!((interpolate(Color::new(1.0, 2.0, 3.0), 581.0) - 1.5).abs() < 1e-9);
+        assert_eq!(interpolate(Color::new(1.0, 2.0, 3.0), 400.0), 3.0);
+    }
+}
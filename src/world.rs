@@ -0,0 +1,502 @@
+use crate::bounds::BoundingBox;
+use crate::bvh::Bvh;
+use crate::color::Color;
+use crate::core::point::Point;
+use crate::core::vector::Vector;
+use crate::integrator::photon::PhotonMap;
+use crate::intersection::{hit, Computations, Intersection};
+use crate::light::{lighting_samples, visibility, Light};
+use crate::medium::{self, Interaction, Medium, Span, Volume};
+use crate::random::random;
+use crate::ray::Ray;
+use crate::shape::Shape;
+
+/// The steps rays are marched in through media with Phong shading.
+const MARCH_STEPS: usize = 64;
+
+#[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize), serde(from = "WorldRepr"))]
+pub struct World {
+    objects: Vec<Box<dyn Shape>>,
+    pub lights: Vec<Box<dyn Light>>,
+    /// Media filling the inside of shapes.
+    volumes: Vec<Volume>,
+    /// A medium filling all of space. Rays that leave the scene for the sky go through it until
+    /// they're past the objects with bounds.
+    pub fog: Option<Medium>,
+    /// Photons that went through mirrors and glass, for the path tracer to find caustics with.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub caustics: Option<PhotonMap>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    bvh: Option<Bvh>,
+    /// The box around the objects and volumes with bounds, which goes with the hierarchy.
+    #[cfg_attr(feature = "serde", serde(skip))]
+    extent: Option<BoundingBox>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn objects(&self) -> &[Box<dyn Shape>] {
+        &self.objects
+    }
+
+    /// Adds an object, which drops the hierarchy over the objects until it's built again.
+    pub fn add_object(&mut self, object: Box<dyn Shape>) {
+        self.objects.push(object);
+        self.bvh = None;
+        self.extent = None;
+    }
+
+    pub fn volumes(&self) -> &[Volume] {
+        &self.volumes
+    }
+
+    /// Adds a volume, which drops the box around the world until the hierarchy is built again.
+    pub fn add_volume(&mut self, volume: Volume) {
+        self.volumes.push(volume);
+        self.extent = None;
+    }
+
+    /// Builds a bounding volume hierarchy over the objects to speed up intersecting rays with
+    /// them. It has to be built again after objects have been added.
+    pub fn build_bvh(&mut self) {
+        self.bvh = Some(Bvh::build(&self.objects));
+        self.extent = Some(self.bounded_extent());
+    }
+
+    /// All intersections of `ray` with the objects in the world, sorted by `t`.
+    pub fn intersect(&self, ray: &Ray) -> Vec<Intersection<'_>> {
+        let mut xs = match &self.bvh {
+            Some(bvh) => bvh.intersect(&self.objects, ray),
+            None      => self.objects.iter()
+                .flat_map(|o| o.intersect(ray))
+                .collect::<Vec<_>>(),
+        };
+        xs.sort_by(|a, b| a.t.total_cmp(&b.t));
+
+        xs
+    }
+
+    pub fn shade_hit(&self, comps: &Computations) -> Color {
+        self.lights.iter()
+            .map(|light| {
+                // Shadows and shading come from the same points on lights with an area.
+                let samples = light.samples(comps.over_point);
+                let intensity = visibility(&samples, comps.over_point, comps.time, self);
+                lighting_samples(comps.object.material(), light.as_ref(), &samples, comps.eyev, comps.normalv, intensity)
+            })
+            .fold(comps.object.material().emission, |acc, c| acc + c)
+    }
+
+    pub fn color_at(&self, ray: &Ray) -> Color {
+        let xs = self.intersect(ray);
+        let hit = hit(&xs);
+        let color = match hit {
+            Some(i) => self.shade_hit(&i.prepare_computations(ray)),
+            None    => self.background(ray),
+        };
+
+        self.march(ray, hit.map_or(f64::INFINITY, |i| i.t), color)
+    }
+
+    /// The light `color` coming from `end` along `ray` as it reaches the origin of the ray through
+    /// the media in between, together with the light from the lights the media scatter towards
+    /// it once. Each stretch of the ray through the same media is sampled in evenly spaced steps.
+    fn march(&self, ray: &Ray, end: f64, color: Color) -> Color {
+        let spans = self.media_along(ray, end);
+        let mut transmittance = Color::new(1.0, 1.0, 1.0);
+        let mut scattered = Color::default();
+
+        for (start, stop, active) in medium::pieces(&spans, end) {
+            let step = (stop - start) / MARCH_STEPS as f64;
+            let length = step * ray.direction.magnitude();
+            let offset = random::<f64>();
+            for i in 0..MARCH_STEPS {
+                let point = ray.position(start + (i as f64 + offset) * step);
+                let mut extinction = Color::default();
+                let mut inscattered = Color::default();
+                for span in &active {
+                    let (absorption, scattering) = span.coefficients(point);
+                    extinction = extinction + absorption + scattering;
+                    if scattering.r + scattering.g + scattering.b > 0.0 {
+                        inscattered = inscattered + scattering * self.light_scattered(point, ray, span.medium);
+                    }
+                }
+                // The light scattered towards the eye over the step, dimmed by the step itself,
+                // which keeps thick media from glowing brighter than their lights.
+                let through = |e: f64| match e > 0.0 {
+                    true  => (1.0 - (-e * length).exp()) / e,
+                    false => length,
+                };
+                let factor = Color::new(through(extinction.r), through(extinction.g), through(extinction.b));
+                scattered = scattered + transmittance * inscattered * factor;
+                let attenuation = extinction * -length;
+                transmittance = transmittance * Color::new(attenuation.r.exp(), attenuation.g.exp(), attenuation.b.exp());
+            }
+        }
+
+        color * transmittance + scattered
+    }
+
+    /// The light from the lights reaching `point` that `medium` scatters back along `ray`.
+    fn light_scattered(&self, point: Point, ray: &Ray, medium: &Medium) -> Color {
+        let direction = ray.direction.normalize();
+
+        self.lights.iter()
+            .map(|light| {
+                let samples = light.samples(point);
+                let total = samples.iter()
+                    .map(|sample| {
+                        let phase = medium.phase.evaluate(sample.direction.dot(direction));
+                        sample.intensity * self.transmittance(point, sample.direction, sample.distance, ray.time) * phase
+                    })
+                    .sum::<Color>();
+                total * (1.0 / samples.len().max(1) as f64)
+            })
+            .sum::<Color>()
+    }
+
+    /// The light seen along `ray` when it misses every object, from environment lights.
+    pub fn background(&self, ray: &Ray) -> Color {
+        self.lights.iter()
+            .filter_map(|light| light.hit(ray))
+            .filter(|(t, _)| t.is_infinite())
+            .fold(Color::default(), |acc, (_, c)| acc + c)
+    }
+
+    /// Whether anything blocks the line of sight between `light_position` and `point`.
+    pub fn is_shadowed(&self, light_position: Point, point: Point) -> bool {
+        let v = light_position - point;
+        let distance = v.magnitude();
+
+        self.is_occluded(point, v / distance, distance, 0.0)
+    }
+
+    /// Whether anything is hit by a shadow ray leaving `point` in `direction` at `time` before it
+    /// has travelled `distance`.
+    pub fn is_occluded(&self, point: Point, direction: Vector, distance: f64, time: f64) -> bool {
+        let ray = Ray::new(point, direction).with_time(time);
+        let xs = self.intersect(&ray);
+
+        matches!(hit(&xs), Some(h) if h.t < distance)
+    }
+
+    /// The fraction of light that makes it from `point` in `direction` at `time` over
+    /// `distance`, through the media of the world and unless anything blocks it.
+    pub fn transmittance(&self, point: Point, direction: Vector, distance: f64, time: f64) -> Color {
+        if self.is_occluded(point, direction, distance, time) {
+            return Color::default();
+        }
+        let ray = Ray::new(point, direction).with_time(time);
+
+        medium::transmittance(&self.media_along(&ray, distance), &ray, distance)
+    }
+
+    /// Where light going along `ray` through the media of the world is first absorbed or
+    /// scattered before `end`, if at all, and the weight of what was sampled (see
+    /// `medium::track`).
+    pub fn track(&self, ray: &Ray, end: f64) -> (Interaction<'_>, Color) {
+        match self.fog.is_none() && self.volumes.is_empty() {
+            true  => (Interaction::Passed, Color::new(1.0, 1.0, 1.0)),
+            false => medium::track(&self.media_along(ray, end), ray, end),
+        }
+    }
+
+    /// The stretches of `ray` up to `end` through the fog and the volumes.
+    pub fn media_along(&self, ray: &Ray, end: f64) -> Vec<Span<'_>> {
+        let mut spans = self.volumes.iter()
+            .flat_map(|volume| volume.spans(ray, end))
+            .collect::<Vec<_>>();
+
+        if let Some(fog) = &self.fog {
+            let end = match end.is_finite() {
+                true  => end,
+                false => {
+                    self.extent().intersection_range(ray).map_or(0.0, |(_, exit)| exit.max(0.0))
+                }
+            };
+            if end > 0.0 {
+                spans.push(Span::new(fog, None, 0.0, end));
+            }
+        }
+
+        spans
+    }
+
+    /// The box around the objects and volumes with bounds.
+    pub fn extent(&self) -> BoundingBox {
+        self.extent.unwrap_or_else(|| self.bounded_extent())
+    }
+
+    fn bounded_extent(&self) -> BoundingBox {
+        self.objects.iter()
+            .chain(self.volumes.iter().map(|volume| &volume.boundary))
+            .map(|shape| shape.parent_space_bounds())
+            .filter(BoundingBox::is_finite)
+            .fold(BoundingBox::empty(), |acc, bounds| acc.merge(&bounds))
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(serde::Deserialize)]
+struct WorldRepr {
+    #[serde(default)]
+    objects: Vec<Box<dyn Shape>>,
+    #[serde(default)]
+    lights: Vec<Box<dyn Light>>,
+    #[serde(default)]
+    volumes: Vec<Volume>,
+    #[serde(default)]
+    fog: Option<Medium>,
+}
+
+/// A deserialized world comes with its bounding volume hierarchy already built.
+#[cfg(feature = "serde")]
+impl From<WorldRepr> for World {
+    fn from(repr: WorldRepr) -> Self {
+        let mut world = Self { objects: repr.objects, lights: repr.lights, volumes: repr.volumes, fog: repr.fog, ..Self::default() };
+        world.build_bvh();
+
+        world
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::core::matrix::Matrix4;
+    use crate::core::tuple::Tuple;
+    use crate::light::area::AreaLight;
+    use crate::light::point::PointLight;
+    use crate::material::Material;
+    use crate::shape::cube::Cube;
+    use crate::shape::plane::Plane;
+    use crate::shape::sphere::Sphere;
+
+    use super::*;
+
+    pub(crate) fn default_world() -> World {
+        let light = PointLight::new(Point::new(-10.0, 10.0, -10.0), Color::new(1.0, 1.0, 1.0));
+
+        let mut s1 = Sphere::new();
+        s1.set_material(Material {
+            color: Color::new(0.8, 1.0, 0.6),
+            diffuse: 0.7,
+            specular: 0.2,
+            ..Material::default()
+        });
+
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix4::scaling(0.5, 0.5, 0.5));
+
+        World {
+            objects: vec![Box::new(s1), Box::new(s2)],
+            lights: vec![Box::new(light)],
+            ..World::default()
+        }
+    }
+
+    #[test]
+    fn creating_a_world() {
+        // Given
+        let w = World::new();
+
+        // Then
+        assert!(w.objects.is_empty());
+        assert!(w.lights.is_empty());
+    }
+
+    #[test]
+    fn intersect_a_world_with_a_ray() {
+        // Given
+        let w = default_world();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let xs = w.intersect(&r);
+
+        // Then
+        assert_eq!(xs.iter().map(|i| i.t).collect::<Vec<_>>(), vec![4.0, 4.5, 5.5, 6.0]);
+    }
+
+    #[test]
+    fn intersect_a_world_with_a_bvh() {
+        // Given
+        let mut w = default_world();
+        w.build_bvh();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let xs = w.intersect(&r);
+
+        // Then
+        assert_eq!(xs.iter().map(|i| i.t).collect::<Vec<_>>(), vec![4.0, 4.5, 5.5, 6.0]);
+        assert_eq!(w.color_at(&r), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn adding_an_object_drops_the_bvh() {
+        // Given
+        let mut w = default_world();
+        w.build_bvh();
+        let mut s = Sphere::new();
+        s.set_transform(Matrix4::translation(0.0, 0.0, 3.0));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        w.add_object(Box::new(s));
+        let xs = w.intersect(&r);
+
+        // Then
+        assert_eq!(xs.iter().map(|i| i.t).collect::<Vec<_>>(), vec![4.0, 4.5, 5.5, 6.0, 7.0, 9.0]);
+    }
+
+    #[test]
+    fn adding_a_volume_grows_the_extent() {
+        // Given
+        let mut w = default_world();
+        w.build_bvh();
+        let mut boundary = Cube::new();
+        boundary.set_transform(Matrix4::translation(5.0, 0.0, 0.0));
+
+        // When
+        w.add_volume(Volume::new(Box::new(boundary), Medium::new(Color::default(), Color::new(1.0, 1.0, 1.0), 0.0)));
+
+        // Then
+        assert_eq!(w.extent().max, Point::new(6.0, 1.0, 1.0));
+    }
+
+    #[test]
+    fn shadows_and_shading_come_from_the_same_samples_of_a_light() {
+        // Given: a strip of light from right above the floor to further off along x, the far
+        // half of which a slab hides.
+        let mut w = World::new();
+        let mut floor = Plane::new();
+        floor.set_material(Material { ambient: 0.1, diffuse: 0.9, specular: 0.0, ..Material::default() });
+        w.add_object(Box::new(floor));
+        let mut slab = Cube::new();
+        slab.set_transform(Matrix4::translation(1.0, 0.5, 0.0) * Matrix4::scaling(0.5, 0.01, 0.5));
+        w.add_object(Box::new(slab));
+        let light = AreaLight::new(Point::new(0.0, 1.0, -0.01), Vector::new(2.0, 0.0, 0.0), 1, Vector::new(0.0, 0.0, 0.02), 1, Color::new(1.0, 1.0, 1.0));
+        w.lights.push(Box::new(light));
+        let r = Ray::new(Point::new(-1.0, 1.0, 0.0), Vector::new(1.0, -1.0, 0.0));
+
+        // When
+        let colors = (0..200).map(|_| w.color_at(&r).r).collect::<Vec<_>>();
+
+        // Then: the floor is in shadow, or lit from the near half of the strip at an angle of
+        // less than 45°.
+        assert!(colors.iter().all(|&c| (c - 0.1).abs() < 1e-9 || c > 0.1 + 0.9 * 0.69));
+        assert!(colors.iter().any(|&c| c > 0.5) && colors.iter().any(|&c| c < 0.2));
+    }
+
+    #[test]
+    fn shading_an_intersection() {
+        // Given
+        let w = default_world();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, w.objects[0].as_ref());
+
+        // When
+        let comps = i.prepare_computations(&r);
+
+        // Then
+        assert_eq!(w.shade_hit(&comps), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn shading_an_intersection_from_the_inside() {
+        // Given
+        let mut w = default_world();
+        w.lights = vec![Box::new(PointLight::new(Point::new(0.0, 0.25, 0.0), Color::new(1.0, 1.0, 1.0)))];
+        let r = Ray::new(Point::new(0.0, 0.0, 0.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(0.5, w.objects[1].as_ref());
+
+        // When
+        let comps = i.prepare_computations(&r);
+
+        // Then
+        assert_eq!(w.shade_hit(&comps), Color::new(0.90498, 0.90498, 0.90498));
+    }
+
+    #[test]
+    fn shade_hit_is_given_an_intersection_in_shadow() {
+        // Given
+        let mut s2 = Sphere::new();
+        s2.set_transform(Matrix4::translation(0.0, 0.0, 10.0));
+        let w = World {
+            objects: vec![Box::new(Sphere::new()), Box::new(s2)],
+            lights: vec![Box::new(PointLight::new(Point::new(0.0, 0.0, -10.0), Color::new(1.0, 1.0, 1.0)))],
+            ..World::default()
+        };
+        let r = Ray::new(Point::new(0.0, 0.0, 5.0), Vector::new(0.0, 0.0, 1.0));
+        let i = Intersection::new(4.0, w.objects[1].as_ref());
+
+        // When
+        let comps = i.prepare_computations(&r);
+
+        // Then
+        assert_eq!(w.shade_hit(&comps), Color::new(0.1, 0.1, 0.1));
+    }
+
+    #[test]
+    fn color_when_a_ray_misses() {
+        // Given
+        let w = default_world();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 1.0, 0.0));
+
+        // Then
+        assert_eq!(w.color_at(&r), Color::new(0.0, 0.0, 0.0));
+    }
+
+    #[test]
+    fn color_when_a_ray_hits() {
+        // Given
+        let w = default_world();
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // Then
+        assert_eq!(w.color_at(&r), Color::new(0.38066, 0.47583, 0.2855));
+    }
+
+    #[test]
+    fn is_shadowed_tests_for_occlusion_between_two_points() {
+        // Given
+        let w = default_world();
+        let light_position = Point::new(-10.0, -10.0, -10.0);
+
+        // Then
+        assert!(!w.is_shadowed(light_position, Point::new(-10.0, -10.0, 10.0)));
+        assert!(w.is_shadowed(light_position, Point::new(10.0, 10.0, 10.0)));
+        assert!(!w.is_shadowed(light_position, Point::new(-20.0, -20.0, -20.0)));
+        assert!(!w.is_shadowed(light_position, Point::new(-5.0, -5.0, -5.0)));
+    }
+
+    #[test]
+    fn fog_dims_what_is_behind_it_and_scatters_light() {
+        // Given
+        let mut w = World::new();
+        let mut glowing = Sphere::new();
+        glowing.set_material(Material { emission: Color::new(1.0, 1.0, 1.0), ambient: 0.0, ..Material::default() });
+        w.add_object(Box::new(glowing));
+        w.fog = Some(Medium::new(Color::new(0.1, 0.2, 0.0), Color::default(), 0.0));
+        let r = Ray::new(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0));
+        let past = Ray::new(Point::new(0.99, 0.99, -5.0), Vector::new(0.0, 0.0, 1.0));
+
+        // When
+        let dimmed = w.color_at(&r);
+        w.fog = Some(Medium::new(Color::default(), Color::new(0.1, 0.1, 0.1), 0.0));
+        let dark = w.color_at(&past);
+        w.lights.push(Box::new(PointLight::new(Point::new(0.0, 3.0, 0.0), Color::new(1.0, 1.0, 1.0))));
+        let lit = w.color_at(&past);
+
+        // Then: the sphere is 4 away through the fog, and the fog ends past it.
+        assert_eq!(dimmed, Color::new((-0.4f64).exp(), (-0.8f64).exp(), 1.0));
+        assert_eq!(w.transmittance(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, -1.0), 2.0, 0.0), Color::new(0.8187, 0.8187, 0.8187));
+        assert_eq!(w.transmittance(Point::new(0.0, 0.0, -5.0), Vector::new(0.0, 0.0, 1.0), 10.0, 0.0), Color::default());
+        assert_eq!(dark, Color::default());
+        assert!(lit.r > 0.01 && lit.r == lit.b, "{:?}", lit);
+    }
+}
\ No newline at end of file